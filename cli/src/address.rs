@@ -0,0 +1,96 @@
+use std::str::FromStr;
+
+use anyhow::{anyhow, Result};
+use bonsol_sdk::{deployment_address, execution_address, execution_claim_address};
+use solana_sdk::pubkey::Pubkey;
+
+use crate::command::AddressKind;
+
+/// Derives the address of a bonsol account the same way the program does, an execution or claim
+/// address needs the requester and execution id and a deployment address needs the image id.
+pub fn derive_address(
+    kind: AddressKind,
+    requester: Option<&str>,
+    execution_id: Option<&str>,
+    image_id: Option<&str>,
+) -> Result<(Pubkey, u8)> {
+    let execution = || -> Result<(Pubkey, u8)> {
+        let (requester, execution_id) = requester
+            .zip(execution_id)
+            .ok_or_else(|| anyhow!("--requester and --execution-id are required"))?;
+        let requester = Pubkey::from_str(requester)
+            .map_err(|_| anyhow!("Invalid requester pubkey '{}'", requester))?;
+        Ok(execution_address(&requester, execution_id.as_bytes()))
+    };
+    match kind {
+        AddressKind::Execution => execution(),
+        AddressKind::Deployment => {
+            let image_id = image_id.ok_or_else(|| anyhow!("--image-id is required"))?;
+            Ok(deployment_address(image_id))
+        }
+        AddressKind::Claim => {
+            let (execution_account, _) = execution()?;
+            Ok(execution_claim_address(execution_account.as_ref()))
+        }
+    }
+}
+
+pub fn address(
+    kind: AddressKind,
+    requester: Option<String>,
+    execution_id: Option<String>,
+    image_id: Option<String>,
+) -> Result<()> {
+    let (address, bump) = derive_address(
+        kind,
+        requester.as_deref(),
+        execution_id.as_deref(),
+        image_id.as_deref(),
+    )?;
+    println!("Address: {}\nBump: {}", address, bump);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const REQUESTER: &str = "BoNsHRcyLLNdtnoDf8hiCNZpyehMC4FDMxs6NTxFi3ew";
+    const IMAGE_ID: &str = "68f4b0c5f9ce034aa60ceb264a18d6c410a3af68fafd931bcfd9ebe7c1e42960";
+
+    fn derive_execution(kind: AddressKind, execution_id: &str) -> (Pubkey, u8) {
+        derive_address(kind, Some(REQUESTER), Some(execution_id), None).unwrap()
+    }
+
+    #[test]
+    fn test_execution_and_claim_addresses() {
+        let requester = Pubkey::from_str(REQUESTER).unwrap();
+        let expected = execution_address(&requester, b"exec-1");
+        let execution = derive_execution(AddressKind::Execution, "exec-1");
+        assert_eq!(execution, expected);
+        assert_eq!(
+            derive_execution(AddressKind::Claim, "exec-1"),
+            execution_claim_address(expected.0.as_ref())
+        );
+        assert_ne!(derive_execution(AddressKind::Execution, "exec-2"), expected);
+    }
+
+    #[test]
+    fn test_deployment_address() {
+        let derived = derive_address(AddressKind::Deployment, None, None, Some(IMAGE_ID));
+        assert_eq!(derived.unwrap(), deployment_address(IMAGE_ID));
+    }
+
+    #[test]
+    fn test_missing_arguments() {
+        let derived = derive_address(AddressKind::Claim, Some(REQUESTER), None, None);
+        assert_eq!(
+            derived.unwrap_err().to_string(),
+            "--requester and --execution-id are required"
+        );
+        let derived = derive_address(AddressKind::Deployment, Some(REQUESTER), None, None);
+        assert_eq!(derived.unwrap_err().to_string(), "--image-id is required");
+        let derived = derive_address(AddressKind::Execution, Some("bad"), Some("e"), None);
+        assert!(derived.is_err());
+    }
+}
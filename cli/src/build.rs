@@ -8,6 +8,7 @@ use cargo_toml::Manifest;
 use indicatif::ProgressBar;
 use risc0_zkvm::compute_image_id;
 use solana_sdk::signer::Signer;
+use tracing::info;
 
 use crate::common::*;
 use crate::error::{BonsolCliError, ZkManifestError};
@@ -36,7 +37,7 @@ pub fn build(keypair: &impl Signer, zk_program_path: String) -> Result<()> {
         Ok(manifest) => {
             serde_json::to_writer_pretty(File::create(&manifest_path)?, &manifest)?;
             bar.finish_and_clear();
-            println!("Build complete");
+            info!("Build complete");
             Ok(())
         }
     }
@@ -1,4 +1,5 @@
-use clap::{command, ArgGroup, Args, Parser, Subcommand};
+use bonsol_prover::prover::ProverKind;
+use clap::{command, ArgGroup, Args, Parser, Subcommand, ValueEnum};
 
 #[derive(Parser, Debug)]
 #[command(version)]
@@ -9,6 +10,14 @@ use clap::{command, ArgGroup, Args, Parser, Subcommand};
         .args(&["config"])
         .conflicts_with("rpc_url")
         .conflicts_with("keypair")
+        .conflicts_with("keypair_env")
+        .multiple(false)
+))]
+#[command(group(
+    // A keypair is read from either a file or an environment variable
+    ArgGroup::new("signer")
+        .required(false)
+        .args(&["keypair", "keypair_env"])
         .multiple(false)
 ))]
 pub struct BonsolCli {
@@ -27,14 +36,31 @@ pub struct BonsolCli {
     )]
     pub keypair: Option<String>,
 
+    #[arg(
+        help = "The environment variable holding a base58 encoded Solana keypair",
+        long,
+        value_name = "VAR",
+        requires = "rpc_url"
+    )]
+    pub keypair_env: Option<String>,
+
     #[arg(
         help = "The Solana cluster the Solana CLI will make requests to",
         short = 'u',
         long,
-        requires = "keypair"
+        requires = "signer"
     )]
     pub rpc_url: Option<String>,
 
+    #[arg(
+        help = "The format of log lines written to stderr",
+        long,
+        value_enum,
+        default_value_t = LogFormat::Text,
+        global = true
+    )]
+    pub log_format: LogFormat,
+
     #[command(subcommand)]
     pub command: Command,
 }
@@ -134,6 +160,34 @@ pub struct SharedDeployArgs {
     pub auto_confirm: bool,
 }
 
+#[derive(Debug, Clone, Copy, Default, ValueEnum)]
+pub enum ProofOutputFormat {
+    /// The risc0 receipt behind a header naming the format and risc0 version
+    #[default]
+    Bin,
+    /// The image id, journal and seals as hex encoded json
+    Json,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
+pub enum LogFormat {
+    /// Human readable lines
+    #[default]
+    Text,
+    /// One json object per line with the timestamp, level, target, message and fields
+    Json,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum AddressKind {
+    /// An execution request, derived from the requester and execution id
+    Execution,
+    /// A program deployment, derived from the image id
+    Deployment,
+    /// The claim on an execution, derived from the requester and execution id
+    Claim,
+}
+
 #[derive(Subcommand, Debug)]
 pub enum Command {
     #[command(
@@ -185,15 +239,35 @@ pub enum Command {
         #[arg(short = 'e', long)]
         execution_id: Option<String>,
 
+        #[arg(
+            help = "Derive the execution id from the image id, inputs, tip and expiry",
+            long,
+            conflicts_with = "execution_id"
+        )]
+        deterministic_id: bool,
+
         #[arg(short = 'x', long)]
         expiry: Option<u64>,
 
+        #[arg(
+            help = "Treat the expiry as the slot the execution expires at, not a slot count",
+            long
+        )]
+        expiry_absolute: bool,
+
         #[arg(short = 'm', long)]
         tip: Option<u64>,
 
         #[arg(short = 'i', long, help = "override inputs in execution request file")]
         input_file: Option<String>,
 
+        #[arg(
+            help = "A directory of inputs named 0.bin, 1.bin, ... with optional <index>.type files",
+            long,
+            conflicts_with = "input_file"
+        )]
+        inputs_dir: Option<String>,
+
         /// wait for execution to be proven
         #[arg(short = 'w', long, help = "wait for execution to be proven")]
         wait: bool,
@@ -201,6 +275,13 @@ pub enum Command {
         /// timeout in seconds
         #[arg(short = 't', long, help = "timeout in seconds")]
         timeout: Option<u64>,
+
+        #[arg(
+            help = "The largest public input downloaded when hashing inputs [default: 10]",
+            long,
+            value_parser = clap::value_parser!(u32).range(1..)
+        )]
+        max_input_size_mb: Option<u32>,
     },
 
     Prove {
@@ -218,6 +299,75 @@ pub enum Command {
 
         #[arg(short = 'o')]
         output_location: Option<String>,
+
+        #[arg(
+            help = "The format the proof is written in",
+            long,
+            value_enum,
+            default_value_t
+        )]
+        output_format: ProofOutputFormat,
+
+        #[arg(
+            help = "The kind of receipt to prove: default, succinct or groth16",
+            long,
+            default_value = "default"
+        )]
+        prover_kind: ProverKind,
+
+        #[arg(help = "Set the maximum number of cycles [default: 268435456]", long)]
+        max_cycles: Option<u64>,
+
+        #[arg(
+            help = "Where memory images are cached [default: <tmp>/bonsol-memory-images]",
+            long
+        )]
+        image_cache_dir: Option<String>,
+    },
+
+    #[command(about = "Verify a proof written by prove against an image id")]
+    Verify {
+        #[arg(help = "The path to a proof written by prove", long)]
+        proof: String,
+
+        #[arg(help = "The image id the proof must be for", long)]
+        image_id: String,
+    },
+
+    #[command(about = "Show whether an execution is pending or the exit code it completed with")]
+    Status {
+        #[arg(help = "The execution id")]
+        execution_id: String,
+
+        #[arg(
+            help = "The public key of the account that requested the execution",
+            long
+        )]
+        requester: String,
+
+        #[arg(help = "Print the status as json", long)]
+        json: bool,
+    },
+
+    #[command(about = "Print the address and bump of a bonsol account")]
+    Address {
+        #[arg(help = "The kind of account", long, value_enum)]
+        kind: AddressKind,
+
+        #[arg(help = "The requester of the execution, for execution and claim", long)]
+        requester: Option<String>,
+
+        #[arg(help = "The execution id, for execution and claim", long)]
+        execution_id: Option<String>,
+
+        #[arg(help = "The image id, for deployment", long)]
+        image_id: Option<String>,
+    },
+
+    #[command(about = "Explain a bonsol program error code")]
+    ExplainError {
+        #[arg(help = "The custom program error code, decimal or 0x prefixed hex")]
+        code: String,
     },
 
     #[command(about = "Initialize a new project")]
@@ -1,19 +1,23 @@
-use std::fs::File;
-use std::path::PathBuf;
+use std::fs::{self, File};
+use std::io::{BufReader, Read};
+use std::path::{Path, PathBuf};
 use std::process::Command;
 use std::str::FromStr;
 
 use anyhow::{Context, Result};
+use base64::prelude::{Engine, BASE64_STANDARD};
 use bonsol_prover::input_resolver::{ProgramInput, ResolvedInput};
 use bonsol_sdk::instructions::CallbackConfig;
-use bonsol_sdk::{InputT, InputType, ProgramInputType};
-use clap::Args;
+use bonsol_sdk::{InputT, InputType, PdaSpec, ProgramInputType};
+use clap::{Args, ValueEnum};
 use rand::distributions::Alphanumeric;
 use rand::Rng;
 use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
 use solana_rpc_client::nonblocking::rpc_client;
 use solana_sdk::instruction::AccountMeta;
 use solana_sdk::pubkey::Pubkey;
+use tracing::info;
 
 use crate::error::{BonsolCliError, ParseConfigError};
 
@@ -59,6 +63,29 @@ pub struct ZkProgramManifest {
 pub struct CliInput {
     pub input_type: String,
     pub data: String, // hex encoded if binary with hex: prefix
+    /// Decodes `data` as is instead of guessing its format from its contents
+    pub encoding: Option<InputEncoding>,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, PartialEq, Eq, ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum InputEncoding {
+    Hex,
+    Base64,
+}
+
+impl InputEncoding {
+    fn decode(self, data: &str) -> Result<Vec<u8>> {
+        match self {
+            InputEncoding::Hex => {
+                let data = data.trim_start_matches("0x");
+                hex::decode(data).context("Invalid hex input data")
+            }
+            InputEncoding::Base64 => BASE64_STANDARD
+                .decode(data)
+                .context("Invalid base64 input data"),
+        }
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -111,6 +138,8 @@ pub struct CliExecutionConfig {
     pub verify_input_hash: Option<bool>,
     pub input_hash: Option<String>,
     pub forward_output: Option<bool>,
+    #[serde(default, with = "bonsol_sdk::instructions::serde_helpers::optpubkey")]
+    pub refund_destination: Option<Pubkey>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
@@ -122,34 +151,85 @@ pub struct CliCallbackConfig {
     pub extra_accounts: Option<Vec<CliAccountMeta>>,
 }
 
-impl From<CliCallbackConfig> for CallbackConfig {
-    fn from(val: CliCallbackConfig) -> Self {
-        CallbackConfig {
+impl TryFrom<CliCallbackConfig> for CallbackConfig {
+    type Error = anyhow::Error;
+
+    fn try_from(val: CliCallbackConfig) -> Result<Self> {
+        Ok(CallbackConfig {
             program_id: val.program_id.unwrap_or_default(),
             instruction_prefix: val.instruction_prefix.unwrap_or_default(),
             extra_accounts: val
                 .extra_accounts
-                .map(|v| v.into_iter().map(|a| a.into()).collect())
-                .unwrap_or_default(),
-        }
+                .unwrap_or_default()
+                .into_iter()
+                .map(AccountMeta::try_from)
+                .collect::<Result<_>>()?,
+        })
     }
 }
 
+/// A callback extra account is either given by `pubkey` or derived from a `pda` spec
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
 #[serde(rename_all = "camelCase")]
 pub struct CliAccountMeta {
     #[serde(default, with = "bonsol_sdk::instructions::serde_helpers::pubkey")]
     pub pubkey: Pubkey,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pda: Option<CliPdaSpec>,
     pub is_signer: bool,
     pub is_writable: bool,
 }
 
-impl From<CliAccountMeta> for AccountMeta {
-    fn from(val: CliAccountMeta) -> Self {
-        AccountMeta {
-            pubkey: val.pubkey,
-            is_signer: val.is_signer,
-            is_writable: val.is_writable,
+impl TryFrom<CliAccountMeta> for AccountMeta {
+    type Error = anyhow::Error;
+
+    fn try_from(val: CliAccountMeta) -> Result<Self> {
+        let Some(pda) = val.pda else {
+            return Ok(AccountMeta {
+                pubkey: val.pubkey,
+                is_signer: val.is_signer,
+                is_writable: val.is_writable,
+            });
+        };
+        if val.pubkey != Pubkey::default() {
+            return Err(anyhow::anyhow!(
+                "Set either pubkey or pda for a callback account"
+            ));
+        }
+        if val.is_signer {
+            return Err(anyhow::anyhow!("A pda callback account cannot be a signer"));
+        }
+        let seeds = pda.seeds.into_iter().map(CliSeed::into_bytes).collect();
+        let spec = PdaSpec::new(pda.program_id, seeds, val.is_writable);
+        let (meta, bump) = spec.resolve()?;
+        info!(address = %meta.pubkey, bump, "Resolved pda callback account");
+        Ok(meta)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CliPdaSpec {
+    #[serde(with = "bonsol_sdk::instructions::serde_helpers::pubkey")]
+    pub program_id: Pubkey,
+    pub seeds: Vec<CliSeed>,
+}
+
+/// A pda seed, written as `{"utf8": "vault"}`, `{"pubkey": "<base58>"}` or `{"bytes": [1, 2]}`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum CliSeed {
+    Utf8(String),
+    Pubkey(#[serde(with = "bonsol_sdk::instructions::serde_helpers::pubkey")] Pubkey),
+    Bytes(Vec<u8>),
+}
+
+impl CliSeed {
+    pub fn into_bytes(self) -> Vec<u8> {
+        match self {
+            CliSeed::Utf8(s) => s.into_bytes(),
+            CliSeed::Pubkey(pubkey) => pubkey.to_bytes().to_vec(),
+            CliSeed::Bytes(bytes) => bytes,
         }
     }
 }
@@ -218,7 +298,17 @@ pub(crate) fn load_solana_config(
     config: Option<String>,
     rpc_url: Option<String>,
     keypair: Option<String>,
+    keypair_env: Option<String>,
 ) -> anyhow::Result<(String, solana_sdk::signer::keypair::Keypair)> {
+    if let Some((rpc_url, var)) = rpc_url.clone().zip(keypair_env) {
+        let keypair = bonsol_sdk::keypair_from_env(&var).map_err(|err| {
+            BonsolCliError::FailedToReadKeypairEnv {
+                var,
+                err: err.to_string(),
+            }
+        })?;
+        return Ok((rpc_url, keypair));
+    }
     let (rpc_url, keypair_file) = match rpc_url.zip(keypair) {
         Some(config) => config,
         None => try_load_from_config(config)?,
@@ -253,15 +343,74 @@ pub fn execute_get_inputs(
     }
 
     if let Some(istr) = inputs_file {
-        let ifile = File::open(istr)?;
-        let parsed: InputFile = serde_json::from_reader(&ifile)
-            .map_err(|e| anyhow::anyhow!("Error parsing inputs file: {:?}", e))?;
-        return Ok(parsed.inputs);
+        return execute_read_inputs(open_inputs_file(&istr)?);
     }
 
     Err(anyhow::anyhow!("No inputs provided"))
 }
 
+/// Parses an inputs file from any reader. Inputs are only ever read front to back, so a fifo or
+/// process substitution works as well as a regular file, with a producer still writing to it.
+pub fn execute_read_inputs(reader: impl Read) -> Result<Vec<CliInput>> {
+    let parsed: InputFile = serde_json::from_reader(BufReader::new(reader))
+        .map_err(|e| anyhow::anyhow!("Error parsing inputs file: {:?}", e))?;
+    Ok(parsed.inputs)
+}
+
+fn open_inputs_file(path: &str) -> Result<File> {
+    File::open(path).with_context(|| format!("Failed to open inputs file {}", path))
+}
+
+/// Loads inputs from a directory holding `0.bin`, `1.bin`, ... with the raw data of each input in
+/// order. Inputs are `PublicData` unless a sidecar `<index>.type` file names another input type,
+/// e.g. `PublicUrl`.
+pub fn execute_get_inputs_dir(dir: &Path) -> Result<Vec<InputT>> {
+    let mut indices = vec![];
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        if !path.extension().is_some_and(|ext| ext == "bin") {
+            continue;
+        }
+        let index = path
+            .file_stem()
+            .and_then(|stem| stem.to_str())
+            .and_then(|stem| stem.parse::<u8>().ok())
+            .ok_or_else(|| {
+                anyhow::anyhow!(
+                    "Invalid input file {}, expected <index>.bin",
+                    path.display()
+                )
+            })?;
+        indices.push(index);
+    }
+    if indices.is_empty() {
+        return Err(anyhow::anyhow!("No inputs found in {}", dir.display()));
+    }
+    indices.sort_unstable();
+    for (expected, index) in indices.iter().enumerate() {
+        if *index as usize != expected {
+            return Err(anyhow::anyhow!(
+                "Input {}.bin is missing from {}, inputs must be numbered from 0 without gaps",
+                expected,
+                dir.display()
+            ));
+        }
+    }
+    indices
+        .into_iter()
+        .map(|index| {
+            let data = fs::read(dir.join(format!("{}.bin", index)))?;
+            let type_path = dir.join(format!("{}.type", index));
+            let input_type = if type_path.exists() {
+                CliInputType::from_str(fs::read_to_string(type_path)?.trim())?.0
+            } else {
+                InputType::PublicData
+            };
+            Ok(InputT::new(input_type, Some(data)))
+        })
+        .collect()
+}
+
 pub fn proof_get_inputs(
     inputs_file: Option<String>,
     stdin: Option<String>,
@@ -270,15 +419,28 @@ pub fn proof_get_inputs(
         return proof_parse_stdin(&std);
     }
     if let Some(istr) = inputs_file {
-        return proof_parse_input_file(&istr);
+        return proof_read_inputs(open_inputs_file(&istr)?);
     }
     Err(anyhow::anyhow!("No inputs provided"))
 }
 
+/// Parses an inputs file for `prove` from any reader, see `execute_read_inputs`
+pub fn proof_read_inputs(mut reader: impl Read) -> Result<Vec<ProgramInput>> {
+    let mut contents = String::new();
+    reader
+        .read_to_string(&mut contents)
+        .context("Failed to read inputs file")?;
+    proof_parse_input_file(&contents)
+}
+
 pub fn execute_transform_cli_inputs(inputs: Vec<CliInput>) -> Result<Vec<InputT>> {
     let mut res = vec![];
     for input in inputs.into_iter() {
         let input_type = CliInputType::from_str(&input.input_type)?.0;
+        if let Some(encoding) = input.encoding {
+            res.push(InputT::new(input_type, Some(encoding.decode(&input.data)?)));
+            continue;
+        }
         match input_type {
             InputType::PublicData => {
                 let has_hex_prefix = input.data.starts_with("0x");
@@ -440,6 +602,31 @@ pub fn rand_id(chars: usize) -> String {
         .collect()
 }
 
+/// Execution id derived from the image, inputs, tip and expiry of a request, so retrying the same
+/// request reuses its execution account instead of paying for a new execution. A relative and an
+/// absolute expiry of the same value are different requests.
+pub fn deterministic_id(
+    image_id: &str,
+    inputs: &[InputT],
+    tip: u64,
+    expiry: u64,
+    expiry_absolute: bool,
+) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update((image_id.len() as u64).to_le_bytes());
+    hasher.update(image_id.as_bytes());
+    for input in inputs {
+        let data = input.data.as_deref().unwrap_or_default();
+        hasher.update([input.input_type.0]);
+        hasher.update((data.len() as u64).to_le_bytes());
+        hasher.update(data);
+    }
+    hasher.update(tip.to_le_bytes());
+    hasher.update(expiry.to_le_bytes());
+    hasher.update([expiry_absolute as u8]);
+    hex::encode(&hasher.finalize()[..8])
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -484,6 +671,65 @@ mod test {
         assert_eq!(inputs_parsed, expected_inputs);
     }
 
+    /// Stands in for a fifo, handing out a few bytes per read and unable to seek
+    struct Pipe {
+        data: Vec<u8>,
+        pos: usize,
+    }
+
+    impl Pipe {
+        fn new(data: &str) -> Self {
+            Pipe {
+                data: data.as_bytes().to_vec(),
+                pos: 0,
+            }
+        }
+    }
+
+    impl Read for Pipe {
+        fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+            let len = buf.len().min(3).min(self.data.len() - self.pos);
+            buf[..len].copy_from_slice(&self.data[self.pos..self.pos + len]);
+            self.pos += len;
+            Ok(len)
+        }
+    }
+
+    const PIPED_INPUTS: &str = r#"{"inputs": [
+        {"inputType": "PublicData", "data": "0x0102"},
+        {"inputType": "Private", "data": "2000"}
+    ]}"#;
+
+    #[test]
+    fn test_execute_inputs_from_pipe() {
+        let inputs = execute_read_inputs(Pipe::new(PIPED_INPUTS)).unwrap();
+        assert_eq!(inputs.len(), 2);
+        assert_eq!(inputs[0].input_type, "PublicData");
+        assert_eq!(inputs[0].data, "0x0102");
+        assert_eq!(inputs[1].data, "2000");
+        assert!(execute_read_inputs(Pipe::new("{\"inputs\": [")).is_err());
+    }
+
+    #[test]
+    fn test_proof_inputs_from_pipe() {
+        let inputs = proof_read_inputs(Pipe::new(PIPED_INPUTS)).unwrap();
+        assert_eq!(
+            inputs,
+            vec![
+                ProgramInput::Resolved(ResolvedInput {
+                    index: 0,
+                    data: vec![1, 2],
+                    input_type: ProgramInputType::Private,
+                }),
+                ProgramInput::Resolved(ResolvedInput {
+                    index: 1,
+                    data: 2000u64.to_le_bytes().to_vec(),
+                    input_type: ProgramInputType::Private,
+                }),
+            ]
+        );
+    }
+
     #[test]
     fn test_is_valid_number() {
         let num = is_valid_number("1234567890abcdef");
@@ -506,22 +752,27 @@ mod test {
         let input = CliInput {
             input_type: "PublicData".to_string(),
             data: "1234567890abcdef".to_string(),
+            encoding: None,
         };
         let hex_input = CliInput {
             input_type: "PublicData".to_string(),
             data: "0x313233343536373839313061626364656667".to_string(),
+            encoding: None,
         };
         let hex_input2 = CliInput {
             input_type: "PublicData".to_string(),
             data: "2.1".to_string(),
+            encoding: None,
         };
         let hex_input3 = CliInput {
             input_type: "PublicData".to_string(),
             data: "2000".to_string(),
+            encoding: None,
         };
         let hex_input4 = CliInput {
             input_type: "PublicData".to_string(),
             data: "-2000".to_string(),
+            encoding: None,
         };
         let inputs = vec![input, hex_input, hex_input2, hex_input3, hex_input4];
         let parsed_inputs = execute_transform_cli_inputs(inputs).unwrap();
@@ -536,4 +787,158 @@ mod test {
             ]
         );
     }
+
+    fn test_data(dir: &str) -> PathBuf {
+        Path::new(env!("CARGO_MANIFEST_DIR"))
+            .join("src")
+            .join("tests")
+            .join("test_data")
+            .join(dir)
+    }
+
+    #[test]
+    fn test_execute_get_inputs_dir() {
+        let inputs = execute_get_inputs_dir(&test_data("inputs_dir")).unwrap();
+        assert_eq!(
+            inputs,
+            vec![
+                InputT::public(b"first".to_vec()),
+                InputT::url(b"https://example.com/input".to_vec()),
+                InputT::public(vec![0, 1, 2, 255]),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_execute_get_inputs_dir_with_gap() {
+        let err = execute_get_inputs_dir(&test_data("inputs_dir_gap")).unwrap_err();
+        assert!(err.to_string().starts_with("Input 1.bin is missing"));
+    }
+
+    fn encoded_input(encoding: &str, data: &str) -> Vec<CliInput> {
+        serde_json::from_value(serde_json::json!([{
+            "inputType": "PublicData",
+            "data": data,
+            "encoding": encoding,
+        }]))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_execute_transform_encoded_inputs() {
+        assert_eq!(
+            execute_transform_cli_inputs(encoded_input("hex", "deadbeef")).unwrap(),
+            vec![InputT::public(vec![0xde, 0xad, 0xbe, 0xef])]
+        );
+        // without an encoding this would be parsed as a number
+        assert_eq!(
+            execute_transform_cli_inputs(encoded_input("hex", "2000")).unwrap(),
+            vec![InputT::public(vec![0x20, 0x00])]
+        );
+        assert_eq!(
+            execute_transform_cli_inputs(encoded_input("base64", "3q2+7w==")).unwrap(),
+            vec![InputT::public(vec![0xde, 0xad, 0xbe, 0xef])]
+        );
+    }
+
+    #[test]
+    fn test_execute_transform_malformed_encoded_inputs() {
+        let err = execute_transform_cli_inputs(encoded_input("hex", "xyz")).unwrap_err();
+        assert_eq!(err.to_string(), "Invalid hex input data");
+        let err = execute_transform_cli_inputs(encoded_input("base64", "!!")).unwrap_err();
+        assert_eq!(err.to_string(), "Invalid base64 input data");
+        let parsed = serde_json::from_value::<Vec<CliInput>>(serde_json::json!([{
+            "inputType": "PublicData",
+            "data": "deadbeef",
+            "encoding": "base32",
+        }]));
+        assert!(parsed.is_err());
+    }
+
+    #[test]
+    fn test_load_keypair_from_env() {
+        let keypair = solana_sdk::signature::Keypair::new();
+        std::env::set_var("BONSOL_CLI_TEST_KEYPAIR", keypair.to_base58_string());
+        let (rpc_url, loaded) = load_solana_config(
+            None,
+            Some("http://localhost:8899".to_string()),
+            None,
+            Some("BONSOL_CLI_TEST_KEYPAIR".to_string()),
+        )
+        .unwrap();
+        assert_eq!(rpc_url, "http://localhost:8899");
+        assert_eq!(loaded.to_bytes(), keypair.to_bytes());
+
+        std::env::set_var("BONSOL_CLI_TEST_MALFORMED_KEYPAIR", "not-a-keypair");
+        let err = load_solana_config(
+            None,
+            Some("http://localhost:8899".to_string()),
+            None,
+            Some("BONSOL_CLI_TEST_MALFORMED_KEYPAIR".to_string()),
+        )
+        .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Failed to read keypair from environment variable 'BONSOL_CLI_TEST_MALFORMED_KEYPAIR': \
+             Invalid keypair: secret is not base58"
+        );
+    }
+
+    #[test]
+    fn test_deterministic_id() {
+        let inputs = vec![InputT::public(b"first".to_vec()), InputT::public(vec![1])];
+        let id = deterministic_id("image", &inputs, 100, 50, false);
+        assert_eq!(id.len(), 16);
+        assert_eq!(deterministic_id("image", &inputs, 100, 50, false), id);
+
+        assert_ne!(deterministic_id("other", &inputs, 100, 50, false), id);
+        assert_ne!(deterministic_id("image", &inputs, 101, 50, false), id);
+        assert_ne!(deterministic_id("image", &inputs, 100, 51, false), id);
+        assert_ne!(deterministic_id("image", &inputs, 100, 50, true), id);
+        assert_ne!(deterministic_id("image", &inputs[..1], 100, 50, false), id);
+        let private = vec![InputT::public(b"first".to_vec()), InputT::private(vec![1])];
+        assert_ne!(deterministic_id("image", &private, 100, 50, false), id);
+    }
+
+    #[test]
+    fn test_pda_callback_account() {
+        let program_id = Pubkey::new_unique();
+        let owner = Pubkey::new_unique();
+        let config: CliCallbackConfig = serde_json::from_value(serde_json::json!({
+            "programId": program_id.to_string(),
+            "extraAccounts": [
+                {
+                    "pda": {
+                        "programId": program_id.to_string(),
+                        "seeds": [{ "utf8": "vault" }, { "pubkey": owner.to_string() }],
+                    },
+                    "isSigner": false,
+                    "isWritable": true,
+                },
+                { "pubkey": owner.to_string(), "isSigner": false, "isWritable": false },
+            ],
+        }))
+        .unwrap();
+        let config = CallbackConfig::try_from(config).unwrap();
+        let (vault, _) = Pubkey::find_program_address(&[b"vault", owner.as_ref()], &program_id);
+        assert_eq!(
+            config.extra_accounts,
+            vec![
+                AccountMeta::new(vault, false),
+                AccountMeta::new_readonly(owner, false)
+            ]
+        );
+    }
+
+    #[test]
+    fn test_pda_callback_account_with_pubkey() {
+        let account: CliAccountMeta = serde_json::from_value(serde_json::json!({
+            "pubkey": Pubkey::new_unique().to_string(),
+            "pda": { "programId": Pubkey::new_unique().to_string(), "seeds": [{ "bytes": [1] }] },
+            "isSigner": false,
+            "isWritable": false,
+        }))
+        .unwrap();
+        assert!(AccountMeta::try_from(account).is_err());
+    }
 }
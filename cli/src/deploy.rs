@@ -6,10 +6,12 @@ use bonsol_sdk::{BonsolClient, ProgramInputType};
 use indicatif::ProgressBar;
 use object_store::aws::AmazonS3Builder;
 use object_store::ObjectStore;
+use sha2::{Digest, Sha256};
 use solana_rpc_client::nonblocking::rpc_client::RpcClient;
 use solana_sdk::commitment_config::CommitmentConfig;
 use solana_sdk::signature::Keypair;
 use solana_sdk::signer::Signer;
+use tracing::info;
 
 use crate::command::{DeployArgs, S3UploadArgs, SharedDeployArgs};
 use crate::common::ZkProgramManifest;
@@ -41,6 +43,7 @@ pub async fn deploy(rpc_url: String, signer: Keypair, deploy_args: DeployArgs) -
             err,
         })
     })?;
+    let sha256: [u8; 32] = Sha256::digest(&loaded_binary).into();
     let url: String = match deploy_args {
         DeployArgs::S3(s3_upload) => {
             let S3UploadArgs {
@@ -96,7 +99,7 @@ pub async fn deploy(rpc_url: String, signer: Keypair, deploy_args: DeployArgs) -
             }
 
             bar.finish_and_clear();
-            println!("Uploaded to S3 url {}", url);
+            info!(%url, "Uploaded to S3");
             url
         }
         DeployArgs::Url(url_upload) => {
@@ -156,6 +159,8 @@ pub async fn deploy(rpc_url: String, signer: Keypair, deploy_args: DeployArgs) -
                             _ => ProgramInputType::Unknown,
                         })
                         .collect(),
+                    Some(sha256),
+                    None,
                 )
                 .await?;
             if let Err(err) = bonsol_client.send_txn_standard(signer, deploy_txn).await {
@@ -17,6 +17,9 @@ pub enum BonsolCliError {
     #[error("Failed to read keypair from file '{file}': {err}")]
     FailedToReadKeypair { file: String, err: String },
 
+    #[error("Failed to read keypair from environment variable '{var}': {err}")]
+    FailedToReadKeypairEnv { var: String, err: String },
+
     #[error("Account '{0}' does not have any SOL to pay for the transaction(s)")]
     InsufficientFunds(String),
 
@@ -3,18 +3,14 @@
 //! to extract the cycle count from an elf.
 
 use anyhow::Result;
+use bonsol_prover::prover::SessionStats;
 use risc0_binfmt::{MemoryImage, Program};
 use risc0_zkvm::{ExecutorEnv, ExecutorImpl, Session, GUEST_MAX_MEM};
 use risc0_zkvm_platform::PAGE_SIZE;
 
 pub fn estimate<E: MkImage>(elf: E, env: ExecutorEnv) -> Result<()> {
     let session = get_session(elf, env)?;
-    println!(
-        "User cycles: {}\nTotal cycles: {}\nSegments: {}",
-        session.user_cycles,
-        session.total_cycles,
-        session.segments.len()
-    );
+    println!("{}", SessionStats::from(&session));
 
     Ok(())
 }
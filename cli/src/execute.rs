@@ -1,18 +1,19 @@
 use crate::common::*;
 use anyhow::Result;
-use bonsol_prover::input_resolver::{DefaultInputResolver, InputResolver, ProgramInput};
-use bonsol_sdk::instructions::{ExecutionConfig, InputRef};
-use bonsol_sdk::{BonsolClient, ExecutionAccountStatus, InputType};
+use bonsol_prover::input_resolver::{DefaultInputResolver, InputResolver};
+use bonsol_sdk::instructions::{CallbackConfig, ExecutionConfig, InputRef};
+use bonsol_sdk::{input_digest, BonsolClient, ExecutionAccountStatus, InputT, InputType};
 use indicatif::ProgressBar;
-use sha2::{Digest, Sha256};
 use solana_rpc_client::nonblocking::rpc_client::RpcClient;
 use solana_sdk::bs58;
 use solana_sdk::commitment_config::CommitmentConfig;
 use solana_sdk::pubkey::Pubkey;
 use solana_sdk::signer::Signer;
 use std::fs::File;
+use std::path::Path;
 use std::sync::Arc;
 use tokio::time::Instant;
+use tracing::info;
 
 pub async fn execution_waiter(
     sdk: &BonsolClient,
@@ -65,14 +66,15 @@ pub async fn execution_waiter(
         }
         interval.tick().await;
         let exec_status = sdk
-            .get_execution_request_v1(&requester, &execution_id)
+            .try_get_execution_request_v1(&requester, &execution_id)
             .await?;
         match exec_status {
-            ExecutionAccountStatus::Completed(ec) => {
+            Some(ExecutionAccountStatus::Completed(ec)) => {
                 indicator.finish_with_message(format!("Execution completed with exit code {}", ec));
                 return Ok(());
             }
-            ExecutionAccountStatus::Pending(_) => {
+            // the rpc node may not have seen the account yet
+            Some(ExecutionAccountStatus::Pending(_)) | None => {
                 indicator.tick();
                 continue;
             }
@@ -80,6 +82,61 @@ pub async fn execution_waiter(
     }
 }
 
+fn input_resolver(rpc_url: String, max_input_size_mb: Option<u32>) -> DefaultInputResolver {
+    let rpc_client = Arc::new(RpcClient::new_with_commitment(
+        rpc_url,
+        CommitmentConfig::confirmed(),
+    ));
+    DefaultInputResolver::new_with_opts(
+        Arc::new(reqwest::Client::new()),
+        rpc_client,
+        max_input_size_mb,
+        None,
+    )
+}
+
+/// The `max_block_height` of an execution, `expiry` is a number of slots from now unless
+/// `expiry_absolute` is set, in which case it must be a slot in the future.
+fn resolve_expiry(expiry: u64, expiry_absolute: bool, current_block: u64) -> Result<u64> {
+    if !expiry_absolute {
+        return Ok(expiry + current_block);
+    }
+    if expiry <= current_block {
+        return Err(anyhow::anyhow!(
+            "Expiry slot {} is not after the current slot {}",
+            expiry,
+            current_block
+        ));
+    }
+    Ok(expiry)
+}
+
+/// The execution id given on the command line or in the request file, otherwise one derived by
+/// `derive` with `--deterministic-id` or a random one. A given id is rejected with
+/// `--deterministic-id` instead of silently replacing the derived one.
+fn resolve_execution_id(
+    execution_id: Option<String>,
+    deterministic_id: bool,
+    derive: impl FnOnce() -> String,
+) -> Result<String> {
+    match (execution_id, deterministic_id) {
+        (Some(_), true) => Err(anyhow::anyhow!(
+            "The execution request sets an execution id, it cannot be used with --deterministic-id"
+        )),
+        (Some(execution_id), false) => Ok(execution_id),
+        (None, true) => Ok(derive()),
+        (None, false) => Ok(rand_id(8)),
+    }
+}
+
+async fn hash_public_inputs(
+    input_resolver: &DefaultInputResolver,
+    inputs: Vec<InputT>,
+) -> Result<Vec<u8>> {
+    let hashing_inputs = input_resolver.resolve_public_inputs(inputs).await?;
+    Ok(input_digest(&hashing_inputs)?)
+}
+
 pub async fn execute(
     sdk: &BonsolClient,
     rpc_url: String,
@@ -87,27 +144,29 @@ pub async fn execute(
     execution_request_file: Option<String>,
     image_id: Option<String>,
     execution_id: Option<String>,
+    deterministic_id: bool,
+    expiry_absolute: bool,
     timeout: Option<u64>,
     inputs_file: Option<String>,
+    inputs_dir: Option<String>,
     tip: Option<u64>,
     expiry: Option<u64>,
     stdin: Option<String>,
     wait: bool,
+    max_input_size_mb: Option<u32>,
 ) -> Result<()> {
     let indicator = ProgressBar::new_spinner();
     let erstr =
         execution_request_file.ok_or(anyhow::anyhow!("Execution request file not provided"))?;
     let erfile = File::open(erstr)?;
     let execution_request_file: ExecutionRequestFile = serde_json::from_reader(erfile)?;
-    let inputs = if let Some(inputs) = execution_request_file.inputs {
-        inputs
+    let transformed_inputs = if let Some(inputs_dir) = inputs_dir {
+        execute_get_inputs_dir(Path::new(&inputs_dir))?
+    } else if let Some(inputs) = execution_request_file.inputs {
+        execute_transform_cli_inputs(inputs)?
     } else {
-        execute_get_inputs(inputs_file, stdin)?
+        execute_transform_cli_inputs(execute_get_inputs(inputs_file, stdin)?)?
     };
-    let execution_id = execution_id
-        .or(execution_request_file.execution_id)
-        .or(Some(rand_id(8)))
-        .ok_or(anyhow::anyhow!("Execution id not provided"))?;
     let image_id = image_id
         .or(execution_request_file.image_id)
         .ok_or(anyhow::anyhow!("Image id not provided"))?;
@@ -117,6 +176,19 @@ pub async fn execute(
     let expiry = expiry
         .or(execution_request_file.expiry)
         .ok_or(anyhow::anyhow!("Expiry not provided"))?;
+    let execution_id = resolve_execution_id(
+        execution_id.or(execution_request_file.execution_id),
+        deterministic_id,
+        || {
+            crate::common::deterministic_id(
+                &image_id,
+                &transformed_inputs,
+                tip,
+                expiry,
+                expiry_absolute,
+            )
+        },
+    )?;
     let callback_config = execution_request_file.callback_config;
     let mut input_hash =
         if let Some(input_hash) = execution_request_file.execution_config.input_hash {
@@ -127,7 +199,6 @@ pub async fn execute(
         };
 
     let signer = keypair.pubkey();
-    let transformed_inputs = execute_transform_cli_inputs(inputs)?;
     let verify_input_hash = execution_request_file
         .execution_config
         .verify_input_hash
@@ -138,24 +209,8 @@ pub async fn execute(
         && transformed_inputs.iter().all(|i| i.input_type != InputType::Private);
     if hash_inputs {
         indicator.set_message("Getting/Hashing inputs");
-        let rpc_client = Arc::new(RpcClient::new_with_commitment(
-            rpc_url.clone(),
-            CommitmentConfig::confirmed(),
-        ));
-        let input_resolver =
-            DefaultInputResolver::new(Arc::new(reqwest::Client::new()), rpc_client);
-        let hashing_inputs = input_resolver
-            .resolve_public_inputs(transformed_inputs.clone())
-            .await?;
-        let mut hash = Sha256::new();
-        for input in hashing_inputs {
-            if let ProgramInput::Resolved(ri) = input {
-                hash.update(&ri.data);
-            } else {
-                return Err(anyhow::anyhow!("Unresolved input"));
-            }
-        }
-        input_hash = hash.finalize().to_vec();
+        let resolver = input_resolver(rpc_url.clone(), max_input_size_mb);
+        input_hash = hash_public_inputs(&resolver, transformed_inputs.clone()).await?;
     }
     let execution_config = ExecutionConfig {
         verify_input_hash,
@@ -164,11 +219,11 @@ pub async fn execute(
             .execution_config
             .forward_output
             .unwrap_or(false),
+        refund_destination: execution_request_file.execution_config.refund_destination,
     };
     let current_block = sdk.get_current_slot().await?;
-    let expiry = expiry + current_block;
-    println!("Execution expiry {}", expiry);
-    println!("current block {}", current_block);
+    let expiry = resolve_expiry(expiry, expiry_absolute, current_block)?;
+    info!(expiry, current_block, "Execution expiry set");
     indicator.set_message("Building transaction");
     let ixs = sdk
         .execute_v1(
@@ -182,8 +237,9 @@ pub async fn execute(
             tip,
             expiry,
             execution_config,
-            callback_config.map(|c| c.into()),
+            callback_config.map(CallbackConfig::try_from).transpose()?,
             None, // A future cli change can implement prover version selection
+            None,
         )
         .await?;
     indicator.finish_with_message("Sending transaction");
@@ -194,3 +250,70 @@ pub async fn execute(
     }
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use sha2::{Digest, Sha256};
+
+    use super::*;
+
+    const DEFAULT_LIMIT_BYTES: usize = 10 * 1024 * 1024;
+
+    async fn hash_large_input(max_input_size_mb: Option<u32>) -> Result<Vec<u8>> {
+        let mut server = mockito::Server::new_async().await;
+        let input = vec![1u8; DEFAULT_LIMIT_BYTES + 1];
+        server
+            .mock("GET", "/input")
+            .with_status(200)
+            .with_body(&input)
+            .create_async()
+            .await;
+        let url = format!("{}/input", server.url());
+        let resolver = input_resolver("http://localhost:8899".to_string(), max_input_size_mb);
+        hash_public_inputs(&resolver, vec![InputT::url(url.into_bytes())]).await
+    }
+
+    #[tokio::test]
+    async fn test_large_input_rejected_by_default() {
+        let err = hash_large_input(None).await.unwrap_err();
+        // the resolver wraps download failures, so only the cause is checked
+        assert!(err.to_string().contains("Max size exceeded"), "{}", err);
+    }
+
+    #[tokio::test]
+    async fn test_large_input_accepted_with_raised_limit() {
+        let hash = hash_large_input(Some(11)).await.unwrap();
+        let expected = Sha256::digest(vec![1u8; DEFAULT_LIMIT_BYTES + 1]);
+        assert_eq!(hash, expected.to_vec());
+    }
+
+    #[test]
+    fn test_resolve_execution_id() {
+        let derived = || "derived".to_string();
+        assert_eq!(
+            resolve_execution_id(Some("given".to_string()), false, derived).unwrap(),
+            "given"
+        );
+        assert_eq!(
+            resolve_execution_id(None, true, derived).unwrap(),
+            "derived"
+        );
+        assert_eq!(resolve_execution_id(None, false, derived).unwrap().len(), 8);
+        assert!(resolve_execution_id(Some("given".to_string()), true, derived).is_err());
+    }
+
+    #[test]
+    fn test_relative_expiry() {
+        assert_eq!(resolve_expiry(100, false, 1000).unwrap(), 1100);
+    }
+
+    #[test]
+    fn test_absolute_expiry() {
+        assert_eq!(resolve_expiry(1100, true, 1000).unwrap(), 1100);
+        let err = resolve_expiry(1000, true, 1000).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Expiry slot 1000 is not after the current slot 1000"
+        );
+    }
+}
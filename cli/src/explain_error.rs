@@ -0,0 +1,59 @@
+use anyhow::{anyhow, Result};
+use bonsol_sdk::ChannelError;
+
+/// Looks up a bonsol program error code as printed by solana, either decimal or the hex form of
+/// `custom program error: 0x1d`.
+pub fn lookup(code: &str) -> Result<ChannelError> {
+    let code = code.trim();
+    let parsed = match code.strip_prefix("0x").or_else(|| code.strip_prefix("0X")) {
+        Some(hex) => u32::from_str_radix(hex, 16),
+        None => code.parse(),
+    }
+    .map_err(|_| anyhow!("Invalid error code '{}'", code))?;
+    ChannelError::from_code(parsed)
+        .ok_or_else(|| anyhow!("{} is not a bonsol program error code", code))
+}
+
+pub fn explain_error(code: String) -> Result<()> {
+    let error = lookup(&code)?;
+    println!(
+        "Code: {} (0x{:x})\nError: {:?}\n{}: {}",
+        error.code(),
+        error.code(),
+        error,
+        error,
+        error.description()
+    );
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_lookup() {
+        assert_eq!(lookup("29").unwrap(), ChannelError::ExecutionExpired);
+        assert_eq!(lookup("0x1d").unwrap(), ChannelError::ExecutionExpired);
+        assert_eq!(
+            lookup(" 0X0 ").unwrap(),
+            ChannelError::InvalidRequesterAccount
+        );
+    }
+
+    #[test]
+    fn test_lookup_unknown_code() {
+        assert_eq!(
+            lookup("100000").unwrap_err().to_string(),
+            "100000 is not a bonsol program error code"
+        );
+        assert_eq!(
+            lookup("39").unwrap_err().to_string(),
+            "39 is not a bonsol program error code"
+        );
+        assert_eq!(
+            lookup("0xzz").unwrap_err().to_string(),
+            "Invalid error code '0xzz'"
+        );
+    }
+}
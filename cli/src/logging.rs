@@ -0,0 +1,89 @@
+use std::io;
+
+use tracing::Subscriber;
+use tracing_subscriber::fmt::{time::UtcTime, MakeWriter};
+use tracing_subscriber::EnvFilter;
+
+use crate::command::LogFormat;
+
+/// Installs the global subscriber writing to stderr so stdout is left to command output,
+/// `RUST_LOG` overrides the default `info` level.
+pub fn init_logging(format: LogFormat) {
+    let filter = EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new("info"));
+    tracing::subscriber::set_global_default(subscriber(format, filter, io::stderr))
+        .expect("logging is initialized once");
+}
+
+fn subscriber<W>(
+    format: LogFormat,
+    filter: EnvFilter,
+    writer: W,
+) -> Box<dyn Subscriber + Send + Sync>
+where
+    W: for<'w> MakeWriter<'w> + Send + Sync + 'static,
+{
+    let builder = tracing_subscriber::fmt()
+        .with_env_filter(filter)
+        .with_writer(writer);
+    match format {
+        LogFormat::Text => Box::new(builder.without_time().with_target(false).finish()),
+        LogFormat::Json => Box::new(
+            builder
+                .json()
+                .flatten_event(true)
+                .with_timer(UtcTime::rfc_3339())
+                .finish(),
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use serde_json::Value;
+    use tracing::{debug, info, warn};
+
+    use super::*;
+
+    #[derive(Clone, Default)]
+    struct Captured(Arc<Mutex<Vec<u8>>>);
+
+    impl io::Write for Captured {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.0.lock().unwrap().extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_json_lines() {
+        let captured = Captured::default();
+        let writer = captured.clone();
+        let make_writer = move || writer.clone();
+        let subscriber = subscriber(LogFormat::Json, EnvFilter::new("info"), make_writer);
+        tracing::subscriber::with_default(subscriber, || {
+            info!(execution_id = "abc", "Generating proof");
+            debug!("Filtered out");
+            warn!("Slow rpc response");
+        });
+
+        let output = String::from_utf8(captured.0.lock().unwrap().clone()).unwrap();
+        let lines: Vec<Value> = output
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        assert_eq!(lines.len(), 2);
+        assert!(lines[0]["timestamp"].is_string());
+        assert_eq!(lines[0]["level"], "INFO");
+        assert_eq!(lines[0]["target"], "bonsol::logging::tests");
+        assert_eq!(lines[0]["message"], "Generating proof");
+        assert_eq!(lines[0]["execution_id"], "abc");
+        assert_eq!(lines[1]["level"], "WARN");
+        assert_eq!(lines[1]["message"], "Slow rpc response");
+    }
+}
@@ -14,12 +14,17 @@ use crate::command::{BonsolCli, Command};
 use crate::common::{execute_get_inputs, load_solana_config, sol_check, ZkProgramManifest};
 use crate::error::{BonsolCliError, ZkManifestError};
 
+mod address;
 mod build;
 mod deploy;
 mod estimate;
 mod execute;
+mod explain_error;
 mod init;
+mod logging;
 mod prove;
+mod status;
+mod verify;
 
 #[cfg(all(test, feature = "integration-tests"))]
 mod tests;
@@ -33,17 +38,20 @@ async fn main() -> anyhow::Result<()> {
     let BonsolCli {
         config,
         keypair,
+        keypair_env,
         rpc_url,
+        log_format,
         command,
     } = BonsolCli::parse();
+    logging::init_logging(log_format);
 
     match command {
         Command::Build { zk_program_path } => build::build(
-            &load_solana_config(config, rpc_url, keypair)?.1,
+            &load_solana_config(config, rpc_url, keypair, keypair_env)?.1,
             zk_program_path,
         ),
         Command::Deploy { deploy_args } => {
-            let (rpc_url, keypair) = load_solana_config(config, rpc_url, keypair)?;
+            let (rpc_url, keypair) = load_solana_config(config, rpc_url, keypair, keypair_env)?;
             if !sol_check(rpc_url.clone(), keypair.pubkey()).await {
                 return Err(BonsolCliError::InsufficientFunds(keypair.pubkey().to_string()).into());
             }
@@ -91,13 +99,17 @@ async fn main() -> anyhow::Result<()> {
             execution_request_file,
             program_id,
             execution_id,
+            deterministic_id,
             expiry,
+            expiry_absolute,
             input_file,
+            inputs_dir,
             wait,
             tip,
             timeout,
+            max_input_size_mb,
         } => {
-            let (rpc_url, keypair) = load_solana_config(config, rpc_url, keypair)?;
+            let (rpc_url, keypair) = load_solana_config(config, rpc_url, keypair, keypair_env)?;
             if !sol_check(rpc_url.clone(), keypair.pubkey()).await {
                 return Err(BonsolCliError::InsufficientFunds(keypair.pubkey().to_string()).into());
             }
@@ -117,12 +129,16 @@ async fn main() -> anyhow::Result<()> {
                 execution_request_file,
                 program_id,
                 execution_id,
+                deterministic_id,
+                expiry_absolute,
                 timeout,
                 input_file,
+                inputs_dir,
                 tip,
                 expiry,
                 stdin,
                 wait,
+                max_input_size_mb,
             )
             .await
         }
@@ -132,8 +148,12 @@ async fn main() -> anyhow::Result<()> {
             input_file,
             execution_id,
             output_location,
+            output_format,
+            prover_kind,
+            max_cycles,
+            image_cache_dir,
         } => {
-            let rpc_url = load_solana_config(config, rpc_url, keypair)?.0;
+            let rpc_url = load_solana_config(config, rpc_url, keypair, keypair_env)?.0;
             let stdin = atty::isnt(Stream::Stdin)
                 .then(|| {
                     let mut buffer = String::new();
@@ -150,10 +170,32 @@ async fn main() -> anyhow::Result<()> {
                 program_id,
                 input_file,
                 output_location,
+                output_format,
+                prover_kind,
+                max_cycles,
+                image_cache_dir,
                 stdin,
             )
             .await
         }
+        Command::Verify { proof, image_id } => verify::verify(proof, image_id),
+        Command::Status {
+            execution_id,
+            requester,
+            json,
+        } => {
+            let rpc_url = load_solana_config(config, rpc_url, keypair, keypair_env)?.0;
+            let sdk = BonsolClient::new(rpc_url);
+
+            status::status(&sdk, requester, execution_id, json).await
+        }
+        Command::Address {
+            kind,
+            requester,
+            execution_id,
+            image_id,
+        } => address::address(kind, requester, execution_id, image_id),
+        Command::ExplainError { code } => explain_error::explain_error(code),
         Command::Init { project_name, dir } => init::init_project(&project_name, dir),
     }
 }
@@ -1,13 +1,59 @@
+use crate::command::ProofOutputFormat;
 use crate::common::{proof_get_inputs, ZkProgramManifest};
 use anyhow::{anyhow, Result};
 use bonsol_prover::image::Image;
-use bonsol_prover::prover::{get_risc0_prover, new_risc0_exec_env};
+use bonsol_prover::image_cache::MemoryImageCache;
+use bonsol_prover::prover::{
+    get_risc0_prover_with, new_risc0_exec_env, run_session, ProverKind, SessionStats,
+};
+use bonsol_prover::receipt_file::serialize_receipt;
 use bonsol_sdk::BonsolClient;
 use bytes::Bytes;
-use risc0_zkvm::VerifierContext;
+use risc0_zkvm::{InnerReceipt, Receipt, VerifierContext};
+use serde::{Deserialize, Serialize};
 use std::fs::{read, File};
 use std::io::Write;
-use std::path::Path;
+use std::path::{Path, PathBuf};
+use tracing::{error, info};
+
+/// A proof written by `prove --output-format json`, every field is hex encoded.
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProofJson {
+    pub image_id: String,
+    /// The outputs committed by the program
+    pub journal: String,
+    /// One seal for a succinct or groth16 receipt, one per segment for a composite receipt. Risc0
+    /// seals are u32 words which are written little endian.
+    pub seals: Vec<String>,
+}
+
+impl ProofJson {
+    pub fn new(image_id: &str, receipt: &Receipt) -> Result<Self> {
+        let seals = match &receipt.inner {
+            InnerReceipt::Composite(r) => {
+                r.segments.iter().map(|s| words_to_hex(&s.seal)).collect()
+            }
+            InnerReceipt::Succinct(r) => vec![words_to_hex(&r.seal)],
+            InnerReceipt::Groth16(r) => vec![hex::encode(&r.seal)],
+            _ => return Err(anyhow!("Receipt has no seal")),
+        };
+        Ok(ProofJson {
+            image_id: image_id.to_string(),
+            journal: hex::encode(&receipt.journal.bytes),
+            seals,
+        })
+    }
+}
+
+fn words_to_hex(words: &[u32]) -> String {
+    hex::encode(
+        words
+            .iter()
+            .flat_map(|w| w.to_le_bytes())
+            .collect::<Vec<u8>>(),
+    )
+}
 
 pub async fn prove(
     sdk: &BonsolClient,
@@ -16,6 +62,10 @@ pub async fn prove(
     program_id: Option<String>,
     input_file: Option<String>,
     output_location: Option<String>,
+    output_format: ProofOutputFormat,
+    prover_kind: ProverKind,
+    max_cycles: Option<u64>,
+    image_cache_dir: Option<String>,
     stdin: Option<String>,
 ) -> Result<()> {
     let pwd = std::env::current_dir()?;
@@ -39,28 +89,46 @@ pub async fn prove(
         }
         _ => Err(anyhow!("Please provide a program id or a manifest path")),
     }?;
-    let ext = Path::new(&execution_id).with_extension("bin");
+    let ext = Path::new(&execution_id).with_extension(match output_format {
+        ProofOutputFormat::Bin => "bin",
+        ProofOutputFormat::Json => "json",
+    });
     let output_binary_path = output_location
         .map(|o| Path::new(&o).join(&ext))
         .unwrap_or(ext);
     let image = Image::from_bytes(image_bytes)?;
-    let memory_image = image.get_memory_image()?;
+    let image_cache_dir = image_cache_dir
+        .map(PathBuf::from)
+        .unwrap_or_else(|| std::env::temp_dir().join("bonsol-memory-images"));
+    let memory_image = MemoryImageCache::new(image_cache_dir)?.get_memory_image(&image)?;
     let program_inputs = proof_get_inputs(input_file, stdin)?;
-    let mut exec = new_risc0_exec_env(memory_image, program_inputs)?;
-    let session = exec.run()?;
-    let prover = get_risc0_prover()?;
-    let ctx = VerifierContext::default();
-    println!("Generating proof");
-    let info = prover.prove_session(&ctx, &session);
+    // executing and proving are cpu bound, the prover is not Send so it is created on the
+    // blocking thread as well
+    let info = tokio::task::spawn_blocking(move || {
+        let mut exec = new_risc0_exec_env(memory_image, program_inputs, max_cycles)?;
+        let session = run_session(&mut exec)?;
+        println!("{}", SessionStats::from(&session));
+        let prover = get_risc0_prover_with(prover_kind)?;
+        let ctx = VerifierContext::default();
+        info!("Generating proof");
+        Ok::<_, anyhow::Error>(prover.prove_session(&ctx, &session))
+    })
+    .await??;
     match info {
         Ok(proveinfo) => {
-            let proof = bincode::serialize(&proveinfo.receipt)?;
+            let proof = match output_format {
+                ProofOutputFormat::Bin => serialize_receipt(&proveinfo.receipt)?,
+                ProofOutputFormat::Json => {
+                    let proof = ProofJson::new(&image.id, &proveinfo.receipt)?;
+                    serde_json::to_vec_pretty(&proof)?
+                }
+            };
             let mut file = File::create(&output_binary_path)?;
             file.write_all(&proof)?;
             println!("Proof written to {}", output_binary_path.to_string_lossy());
         }
         Err(e) => {
-            println!("Error generating proof: {:?}", e);
+            error!("Error generating proof: {:?}", e);
         }
     }
     Ok(())
@@ -0,0 +1,141 @@
+use std::fmt;
+use std::str::FromStr;
+
+use anyhow::{anyhow, Result};
+use bonsol_sdk::{BonsolClient, ExecutionAccountStatus, ExitCode};
+use serde::Serialize;
+use solana_sdk::pubkey::Pubkey;
+
+/// What `bonsol status` reports about an execution account. Once an execution completes the
+/// program shrinks its account to the exit code, the committed outputs only reach the callback.
+#[derive(Debug, PartialEq, Serialize)]
+#[serde(tag = "status", rename_all = "camelCase")]
+pub enum ExecutionReport {
+    #[serde(rename_all = "camelCase")]
+    Pending {
+        max_block_height: u64,
+        /// Hex digest the requester committed the inputs to, if any
+        input_digest: Option<String>,
+    },
+    #[serde(rename_all = "camelCase")]
+    Completed { exit_code: String },
+}
+
+impl ExecutionReport {
+    pub fn completed(exit_code: ExitCode) -> Self {
+        ExecutionReport::Completed {
+            exit_code: exit_code.to_string(),
+        }
+    }
+}
+
+impl From<ExecutionAccountStatus> for ExecutionReport {
+    fn from(status: ExecutionAccountStatus) -> Self {
+        match status {
+            ExecutionAccountStatus::Pending(er) => ExecutionReport::Pending {
+                max_block_height: er.max_block_height,
+                input_digest: er.input_digest.map(hex::encode),
+            },
+            ExecutionAccountStatus::Completed(exit_code) => ExecutionReport::completed(exit_code),
+        }
+    }
+}
+
+impl fmt::Display for ExecutionReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ExecutionReport::Pending {
+                max_block_height,
+                input_digest,
+            } => {
+                write!(
+                    f,
+                    "Status: pending\nExpires after slot: {}",
+                    max_block_height
+                )?;
+                if let Some(input_digest) = input_digest {
+                    write!(f, "\nInput digest: {}", input_digest)?;
+                }
+                Ok(())
+            }
+            ExecutionReport::Completed { exit_code } => {
+                write!(f, "Status: completed\nExit code: {}", exit_code)
+            }
+        }
+    }
+}
+
+pub async fn status(
+    sdk: &BonsolClient,
+    requester: String,
+    execution_id: String,
+    json: bool,
+) -> Result<()> {
+    let requester = Pubkey::from_str(&requester)
+        .map_err(|_| anyhow!("Invalid requester pubkey '{}'", requester))?;
+    let report = ExecutionReport::from(
+        sdk.get_execution_request_v1(&requester, &execution_id)
+            .await?,
+    );
+    if json {
+        println!("{}", serde_json::to_string_pretty(&report)?);
+    } else {
+        println!("{}", report);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use bonsol_sdk::ExecutionRequestV1T;
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn test_pending_report() {
+        let mut er = ExecutionRequestV1T::default();
+        er.max_block_height = 100;
+        er.input_digest = Some(vec![0xab; 32]);
+        let report = ExecutionReport::from(ExecutionAccountStatus::Pending(er));
+        let digest = "ab".repeat(32);
+        assert_eq!(
+            serde_json::to_value(&report).unwrap(),
+            json!({ "status": "pending", "maxBlockHeight": 100, "inputDigest": digest })
+        );
+        assert_eq!(
+            report.to_string(),
+            format!(
+                "Status: pending\nExpires after slot: 100\nInput digest: {}",
+                digest
+            )
+        );
+
+        let report = ExecutionReport::Pending {
+            max_block_height: 100,
+            input_digest: None,
+        };
+        assert_eq!(
+            serde_json::to_value(&report).unwrap(),
+            json!({ "status": "pending", "maxBlockHeight": 100, "inputDigest": null })
+        );
+        assert_eq!(
+            report.to_string(),
+            "Status: pending\nExpires after slot: 100"
+        );
+    }
+
+    #[test]
+    fn test_completed_report() {
+        let report =
+            ExecutionReport::from(ExecutionAccountStatus::Completed(ExitCode::VerifyError));
+        assert_eq!(
+            serde_json::to_value(&report).unwrap(),
+            json!({ "status": "completed", "exitCode": "VerifyError" })
+        );
+        assert_eq!(
+            report.to_string(),
+            "Status: completed\nExit code: VerifyError"
+        );
+    }
+}
@@ -3,6 +3,8 @@ use std::path::Path;
 use assert_cmd::Command;
 
 mod estimate;
+mod prove;
+mod verify;
 
 pub(crate) fn bonsol_cmd() -> Command {
     let mut cmd = Command::cargo_bin("bonsol").unwrap();
@@ -11,7 +13,7 @@ pub(crate) fn bonsol_cmd() -> Command {
     cmd
 }
 
-pub(crate) fn bonsol_build() -> Command {
+pub(crate) fn bonsol_with_config() -> Command {
     let mut cmd = bonsol_cmd();
     let keypair = cmd
         .get_current_dir()
@@ -26,7 +28,12 @@ pub(crate) fn bonsol_build() -> Command {
         keypair.to_str().unwrap(),
         "--rpc-url",
         "http://localhost:8899",
-    ])
-    .arg("build");
+    ]);
+    cmd
+}
+
+pub(crate) fn bonsol_build() -> Command {
+    let mut cmd = bonsol_with_config();
+    cmd.arg("build");
     cmd
 }
@@ -0,0 +1,108 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use assert_cmd::Command;
+use bonsol_prover::receipt_file::deserialize_receipt;
+use hex::FromHex;
+use risc0_zkvm::sha::Digest;
+use sha2::{Digest as _, Sha256};
+
+use super::bonsol_with_config;
+use crate::prove::ProofJson;
+
+pub(crate) const SIMPLE_IMAGE_ID: &str =
+    "68f4b0c5f9ce034aa60ceb264a18d6c410a3af68fafd931bcfd9ebe7c1e42960";
+const SIMPLE_PUBLIC_INPUT: &str = r#"{"attestation":"test"}"#;
+const SIMPLE_PRIVATE_INPUT: &str = "test";
+
+/// A command proving the simple test image into `output_dir`
+fn prove_simple_cmd(output_dir: &Path, execution_id: &str, format: &str) -> Command {
+    let mut cmd = bonsol_with_config();
+    let manifest = cmd
+        .get_current_dir()
+        .unwrap()
+        .join("cli")
+        .join("src")
+        .join("tests")
+        .join("test_data")
+        .join("test_manifest.json");
+    cmd.args(&[
+        "prove",
+        "-m",
+        manifest.to_str().unwrap(),
+        "-e",
+        execution_id,
+        "-o",
+        output_dir.to_str().unwrap(),
+        "--output-format",
+        format,
+    ])
+    .write_stdin(format!("{} {}", SIMPLE_PUBLIC_INPUT, SIMPLE_PRIVATE_INPUT));
+    cmd
+}
+
+/// Proves the simple test image into `output_dir`, returning the path of the written proof
+pub(crate) fn prove_simple(output_dir: &Path, execution_id: &str, format: &str) -> PathBuf {
+    prove_simple_cmd(output_dir, execution_id, format)
+        .assert()
+        .success();
+    output_dir.join(execution_id).with_extension(format)
+}
+
+/// The simple image commits the digest of its inputs followed by whether they matched
+fn simple_journal() -> Vec<u8> {
+    let inputs = format!("{}{}", SIMPLE_PUBLIC_INPUT, SIMPLE_PRIVATE_INPUT);
+    let mut journal = Sha256::digest(inputs.as_bytes()).to_vec();
+    journal.push(1);
+    journal
+}
+
+#[test]
+fn prove_bin_output() {
+    let output_dir = std::env::temp_dir().join("bonsol-prove-test");
+    fs::create_dir_all(&output_dir).unwrap();
+    let proof = prove_simple(&output_dir, "bin-proof", "bin");
+
+    let receipt = deserialize_receipt(&fs::read(proof).unwrap()).unwrap();
+    receipt
+        .verify(Digest::from_hex(SIMPLE_IMAGE_ID).unwrap())
+        .unwrap();
+    assert_eq!(receipt.journal.bytes, simple_journal());
+}
+
+#[test]
+fn prove_json_output() {
+    let output_dir = std::env::temp_dir().join("bonsol-prove-test");
+    fs::create_dir_all(&output_dir).unwrap();
+    let proof = prove_simple(&output_dir, "json-proof", "json");
+
+    let proof: ProofJson = serde_json::from_slice(&fs::read(proof).unwrap()).unwrap();
+    assert_eq!(hex::decode(&proof.journal).unwrap(), simple_journal());
+    assert_eq!(proof.image_id, SIMPLE_IMAGE_ID);
+    assert!(!proof.seals.is_empty());
+}
+
+#[test]
+fn prove_json_logs() {
+    let output_dir = std::env::temp_dir().join("bonsol-prove-test");
+    fs::create_dir_all(&output_dir).unwrap();
+    let mut cmd = prove_simple_cmd(&output_dir, "json-logs", "bin");
+    cmd.args(&["--log-format", "json"]);
+    let output = cmd.assert().success().get_output().clone();
+
+    let lines: Vec<serde_json::Value> = String::from_utf8(output.stderr)
+        .unwrap()
+        .lines()
+        .map(|line| serde_json::from_str(line).unwrap())
+        .collect();
+    // risc0 logs through the same subscriber, only the cli's own lines are checked
+    let prove_lines: Vec<&serde_json::Value> = lines
+        .iter()
+        .filter(|line| line["target"] == "bonsol::prove")
+        .collect();
+    assert_eq!(prove_lines.len(), 1);
+    assert_eq!(prove_lines[0]["message"], "Generating proof");
+    // the result stays on stdout
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(stdout.trim_end().ends_with("json-logs.bin"));
+}
@@ -0,0 +1,38 @@
+use std::fs;
+use std::path::Path;
+
+use bonsol_prover::receipt_file::{deserialize_receipt, serialize_receipt};
+
+use super::bonsol_cmd;
+use super::prove::{prove_simple, SIMPLE_IMAGE_ID};
+
+fn bonsol_verify(proof: &Path) -> assert_cmd::assert::Assert {
+    let mut cmd = bonsol_cmd();
+    cmd.args(&[
+        "verify",
+        "--proof",
+        proof.to_str().unwrap(),
+        "--image-id",
+        SIMPLE_IMAGE_ID,
+    ]);
+    cmd.assert()
+}
+
+#[test]
+fn verify_proof() {
+    let output_dir = std::env::temp_dir().join("bonsol-verify-test");
+    fs::create_dir_all(&output_dir).unwrap();
+    let proof = prove_simple(&output_dir, "verify", "bin");
+
+    bonsol_verify(&proof)
+        .success()
+        .stdout(predicates::str::contains("Proof verified"));
+
+    let mut receipt = deserialize_receipt(&fs::read(&proof).unwrap()).unwrap();
+    receipt.journal.bytes[0] ^= 1;
+    let tampered = output_dir.join("tampered.bin");
+    fs::write(&tampered, serialize_receipt(&receipt).unwrap()).unwrap();
+    bonsol_verify(&tampered)
+        .failure()
+        .stderr(predicates::str::contains("Proof verification failed"));
+}
@@ -0,0 +1,23 @@
+use std::fs;
+
+use anyhow::{anyhow, Result};
+use bonsol_prover::receipt_file::deserialize_receipt;
+use hex::FromHex;
+use risc0_zkvm::sha::Digest;
+
+/// Checks a receipt written by `bonsol prove` against `image_id` without touching the chain, so a
+/// bad proof is caught before paying to submit it.
+pub fn verify(proof_path: String, image_id: String) -> Result<()> {
+    let proof = fs::read(&proof_path)
+        .map_err(|e| anyhow!("Failed to read proof '{}': {}", proof_path, e))?;
+    let receipt = deserialize_receipt(&proof)
+        .map_err(|e| anyhow!("Failed to deserialize proof '{}': {}", proof_path, e))?;
+    let image_id =
+        Digest::from_hex(&image_id).map_err(|_| anyhow!("Invalid image id '{}'", image_id))?;
+    receipt
+        .verify(image_id)
+        .map_err(|e| anyhow!("Proof verification failed: {}", e))?;
+    println!("Proof verified");
+    println!("Journal: {}", hex::encode(&receipt.journal.bytes));
+    Ok(())
+}
@@ -1,8 +1,10 @@
 use {
+    bonsol_prover::prover::DEFAULT_MAX_CYCLES,
     figment::{
         providers::{Format, Toml},
         Figment,
     },
+    risc0_zkvm::PAGE_SIZE,
     serde::{Deserialize, Serialize},
     std::path::Path,
 };
@@ -41,6 +43,13 @@ pub enum MissingImageStrategy {
     Fail,
 }
 
+/// A prover paid part of the execution tip, in proportion to `weight`
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct ProverShareConfig {
+    pub pubkey: String,
+    pub weight: u64,
+}
+
 #[derive(Debug, Deserialize, Serialize, Clone)]
 pub struct ProverNodeConfig {
     pub env: Option<String>,
@@ -50,6 +59,10 @@ pub struct ProverNodeConfig {
     pub risc0_image_folder: String,
     #[serde(default = "default_max_image_size_mb")]
     pub max_image_size_mb: u32,
+    #[serde(default = "default_max_memory_image_mb")]
+    pub max_memory_image_mb: u32,
+    #[serde(default = "default_memory_image_cache_folder")]
+    pub memory_image_cache_folder: String,
     #[serde(default = "default_image_compression_ttl_hours")]
     pub image_compression_ttl_hours: u32,
     #[serde(default = "default_max_input_size_mb")]
@@ -72,6 +85,22 @@ pub struct ProverNodeConfig {
     pub metrics_config: MetricsConfig,
     #[serde(default)]
     pub missing_image_strategy: MissingImageStrategy,
+    #[serde(default = "default_fee_cache_ttl_secs")]
+    pub fee_cache_ttl_secs: u32,
+    #[serde(default = "default_min_blocks_to_prove")]
+    pub min_blocks_to_prove: u64,
+    #[serde(default = "default_max_cycles")]
+    pub max_cycles: u64,
+    #[serde(default)]
+    pub image_allowlist: Vec<String>,
+    #[serde(default)]
+    pub image_denylist: Vec<String>,
+    #[serde(default)]
+    pub dry_run_proofs: bool,
+    #[serde(default)]
+    pub lookup_tables: Vec<String>,
+    #[serde(default)]
+    pub prover_shares: Vec<ProverShareConfig>,
 }
 
 #[derive(Debug, Deserialize, Serialize, Clone)]
@@ -102,10 +131,18 @@ fn default_risc0_image_folder() -> String {
     "./elf".to_string()
 }
 
+fn default_memory_image_cache_folder() -> String {
+    "./memory_images".to_string()
+}
+
 const fn default_max_image_size_mb() -> u32 {
     10
 }
 
+const fn default_max_memory_image_mb() -> u32 {
+    256
+}
+
 const fn default_image_compression_ttl_hours() -> u32 {
     5
 }
@@ -122,6 +159,18 @@ const fn default_input_download_timeout_secs() -> u32 {
     30
 }
 
+const fn default_fee_cache_ttl_secs() -> u32 {
+    3
+}
+
+const fn default_min_blocks_to_prove() -> u64 {
+    20
+}
+
+const fn default_max_cycles() -> u64 {
+    DEFAULT_MAX_CYCLES
+}
+
 const fn default_maximum_concurrent_proofs() -> u32 {
     100
 }
@@ -144,6 +193,13 @@ fn default_signer_config() -> SignerConfig {
     }
 }
 
+impl ProverNodeConfig {
+    /// Most pages a loaded image's memory image may hold
+    pub fn max_image_pages(&self) -> usize {
+        self.max_memory_image_mb as usize * 1024 * 1024 / PAGE_SIZE
+    }
+}
+
 impl Default for ProverNodeConfig {
     fn default() -> Self {
         ProverNodeConfig {
@@ -151,6 +207,8 @@ impl Default for ProverNodeConfig {
             bonsol_program: default_bonsol_program(),
             risc0_image_folder: default_risc0_image_folder(),
             max_image_size_mb: default_max_image_size_mb(),
+            max_memory_image_mb: default_max_memory_image_mb(),
+            memory_image_cache_folder: default_memory_image_cache_folder(),
             image_compression_ttl_hours: default_image_compression_ttl_hours(),
             max_input_size_mb: default_max_input_size_mb(),
             image_download_timeout_secs: default_image_download_timeout_secs(),
@@ -162,6 +220,14 @@ impl Default for ProverNodeConfig {
             stark_compression_tools_path: default_stark_compression_tools_path(),
             metrics_config: default_metrics_config(),
             missing_image_strategy: MissingImageStrategy::default(),
+            fee_cache_ttl_secs: default_fee_cache_ttl_secs(),
+            min_blocks_to_prove: default_min_blocks_to_prove(),
+            max_cycles: default_max_cycles(),
+            image_allowlist: vec![],
+            image_denylist: vec![],
+            dry_run_proofs: false,
+            lookup_tables: vec![],
+            prover_shares: vec![],
         }
     }
 }
@@ -12,7 +12,6 @@ use {
     config::*,
     ingest::{GrpcIngester, Ingester, RpcIngester},
     metrics::counter,
-    metrics_exporter_prometheus::PrometheusBuilder,
     observe::MetricEvents,
     risc0_runner::Risc0Runner,
     rlimit::Resource,
@@ -23,9 +22,14 @@ use {
     tokio::{select, signal},
     tracing::{error, info},
     tracing_subscriber,
-    transaction_sender::{RpcTransactionSender, TransactionSender},
+    transaction_sender::{
+        Metrics, NoopMetrics, RpcTransactionSender, TransactionSender, TransactionStatus,
+    },
 };
 
+#[cfg(feature = "prometheus")]
+use {metrics_exporter_prometheus::PrometheusBuilder, transaction_sender::PrometheusMetrics};
+
 #[derive(Error, Debug)]
 pub enum CliError {
     #[error("Invalid RPC URL")]
@@ -61,13 +65,23 @@ async fn main() -> Result<()> {
     let config_file = &args[2];
     let config = config::load_config(config_file);
     let program = Pubkey::from_str(&config.bonsol_program)?;
-    if let MetricsConfig::Prometheus {} = config.metrics_config {
-        let builder = PrometheusBuilder::new();
-        builder
-            .install()
-            .expect("failed to install prometheus exporter");
-        info!("Prometheus exporter installed");
-    }
+    let metrics: Arc<dyn Metrics> = match config.metrics_config {
+        #[cfg(feature = "prometheus")]
+        MetricsConfig::Prometheus {} => {
+            let builder = PrometheusBuilder::new();
+            builder
+                .install()
+                .expect("failed to install prometheus exporter");
+            info!("Prometheus exporter installed");
+            Arc::new(PrometheusMetrics)
+        }
+        #[cfg(not(feature = "prometheus"))]
+        MetricsConfig::Prometheus {} => {
+            error!("Prometheus metrics require the prometheus feature");
+            Arc::new(NoopMetrics)
+        }
+        MetricsConfig::None => Arc::new(NoopMetrics),
+    };
     emit_event!(MetricEvents::BonsolStartup, up => true);
     //todo use traits for signer
     let signer = match config.signer_config.clone() {
@@ -100,15 +114,50 @@ async fn main() -> Result<()> {
         _ => return Err(CliError::InvalidIngester.into()),
     };
 
-    let (mut transaction_sender, solana_rpc_client) = match config.transaction_sender_config.clone()
+    let (transaction_sender, solana_rpc_client) = match config.transaction_sender_config.clone()
     {
         TransactionSenderConfig::Rpc { rpc_url } => (
-            RpcTransactionSender::new(rpc_url.clone(), program, signer),
+            RpcTransactionSender::new(rpc_url.clone(), program, signer)
+                .with_metrics(metrics)
+                .with_fee_cache_ttl(Duration::from_secs(config.fee_cache_ttl_secs as u64))
+                .with_dry_run_proofs(config.dry_run_proofs),
             RpcClient::new(rpc_url),
         ),
         _ => return Err(CliError::InvalidRpcUrl.into()),
     };
+    let lookup_tables = config
+        .lookup_tables
+        .iter()
+        .map(|t| Pubkey::from_str(t))
+        .collect::<Result<Vec<_>, _>>()?;
+    let lookup_tables = transaction_sender
+        .fetch_lookup_tables(&lookup_tables)
+        .await?;
+    info!("Using {} lookup tables", lookup_tables.len());
+    let prover_shares = config
+        .prover_shares
+        .iter()
+        .map(|s| Ok((Pubkey::from_str(&s.pubkey)?, s.weight)))
+        .collect::<Result<Vec<_>>>()?;
+    let (status_updates, mut status_updates_chan) = tokio::sync::mpsc::unbounded_channel();
+    let mut transaction_sender = transaction_sender
+        .with_lookup_tables(lookup_tables)
+        .with_prover_shares(prover_shares)
+        .with_status_updates(status_updates);
+    tokio::spawn(async move {
+        while let Some(transition) = status_updates_chan.recv().await {
+            match transition.status {
+                TransactionStatus::Confirmed(status) => match status.err {
+                    Some(err) => error!("Transaction {} failed: {:?}", transition.signature, err),
+                    None => info!("Transaction {} confirmed", transition.signature),
+                },
+                TransactionStatus::Expired => info!("Transaction {} expired", transition.signature),
+                TransactionStatus::Pending { .. } => {}
+            }
+        }
+    });
     transaction_sender.start();
+    let transaction_sender = Arc::new(transaction_sender);
     let input_resolver = DefaultInputResolver::new_with_opts(
         Arc::new(reqwest::Client::new()),
         Arc::new(solana_rpc_client),
@@ -121,7 +170,7 @@ async fn main() -> Result<()> {
     let mut runner = Risc0Runner::new(
         config.clone(),
         signer_identity,
-        Arc::new(transaction_sender),
+        transaction_sender.clone(),
         Arc::new(input_resolver),
     )
     .await?;
@@ -135,18 +184,25 @@ async fn main() -> Result<()> {
             }
         }
     });
-    select! {
+    let interrupted = select! {
         e = handle => {
             info!("Runner exited: {:?}", e);
-            let _ = ingester.stop();
-            let _ = runner.stop();
+            false
         },
         _ = signal::ctrl_c() => {
             info!("Received Ctrl-C");
-            exit(1);
+            true
         },
+    };
+    let _ = ingester.stop();
+    let _ = runner.stop();
+    if let Err(e) = transaction_sender.stop().await {
+        error!("Error stopping transaction sender: {:?}", e);
     }
     info!("Exited");
+    if interrupted {
+        exit(1);
+    }
 
     Ok(())
 }
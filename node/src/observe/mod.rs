@@ -26,6 +26,7 @@ pub enum MetricEvents {
     ProofSegments,
     BonsolStartup,
     SignaturesInFlight,
+    ProofsInFlight,
     IncompatibleProverVersion,
     ProofSubmissionError,
     TransactionExpired,
@@ -65,7 +66,7 @@ macro_rules! emit_counter {
 
 macro_rules! emit_gauge {
     ($event:expr, $value:expr, $($field_name:expr => $field_value:expr)*) => {
-        info!(event = $event.to_string(), $($field_name = $field_value),* "{} = {}", $event, $value);
+        info!(event = $event.to_string(), $($field_name = $field_value),*, "{} = {}", $event, $value);
         let g = gauge!("gauges", "gauge" => $event.to_string());
         g.set($value);
     };
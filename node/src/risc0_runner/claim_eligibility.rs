@@ -0,0 +1,66 @@
+use {bonsol_interface::claim_state::ClaimStateV1, thiserror::Error};
+
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum ClaimIneligible {
+    #[error("Execution already claimed until block {0}")]
+    AlreadyClaimed(u64),
+    #[error("Only {remaining} blocks left before expiry, need at least {required}")]
+    TooCloseToExpiry { remaining: u64, required: u64 },
+}
+
+/// Checks an execution is worth claiming before paying for the claim transaction. A claim is
+/// only replaced on chain once its block commitment has passed, and a claim made too close to
+/// `max_block_height` leaves no time to prove.
+pub fn check_claim_eligibility(
+    current_block: u64,
+    max_block_height: u64,
+    claim: Option<&ClaimStateV1>,
+    min_blocks_to_prove: u64,
+) -> Result<(), ClaimIneligible> {
+    if let Some(claim) = claim {
+        if claim.block_commitment >= current_block {
+            return Err(ClaimIneligible::AlreadyClaimed(claim.block_commitment));
+        }
+    }
+    let remaining = max_block_height.saturating_sub(current_block);
+    if remaining < min_blocks_to_prove {
+        return Err(ClaimIneligible::TooCloseToExpiry {
+            remaining,
+            required: min_blocks_to_prove,
+        });
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use {super::*, solana_sdk::pubkey::Pubkey};
+
+    #[test]
+    fn test_unclaimed_execution_is_eligible() {
+        assert_eq!(check_claim_eligibility(100, 200, None, 50), Ok(()));
+    }
+
+    #[test]
+    fn test_already_claimed_is_rejected() {
+        let claim = ClaimStateV1::from_claim_ix(&Pubkey::new_unique(), 90, 150);
+        assert_eq!(
+            check_claim_eligibility(100, 200, Some(&claim), 50),
+            Err(ClaimIneligible::AlreadyClaimed(150))
+        );
+        // a claim whose commitment has passed can be taken over
+        assert_eq!(check_claim_eligibility(151, 300, Some(&claim), 50), Ok(()));
+    }
+
+    #[test]
+    fn test_too_close_to_expiry_is_rejected() {
+        assert_eq!(
+            check_claim_eligibility(180, 200, None, 50),
+            Err(ClaimIneligible::TooCloseToExpiry {
+                remaining: 20,
+                required: 50
+            })
+        );
+        assert_eq!(check_claim_eligibility(150, 200, None, 50), Ok(()));
+    }
+}
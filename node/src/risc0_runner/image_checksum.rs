@@ -0,0 +1,60 @@
+use {
+    sha2::{Digest, Sha256},
+    thiserror::Error,
+};
+
+#[derive(Debug, Error, PartialEq, Eq)]
+#[error("Image sha256 {actual} does not match the deployment's {expected}")]
+pub struct ImageChecksumMismatch {
+    pub expected: String,
+    pub actual: String,
+}
+
+/// Checks a downloaded program against the sha256 stored in its deployment before the much more
+/// expensive image id is computed. Deployments without a sha256 are only checked by image id.
+pub fn check_image_checksum(
+    expected: Option<&[u8]>,
+    program: &[u8],
+) -> Result<(), ImageChecksumMismatch> {
+    let Some(expected) = expected.filter(|e| !e.is_empty()) else {
+        return Ok(());
+    };
+    let actual = Sha256::digest(program);
+    if actual.as_slice() != expected {
+        return Err(ImageChecksumMismatch {
+            expected: hex::encode(expected),
+            actual: hex::encode(actual),
+        });
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PROGRAM: &[u8] = b"not really an elf";
+
+    #[test]
+    fn test_matching_checksum() {
+        let digest = Sha256::digest(PROGRAM);
+        assert_eq!(check_image_checksum(Some(&digest), PROGRAM), Ok(()));
+    }
+
+    #[test]
+    fn test_missing_checksum_is_not_checked() {
+        assert_eq!(check_image_checksum(None, PROGRAM), Ok(()));
+        assert_eq!(check_image_checksum(Some(&[]), PROGRAM), Ok(()));
+    }
+
+    #[test]
+    fn test_mismatched_checksum() {
+        assert_eq!(
+            check_image_checksum(Some(&[0; 32]), PROGRAM),
+            Err(ImageChecksumMismatch {
+                expected: "00".repeat(32),
+                actual: hex::encode(Sha256::digest(PROGRAM)),
+            })
+        );
+    }
+}
@@ -0,0 +1,58 @@
+/// Whether `pattern` matches `image_id`, a trailing `*` matches every image id with that prefix
+fn matches(pattern: &str, image_id: &str) -> bool {
+    match pattern.strip_suffix('*') {
+        Some(prefix) => image_id.starts_with(prefix),
+        None => pattern == image_id,
+    }
+}
+
+/// Checks the operator serves `image_id` before anything is claimed. The denylist wins over the
+/// allowlist, and an empty allowlist permits every image that is not denied.
+pub fn image_permitted(image_id: &str, allowlist: &[String], denylist: &[String]) -> bool {
+    if denylist.iter().any(|p| matches(p, image_id)) {
+        return false;
+    }
+    allowlist.is_empty() || allowlist.iter().any(|p| matches(p, image_id))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const IMAGE_ID: &str = "68f4b0c5f9ce034aa60ceb264a18d6c410a3af68fafd931bcfd9ebe7c1e42960";
+
+    fn patterns(patterns: &[&str]) -> Vec<String> {
+        patterns.iter().map(|p| p.to_string()).collect()
+    }
+
+    #[test]
+    fn test_allowlisted_image_is_permitted() {
+        assert!(image_permitted(IMAGE_ID, &[], &[]));
+        assert!(image_permitted(IMAGE_ID, &patterns(&[IMAGE_ID]), &[]));
+        assert!(image_permitted(
+            IMAGE_ID,
+            &patterns(&["00ff*", "68f4*"]),
+            &[]
+        ));
+        assert!(image_permitted(IMAGE_ID, &patterns(&["*"]), &[]));
+    }
+
+    #[test]
+    fn test_denylisted_image_is_skipped() {
+        assert!(!image_permitted(IMAGE_ID, &[], &patterns(&[IMAGE_ID])));
+        assert!(!image_permitted(IMAGE_ID, &[], &patterns(&["68f4*"])));
+        // the denylist wins over an allowlist entry for the same image
+        assert!(!image_permitted(
+            IMAGE_ID,
+            &patterns(&[IMAGE_ID]),
+            &patterns(&["68*"])
+        ));
+    }
+
+    #[test]
+    fn test_image_not_in_allowlist_is_skipped() {
+        assert!(!image_permitted(IMAGE_ID, &patterns(&["00ff*"]), &[]));
+        // without a trailing wildcard a prefix has to match the whole id
+        assert!(!image_permitted(IMAGE_ID, &patterns(&["68f4"]), &[]));
+    }
+}
@@ -1,3 +1,7 @@
+mod claim_eligibility;
+mod image_checksum;
+mod image_filter;
+mod proof_slots;
 mod utils;
 pub mod verify_prover_version;
 
@@ -35,10 +39,15 @@ use {
     bonsol_interface::bonsol_schema::{parse_ix_data, root_as_deploy_v1, ChannelInstructionIxType},
     bonsol_prover::{
         image::Image,
+        image_cache::MemoryImageCache,
         input_resolver::{InputResolver, ProgramInput},
-        prover::{get_risc0_prover, new_risc0_exec_env},
+        prover::{get_risc0_prover, new_risc0_exec_env, run_session},
         util::get_body_max_size,
     },
+    claim_eligibility::check_claim_eligibility,
+    image_checksum::check_image_checksum,
+    image_filter::image_permitted,
+    proof_slots::ProofSlots,
     risc0_groth16::{ProofJson, Seal},
     risc0_zkvm::{
         recursion::identity_p254,
@@ -97,6 +106,7 @@ pub struct InflightProof {
     pub requester: Pubkey,
     pub program_callback: Option<ProgramExec>,
     pub additional_accounts: Vec<AccountMeta>,
+    pub refund_destination: Option<Pubkey>,
 }
 
 type InflightProofs = Arc<DashMap<String, InflightProof>>;
@@ -117,7 +127,9 @@ pub struct Risc0Runner {
     input_staging_area: InputStagingArea,
     self_identity: Arc<Pubkey>,
     inflight_proofs: InflightProofs,
+    proof_slots: Arc<ProofSlots>,
     input_resolver: Arc<dyn InputResolver + 'static>,
+    memory_image_cache: Arc<MemoryImageCache>,
 }
 
 impl Risc0Runner {
@@ -132,7 +144,7 @@ impl Risc0Runner {
         for entry in dir {
             let entry = entry?;
             if entry.file_type()?.is_file() {
-                let img = Image::new(entry.path()).await?;
+                let img = Image::new_with_max_pages(entry.path(), config.max_image_pages()).await?;
                 info!("Loaded image: {}", &img.id);
                 loaded_images.insert(img.id.clone(), img);
             }
@@ -143,6 +155,8 @@ impl Risc0Runner {
         }
 
         check_stark_compression_tools_path(&config.stark_compression_tools_path)?;
+        let proof_slots = ProofSlots::new(config.maximum_concurrent_proofs as usize);
+        let memory_image_cache = MemoryImageCache::new(&config.memory_image_cache_folder)?;
 
         Ok(Risc0Runner {
             config: Arc::new(config),
@@ -153,7 +167,9 @@ impl Risc0Runner {
             input_staging_area: Arc::new(DashMap::new()),
             self_identity: Arc::new(self_identity),
             inflight_proofs: Arc::new(DashMap::new()),
+            proof_slots: Arc::new(proof_slots),
             input_resolver,
+            memory_image_cache: Arc::new(memory_image_cache),
         })
     }
 
@@ -177,7 +193,9 @@ impl Risc0Runner {
         let self_id = self.self_identity.clone();
         let input_staging_area = self.input_staging_area.clone();
         let inflight_proofs = self.inflight_proofs.clone();
+        let proof_slots = self.proof_slots.clone();
         let txn_sender = self.txn_sender.clone();
+        let gauge_config = config.clone();
         self.inflight_proof_worker_handle = Some(tokio::spawn(async move {
             let mut interval = tokio::time::interval(Duration::from_secs(1));
             loop {
@@ -232,12 +250,19 @@ impl Risc0Runner {
                     };
                     true
                 });
+                let in_flight = proof_slots.in_flight();
+                proof_slots.release_inactive(|execution_id| inflight_proofs.contains_key(execution_id));
+                if proof_slots.in_flight() != in_flight {
+                    emit_proofs_in_flight(&gauge_config, &proof_slots);
+                }
             }
         }));
 
         let inflight_proofs = self.inflight_proofs.clone();
+        let proof_slots = self.proof_slots.clone();
         let txn_sender = self.txn_sender.clone();
         let input_resolver = self.input_resolver.clone();
+        let memory_image_cache = self.memory_image_cache.clone();
         self.worker_handle = Some(tokio::spawn(async move {
             while let Some(bix) = rx.recv().await {
                 let txn_sender = txn_sender.clone();
@@ -248,6 +273,8 @@ impl Risc0Runner {
                 let self_id = self_id.clone();
                 let input_staging_area = input_staging_area.clone();
                 let inflight_proofs = inflight_proofs.clone();
+                let proof_slots = proof_slots.clone();
+                let memory_image_cache = memory_image_cache.clone();
                 tokio::spawn(async move {
                     let bonsol_ix_type =
                         parse_ix_data(&bix.data).map_err(|_| Risc0RunnerError::InvalidData)?;
@@ -290,10 +317,12 @@ impl Risc0Runner {
                             handle_execution_request(
                                 &config,
                                 &inflight_proofs,
+                                &proof_slots,
                                 input_resolver.clone(),
                                 img_client.clone(),
                                 &txn_sender,
                                 &loaded_images,
+                                &memory_image_cache,
                                 &input_staging_area,
                                 bix.last_known_block,
                                 payload,
@@ -312,10 +341,8 @@ impl Risc0Runner {
                                 &config,
                                 &self_id,
                                 &inflight_proofs,
-                                input_resolver.clone(),
+                                &proof_slots,
                                 &txn_sender,
-                                &loaded_images,
-                                &input_staging_area,
                                 payload,
                                 &bix.accounts,
                             )
@@ -348,10 +375,8 @@ pub async fn handle_claim<'a>(
     config: &ProverNodeConfig,
     self_identity: &Pubkey,
     in_flight_proofs: InflightProofRef<'a>,
-    input_resolver: Arc<dyn InputResolver + 'static>,
+    proof_slots: &ProofSlots,
     transaction_sender: &RpcTransactionSender,
-    loaded_images: LoadedImageMapRef<'a>,
-    input_staging_area: InputStagingAreaRef<'a>,
     claim: ClaimV1<'a>,
     accounts: &[Pubkey], // need to create cannonical parsing of accounts per instruction type for my flatbuffer model or use shank
 ) -> Result<()> {
@@ -365,15 +390,36 @@ pub async fn handle_claim<'a>(
                 transaction_sender.clear_signature_status(&claim.claim_signature);
                 emit_event!(MetricEvents::ClaimMissed, execution_id => ifp, signature => &claim.claim_signature.to_string());
             }
+            proof_slots.release(execution_id);
+            emit_proofs_in_flight(config, proof_slots);
         }
         return Ok(());
     }
 
+    // our own claims are proven by handle_execution_request once the claim is confirmed
+    if in_flight_proofs.contains_key(execution_id) {
+        emit_event!(MetricEvents::ClaimReceived, execution_id => execution_id);
+    }
+    Ok(())
+}
+
+/// Proves a claimed execution and submits the proof, the claim must be confirmed first
+#[allow(clippy::too_many_arguments)]
+async fn prove_claimed_execution<'a>(
+    config: &ProverNodeConfig,
+    execution_id: &str,
+    in_flight_proofs: InflightProofRef<'a>,
+    proof_slots: &ProofSlots,
+    input_resolver: Arc<dyn InputResolver + 'static>,
+    transaction_sender: &RpcTransactionSender,
+    loaded_images: LoadedImageMapRef<'a>,
+    memory_image_cache: &MemoryImageCache,
+    input_staging_area: InputStagingAreaRef<'a>,
+) -> Result<()> {
     let claim_status = in_flight_proofs
         .get(execution_id)
         .map(|v| v.value().to_owned());
     if let Some(mut claim) = claim_status {
-        emit_event!(MetricEvents::ClaimReceived, execution_id => execution_id);
         if let ClaimStatus::Claiming = claim.status {
             if let Some(image) = loaded_images.get(&claim.image_id) {
                 if image.data.is_none() {
@@ -409,12 +455,13 @@ pub async fn handle_claim<'a>(
                 let (eid, inputs) = input_staging_area
                     .remove(execution_id)
                     .ok_or(Risc0RunnerError::InvalidData)?;
-                let mem_image = image.get_memory_image()?;
+                let mem_image = memory_image_cache.get_memory_image(&image)?;
+                let max_cycles = config.max_cycles;
                 let result: Result<
                     (Journal, Digest, SuccinctReceipt<ReceiptClaim>),
                     Risc0RunnerError,
                 > = tokio::task::spawn_blocking(move || {
-                    risc0_prove(mem_image, inputs).map_err(|e| {
+                    risc0_prove(mem_image, inputs, max_cycles).map_err(|e| {
                         info!("Error generating proof: {:?}", e);
                         Risc0RunnerError::ProofGenerationError
                     })
@@ -444,6 +491,7 @@ pub async fn handle_claim<'a>(
                                 assumptions_digest.as_bytes(),
                                 committed_outputs,
                                 claim.additional_accounts.clone(),
+                                claim.refund_destination,
                                 compressed_receipt.exit_code_system,
                                 compressed_receipt.exit_code_user,
                             )
@@ -463,6 +511,8 @@ pub async fn handle_claim<'a>(
                     }
                 };
                 in_flight_proofs.remove(&eid);
+                proof_slots.release(&eid);
+                emit_proofs_in_flight(config, proof_slots);
             } else {
                 info!("Image not loaded, fatal error aborting execution");
             }
@@ -474,10 +524,12 @@ pub async fn handle_claim<'a>(
 async fn handle_execution_request<'a>(
     config: &ProverNodeConfig,
     in_flight_proofs: InflightProofRef<'a>,
+    proof_slots: &ProofSlots,
     input_resolver: Arc<dyn InputResolver + 'static>,
     img_client: Arc<reqwest::Client>,
     transaction_sender: &RpcTransactionSender,
     loaded_images: LoadedImageMapRef<'a>,
+    memory_image_cache: &MemoryImageCache,
     input_staging_area: InputStagingAreaRef<'a>,
     _execution_block: u64,
     exec: ExecutionRequestV1<'a>,
@@ -491,11 +543,16 @@ async fn handle_execution_request<'a>(
         emit_event!(MetricEvents::IncompatibleProverVersion, execution_id => exec.execution_id().unwrap_or_default());
         return Ok(());
     }
+    let requested_image = exec.image_id().unwrap_or_default();
+    if !image_permitted(requested_image, &config.image_allowlist, &config.image_denylist) {
+        info!("Image {} is not served by this node, not claiming", requested_image);
+        return Ok(());
+    }
 
-    // current naive implementation is to accept everything we have pending capacity for on this node, but this needs work
-    let inflight = in_flight_proofs.len();
+    // accept everything we have a free proof slot for, the slot is held until the proof is
+    // submitted or the execution is dropped and is given back on any early return before the claim
     emit_event!(MetricEvents::ExecutionRequest, execution_id => exec.execution_id().unwrap_or_default());
-    if inflight < config.maximum_concurrent_proofs as usize {
+    if let Some(permit) = proof_slots.try_acquire() {
         let eid = exec
             .execution_id()
             .map(|d| d.to_string())
@@ -542,6 +599,18 @@ async fn handle_execution_request<'a>(
         }
         .ok_or(Risc0RunnerError::ImgLoadError)?;
 
+        let current_block = transaction_sender.get_current_block().await?;
+        let claim = transaction_sender.get_claim(accounts[2]).await?;
+        if let Err(e) = check_claim_eligibility(
+            current_block,
+            expiry,
+            claim.as_ref(),
+            config.min_blocks_to_prove,
+        ) {
+            info!("Not claiming {}: {}", eid, e);
+            return Ok(());
+        }
+
         // naive compute cost estimate which is YES WE CAN DO THIS in the default amount of time
         emit_histogram!(MetricEvents::ImageComputeEstimate, img.size  as f64, image_id => image_id.clone());
         //ensure compute can happen before expiry
@@ -558,12 +627,12 @@ async fn handle_execution_request<'a>(
                 ).await?
             }, execution_id => eid, stage => "public");
             input_staging_area.insert(eid.clone(), program_inputs);
-            let sig = transaction_sender
-                .claim(&eid, accounts[0], accounts[2], computable_by)
+            let claimed = transaction_sender
+                .claim_and_confirm(&eid, accounts[0], accounts[2], computable_by)
                 .await
                 .map_err(|e| Risc0RunnerError::TransactionError(e.to_string()));
-            match sig {
-                Ok(sig) => {
+            match claimed {
+                Ok((sig, TransactionStatus::Confirmed(status))) if status.err.is_none() => {
                     let callback_program = exec
                         .callback_program_id()
                         .and_then::<[u8; 32], _>(|v| v.bytes().try_into().ok())
@@ -606,20 +675,52 @@ async fn handle_execution_request<'a>(
                                     }
                                 })
                                 .collect(),
+                            refund_destination: exec
+                                .refund_destination()
+                                .and_then::<[u8; 32], _>(|v| v.bytes().try_into().ok())
+                                .map(Pubkey::from),
                         },
                     );
+                    proof_slots.hold(&eid, permit);
+                    emit_proofs_in_flight(config, proof_slots);
                     emit_event!(MetricEvents::ClaimAttempt, execution_id => eid);
+                    prove_claimed_execution(
+                        config,
+                        &eid,
+                        in_flight_proofs,
+                        proof_slots,
+                        input_resolver,
+                        transaction_sender,
+                        loaded_images,
+                        memory_image_cache,
+                        input_staging_area,
+                    )
+                    .await?;
+                }
+                Ok((sig, status)) => {
+                    info!("Claim {} not confirmed: {:?}", sig, status);
+                    transaction_sender.clear_signature_status(&sig);
+                    input_staging_area.remove(&eid);
                 }
                 Err(e) => {
                     info!("Error claiming: {:?}", e);
-                    in_flight_proofs.remove(&eid);
+                    input_staging_area.remove(&eid);
                 }
             }
         }
+    } else {
+        info!(
+            "All {} proof slots in use, not claiming",
+            config.maximum_concurrent_proofs
+        );
     }
     Ok(())
 }
 
+fn emit_proofs_in_flight(config: &ProverNodeConfig, proof_slots: &ProofSlots) {
+    emit_gauge!(MetricEvents::ProofsInFlight, proof_slots.in_flight() as f64, "max" => config.maximum_concurrent_proofs);
+}
+
 async fn load_image<'a>(
     config: &ProverNodeConfig,
     transaction_sender: &RpcTransactionSender,
@@ -655,8 +756,9 @@ async fn handle_image_deployment<'a>(
             let resp_data = get_body_max_size(stream, min)
                 .await
                 .map_err(|_|Risc0RunnerError::ImgTooLarge)?;
+            check_image_checksum(deploy.sha256().map(|s| s.bytes()), &resp_data)?;
 
-            let img = Image::from_bytes(resp_data)?;
+            let img = Image::from_bytes_with_max_pages(resp_data, config.max_image_pages())?;
             if let Some(bytes) = img.bytes() {
                 tokio::fs::write(Path::new(&config.risc0_image_folder).join(img.id.clone()), bytes).await?;
             }
@@ -673,10 +775,11 @@ async fn handle_image_deployment<'a>(
 fn risc0_prove(
     memory_image: MemoryImage,
     sorted_inputs: Vec<ProgramInput>,
+    max_cycles: u64,
 ) -> Result<(Journal, Digest, SuccinctReceipt<ReceiptClaim>)> {
     let image_id = memory_image.compute_id().to_string();
-    let mut exec = new_risc0_exec_env(memory_image, sorted_inputs)?;
-    let session = exec.run()?;
+    let mut exec = new_risc0_exec_env(memory_image, sorted_inputs, Some(max_cycles))?;
+    let session = run_session(&mut exec)?;
     // Obtain the default prover.
     let prover = get_risc0_prover()?;
     let ctx = VerifierContext::default();
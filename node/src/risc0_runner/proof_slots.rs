@@ -0,0 +1,92 @@
+use {
+    dashmap::DashMap,
+    std::sync::Arc,
+    tokio::sync::{OwnedSemaphorePermit, Semaphore},
+};
+
+/// Bounds how many executions go through claim, prove and submit at once. A slot is taken before
+/// the claim is sent and handed back once the proof is submitted or the execution is dropped, so
+/// a saturated node stops claiming instead of piling up proofs it has no memory for.
+pub struct ProofSlots {
+    semaphore: Arc<Semaphore>,
+    held: DashMap<String, OwnedSemaphorePermit>,
+}
+
+impl ProofSlots {
+    pub fn new(max_concurrent_proofs: usize) -> Self {
+        ProofSlots {
+            semaphore: Arc::new(Semaphore::new(max_concurrent_proofs)),
+            held: DashMap::new(),
+        }
+    }
+
+    /// Takes a free slot, or `None` when every slot is in use. Dropping the permit gives the slot
+    /// back, so an execution that is not claimed after all releases it without extra bookkeeping.
+    pub fn try_acquire(&self) -> Option<OwnedSemaphorePermit> {
+        self.semaphore.clone().try_acquire_owned().ok()
+    }
+
+    /// Keeps the slot taken by `permit` until `release` is called for `execution_id`
+    pub fn hold(&self, execution_id: &str, permit: OwnedSemaphorePermit) {
+        self.held.insert(execution_id.to_string(), permit);
+    }
+
+    pub fn release(&self, execution_id: &str) {
+        self.held.remove(execution_id);
+    }
+
+    /// Releases the slots of executions `is_active` no longer reports, e.g. after inflight proofs
+    /// expired or their claim failed
+    pub fn release_inactive(&self, is_active: impl Fn(&str) -> bool) {
+        self.held.retain(|execution_id, _| is_active(execution_id));
+    }
+
+    pub fn in_flight(&self) -> usize {
+        self.held.len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_claims_stop_when_saturated() {
+        let slots = ProofSlots::new(2);
+        let permit = slots.try_acquire().unwrap();
+        slots.hold("a", permit);
+        let permit = slots.try_acquire().unwrap();
+        slots.hold("b", permit);
+        assert_eq!(slots.in_flight(), 2);
+        assert!(slots.try_acquire().is_none());
+
+        // submitting a proof frees its slot for the next claim
+        slots.release("a");
+        assert_eq!(slots.in_flight(), 1);
+        let permit = slots.try_acquire().unwrap();
+        slots.hold("c", permit);
+        assert!(slots.try_acquire().is_none());
+    }
+
+    #[test]
+    fn test_unheld_permit_is_returned_on_drop() {
+        let slots = ProofSlots::new(1);
+        let permit = slots.try_acquire().unwrap();
+        assert!(slots.try_acquire().is_none());
+        drop(permit);
+        assert!(slots.try_acquire().is_some());
+        assert_eq!(slots.in_flight(), 0);
+    }
+
+    #[test]
+    fn test_release_inactive() {
+        let slots = ProofSlots::new(2);
+        let permit = slots.try_acquire().unwrap();
+        slots.hold("expired", permit);
+        let permit = slots.try_acquire().unwrap();
+        slots.hold("proving", permit);
+        slots.release_inactive(|execution_id| execution_id == "proving");
+        assert_eq!(slots.in_flight(), 1);
+        assert!(slots.try_acquire().is_some());
+    }
+}
@@ -0,0 +1,48 @@
+use std::{
+    collections::HashMap,
+    future::Future,
+    time::{Duration, Instant},
+};
+
+use {anyhow::Result, solana_sdk::pubkey::Pubkey, tokio::sync::Mutex};
+
+/// How long a prioritization fee lookup is reused before asking the rpc node again.
+pub const DEFAULT_FEE_CACHE_TTL: Duration = Duration::from_secs(3);
+
+/// Caches prioritization fees per account set for a short ttl. The lock is held while fetching so
+/// concurrent lookups for the same accounts wait for the first one instead of all hitting rpc.
+pub struct FeeCache {
+    ttl: Duration,
+    entries: Mutex<HashMap<Vec<Pubkey>, (Instant, u64)>>,
+}
+
+impl FeeCache {
+    pub fn new(ttl: Duration) -> Self {
+        Self {
+            ttl,
+            entries: Mutex::new(HashMap::new()),
+        }
+    }
+
+    pub async fn get_or_fetch<F, Fut>(&self, accounts: &[Pubkey], fetch: F) -> Result<u64>
+    where
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = Result<u64>>,
+    {
+        let mut entries = self.entries.lock().await;
+        if let Some((fetched_at, fee)) = entries.get(accounts) {
+            if fetched_at.elapsed() < self.ttl {
+                return Ok(*fee);
+            }
+        }
+        let fee = fetch().await?;
+        entries.insert(accounts.to_vec(), (Instant::now(), fee));
+        Ok(fee)
+    }
+}
+
+impl Default for FeeCache {
+    fn default() -> Self {
+        Self::new(DEFAULT_FEE_CACHE_TTL)
+    }
+}
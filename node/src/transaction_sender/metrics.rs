@@ -0,0 +1,39 @@
+use std::time::Duration;
+
+/// Hooks called by `RpcTransactionSender` as transactions move through their lifecycle. Every
+/// method defaults to a no-op so implementations only need to handle what they report on.
+pub trait Metrics: Send + Sync {
+    fn claim_submitted(&self) {}
+    fn proof_submitted(&self) {}
+    fn txn_expired(&self) {}
+    /// Called once per signature with the time between submission and the first confirmed status
+    fn txn_confirmed(&self, _latency: Duration) {}
+}
+
+pub struct NoopMetrics;
+
+impl Metrics for NoopMetrics {}
+
+/// Records through the `metrics` facade, served by the prometheus exporter installed in main.
+#[cfg(feature = "prometheus")]
+pub struct PrometheusMetrics;
+
+#[cfg(feature = "prometheus")]
+impl Metrics for PrometheusMetrics {
+    fn claim_submitted(&self) {
+        metrics::counter!("claims_submitted").increment(1);
+    }
+
+    fn proof_submitted(&self) {
+        metrics::counter!("proofs_submitted").increment(1);
+    }
+
+    fn txn_expired(&self) {
+        metrics::counter!("txns_expired").increment(1);
+    }
+
+    fn txn_confirmed(&self, latency: Duration) {
+        metrics::counter!("txns_confirmed").increment(1);
+        metrics::histogram!("txn_confirmation_latency_ms").record(latency.as_millis() as f64);
+    }
+}
@@ -1,14 +1,26 @@
-use std::sync::Arc;
+mod fee_cache;
+mod metrics;
+
+use std::{
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
 
 use tracing::error;
 
+pub use fee_cache::FeeCache;
+#[cfg(feature = "prometheus")]
+pub use metrics::PrometheusMetrics;
+pub use metrics::{Metrics, NoopMetrics};
+
 use {
     async_trait::async_trait,
     bonsol_interface::{
         bonsol_schema::{
             ChannelInstruction, ChannelInstructionArgs, ChannelInstructionIxType, ClaimV1,
-            ClaimV1Args, StatusTypes, StatusV1, StatusV1Args,
+            ClaimV1Args, ProverShare, StatusTypes, StatusV1, StatusV1Args,
         },
+        claim_state::{ClaimStateV1, ProverShareV1},
         util::{deployment_address, execution_address, execution_claim_address},
     },
     dashmap::DashMap,
@@ -17,7 +29,9 @@ use {
     solana_rpc_client_api::config::RpcSendTransactionConfig,
     solana_sdk::{
         account::Account,
+        address_lookup_table::{state::AddressLookupTable, AddressLookupTableAccount},
         commitment_config::CommitmentConfig,
+        compute_budget::ComputeBudgetInstruction,
         message::{v0, VersionedMessage},
         signature::Signature,
         signer::SignerError,
@@ -25,7 +39,8 @@ use {
         transaction::VersionedTransaction,
     },
     solana_transaction_status::TransactionStatus as TransactionConfirmationStatus,
-    tokio::task::JoinHandle,
+    tokio::{sync::mpsc::UnboundedSender, task::JoinHandle},
+    tokio_util::sync::CancellationToken,
 };
 
 use {
@@ -41,15 +56,94 @@ use {
     tracing::info,
 };
 
+/// Compute unit limit requested by claim transactions, a claim only creates the claim account.
+pub const DEFAULT_CLAIM_COMPUTE_UNIT_LIMIT: u32 = 50_000;
+
+/// Compute unit limit requested by proof submissions, groth16 verification and the callback need
+/// the full transaction budget.
+pub const DEFAULT_SUBMIT_PROOF_COMPUTE_UNIT_LIMIT: u32 = 1_400_000;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TransactionOperation {
+    Claim,
+    SubmitProof,
+}
+
+impl TransactionOperation {
+    pub const fn default_compute_unit_limit(self) -> u32 {
+        match self {
+            TransactionOperation::Claim => DEFAULT_CLAIM_COMPUTE_UNIT_LIMIT,
+            TransactionOperation::SubmitProof => DEFAULT_SUBMIT_PROOF_COMPUTE_UNIT_LIMIT,
+        }
+    }
+}
+
+/// Compute budget instructions for `operation`, an explicit `compute_unit_limit` overrides the
+/// operation's default.
+pub fn create_compute_budget_instructions(
+    operation: TransactionOperation,
+    compute_unit_limit: Option<u32>,
+    compute_unit_price: u64,
+) -> Vec<Instruction> {
+    vec![
+        ComputeBudgetInstruction::set_compute_unit_limit(
+            compute_unit_limit.unwrap_or(operation.default_compute_unit_limit()),
+        ),
+        ComputeBudgetInstruction::set_compute_unit_price(compute_unit_price),
+    ]
+}
+
+/// Median of the recent prioritization fees, zero when the rpc node has no samples
+fn median_fee(mut fees: Vec<u64>) -> u64 {
+    if fees.is_empty() {
+        return 0;
+    }
+    fees.sort_unstable();
+    fees[fees.len() / 2]
+}
+
+/// What a proof submission would have sent, see `RpcTransactionSender::with_dry_run_proofs`
+#[derive(Debug, Clone, PartialEq)]
+pub struct ProofSubmissionSummary {
+    /// Accounts of the bonsol instruction, including any callback extra accounts
+    pub accounts: Vec<AccountMeta>,
+    pub instruction_data_len: usize,
+    pub message_len: usize,
+    pub compute_unit_limit: u32,
+    pub prioritization_fee: u64,
+    pub last_valid_block_height: u64,
+}
+
+#[derive(Debug)]
+pub struct ProofSubmissionDryRun {
+    pub transaction: VersionedTransaction,
+    pub summary: ProofSubmissionSummary,
+}
+
 #[derive(Debug, Clone, PartialEq)]
 pub enum TransactionStatus {
     Pending { expiry: u64 },
     Confirmed(TransactionConfirmationStatus),
+    /// Returned by `claim_and_confirm` when the blockhash expired before the claim landed
+    Expired,
 }
 
+/// Sent to `RpcTransactionSender::with_status_updates` listeners when a tracked signature is first
+/// confirmed or is dropped as expired
+#[derive(Debug, Clone, PartialEq)]
+pub struct StatusTransition {
+    pub signature: Signature,
+    pub status: TransactionStatus,
+}
+
+/// How often signature statuses are refreshed from rpc
+const STATUS_POLL_INTERVAL: Duration = Duration::from_secs(1);
+
 #[async_trait]
 pub trait TransactionSender {
     fn start(&mut self);
+    /// Signals the status loop started by `start` and waits for it to exit
+    async fn stop(&self) -> Result<()>;
     async fn claim(
         &self,
         execution_id: &str,
@@ -68,21 +162,33 @@ pub trait TransactionSender {
         assumption_digest: &[u8],
         committed_outputs: &[u8],
         additional_accounts: Vec<AccountMeta>,
+        refund_destination: Option<Pubkey>,
         exit_code_system: u32,
         exit_code_user: u32,
     ) -> Result<Signature>;
+    /// The current slot, which execution expiries and claim block commitments are measured in
     async fn get_current_block(&self) -> Result<u64>;
     fn get_signature_status(&self, sig: &Signature) -> Option<TransactionStatus>;
     fn clear_signature_status(&self, sig: &Signature);
     async fn get_deployment_account(&self, image_id: &str) -> Result<Account>;
+    /// The current claim on an execution, `None` when it has never been claimed
+    async fn get_claim(&self, execution_account: Pubkey) -> Result<Option<ClaimStateV1>>;
 }
 
 pub struct RpcTransactionSender {
     pub rpc_client: Arc<RpcClient>,
     pub bonsol_program: Pubkey,
     pub signer: Keypair,
-    pub txn_status_handle: Option<JoinHandle<()>>,
+    pub txn_status_handle: Mutex<Option<JoinHandle<()>>>,
+    pub shutdown: CancellationToken,
     pub sigs: Arc<DashMap<Signature, TransactionStatus>>,
+    pub sent_at: Arc<DashMap<Signature, Instant>>,
+    pub metrics: Arc<dyn Metrics>,
+    pub fee_cache: Arc<FeeCache>,
+    pub lookup_tables: Vec<AddressLookupTableAccount>,
+    pub status_updates: Option<UnboundedSender<StatusTransition>>,
+    pub prover_shares: Vec<(Pubkey, u64)>,
+    pub dry_run_proofs: bool,
 }
 
 impl Signer for RpcTransactionSender {
@@ -116,29 +222,107 @@ impl RpcTransactionSender {
             rpc_client: Arc::new(RpcClient::new(rpc_url)),
             signer,
             bonsol_program,
-            txn_status_handle: None,
+            txn_status_handle: Mutex::new(None),
+            shutdown: CancellationToken::new(),
             sigs: Arc::new(DashMap::new()),
+            sent_at: Arc::new(DashMap::new()),
+            metrics: Arc::new(NoopMetrics),
+            fee_cache: Arc::new(FeeCache::default()),
+            lookup_tables: vec![],
+            status_updates: None,
+            prover_shares: vec![],
+            dry_run_proofs: false,
         }
     }
-}
 
-#[async_trait]
-impl TransactionSender for RpcTransactionSender {
-    fn get_signature_status(&self, sig: &Signature) -> Option<TransactionStatus> {
-        self.sigs.get(sig).map(|status| status.value().to_owned())
+    /// Lookup tables used when compiling transactions, so proof submissions with large callback
+    /// account sets stay under the transaction size limit, see `fetch_lookup_tables`
+    pub fn with_lookup_tables(mut self, lookup_tables: Vec<AddressLookupTableAccount>) -> Self {
+        self.lookup_tables = lookup_tables;
+        self
     }
 
-    fn clear_signature_status(&self, sig: &Signature) {
-        self.sigs.remove(sig);
+    /// Loads the lookup table accounts at `addresses` for `with_lookup_tables`
+    pub async fn fetch_lookup_tables(
+        &self,
+        addresses: &[Pubkey],
+    ) -> Result<Vec<AddressLookupTableAccount>> {
+        let mut lookup_tables = Vec::with_capacity(addresses.len());
+        for key in addresses {
+            let account = self
+                .rpc_client
+                .get_account(key)
+                .await
+                .map_err(|e| anyhow::anyhow!("Failed to get lookup table {}: {:?}", key, e))?;
+            let table = AddressLookupTable::deserialize(&account.data)
+                .map_err(|e| anyhow::anyhow!("Invalid lookup table {}: {:?}", key, e))?;
+            lookup_tables.push(AddressLookupTableAccount {
+                key: *key,
+                addresses: table.addresses.to_vec(),
+            });
+        }
+        Ok(lookup_tables)
     }
 
-    async fn claim(
+    /// Sets how long prioritization fee lookups are reused, defaults to `DEFAULT_FEE_CACHE_TTL`
+    pub fn with_fee_cache_ttl(mut self, ttl: Duration) -> Self {
+        self.fee_cache = Arc::new(FeeCache::new(ttl));
+        self
+    }
+
+    /// Prioritization fee for transactions sent to the bonsol program, shared by claims and proof
+    /// submissions through the fee cache.
+    pub async fn prioritization_fee(&self) -> Result<u64> {
+        let accounts = [self.signer.pubkey(), self.bonsol_program];
+        self.fee_cache
+            .get_or_fetch(&accounts, || async {
+                let fees = self
+                    .rpc_client
+                    .get_recent_prioritization_fees(&accounts)
+                    .await
+                    .map_err(|e| anyhow::anyhow!("Failed to get prioritization fees: {:?}", e))?;
+                Ok(median_fee(fees.iter().map(|f| f.prioritization_fee).collect()))
+            })
+            .await
+    }
+
+    pub fn with_metrics(mut self, metrics: Arc<dyn Metrics>) -> Self {
+        self.metrics = metrics;
+        self
+    }
+
+    /// Splits the tip of claimed executions between `prover_shares` by weight instead of paying
+    /// it all to the signer
+    pub fn with_prover_shares(mut self, prover_shares: Vec<(Pubkey, u64)>) -> Self {
+        self.prover_shares = prover_shares;
+        self
+    }
+
+    /// Reports status transitions to `status_updates`, `sigs` stays the source of truth and a
+    /// dropped receiver is ignored
+    pub fn with_status_updates(
+        mut self,
+        status_updates: UnboundedSender<StatusTransition>,
+    ) -> Self {
+        self.status_updates = Some(status_updates);
+        self
+    }
+
+    /// Makes `submit_proof` build, sign and log the proof submission without sending it, so
+    /// callback accounts and payload sizes can be checked before paying for a failed transaction.
+    /// The returned signature is never tracked.
+    pub fn with_dry_run_proofs(mut self, dry_run_proofs: bool) -> Self {
+        self.dry_run_proofs = dry_run_proofs;
+        self
+    }
+
+    fn claim_instruction(
         &self,
         execution_id: &str,
         requester: Pubkey,
         execution_account: Pubkey,
         block_commitment: u64,
-    ) -> Result<Signature> {
+    ) -> Instruction {
         let (execution_claim_account, _) = execution_claim_address(execution_account.as_ref());
         let accounts = vec![
             AccountMeta::new(execution_account, false),
@@ -150,11 +334,20 @@ impl TransactionSender for RpcTransactionSender {
         ];
         let mut fbb = FlatBufferBuilder::new();
         let eid = fbb.create_string(execution_id);
+        let prover_shares = (!self.prover_shares.is_empty()).then(|| {
+            let shares = self
+                .prover_shares
+                .iter()
+                .map(|(prover, weight)| ProverShare::new(*weight, &prover.to_bytes()))
+                .collect_vec();
+            fbb.create_vector(&shares)
+        });
         let stat = ClaimV1::create(
             &mut fbb,
             &ClaimV1Args {
                 block_commitment,
                 execution_id: Some(eid),
+                prover_shares,
             },
         );
         fbb.finish(stat, None);
@@ -171,33 +364,11 @@ impl TransactionSender for RpcTransactionSender {
         );
         fbb2.finish(root, None);
         let ix_data = fbb2.finished_data();
-        let instruction = Instruction::new_with_bytes(self.bonsol_program, ix_data, accounts);
-        let (blockhash_req, last_valid) = self
-            .rpc_client
-            .get_latest_blockhash_with_commitment(self.rpc_client.commitment())
-            .await
-            .map_err(|e| anyhow::anyhow!("Failed to get blockhash: {:?}", e))?;
-
-        let msg =
-            v0::Message::try_compile(&self.signer.pubkey(), &[instruction], &[], blockhash_req)?;
-        let tx = VersionedTransaction::try_new(VersionedMessage::V0(msg), &[&self.signer])?;
-        let sig = self
-            .rpc_client
-            .send_transaction_with_config(
-                &tx,
-                RpcSendTransactionConfig {
-                    skip_preflight: true,
-                    ..Default::default()
-                },
-            )
-            .await
-            .map_err(|e| anyhow::anyhow!("Failed to send transaction: {:?}", e))?;
-        self.sigs
-            .insert(sig, TransactionStatus::Pending { expiry: last_valid });
-        Ok(sig)
+        Instruction::new_with_bytes(self.bonsol_program, ix_data, accounts)
     }
 
-    async fn submit_proof(
+    #[allow(clippy::too_many_arguments)]
+    fn proof_instruction(
         &self,
         execution_id: &str,
         requester_account: Pubkey,
@@ -208,9 +379,11 @@ impl TransactionSender for RpcTransactionSender {
         assumption_digest: &[u8],
         committed_outputs: &[u8],
         additional_accounts: Vec<AccountMeta>,
+        refund_destination: Option<Pubkey>,
+        prover_shares: &[ProverShareV1],
         exit_code_system: u32,
         exit_code_user: u32,
-    ) -> Result<Signature> {
+    ) -> Instruction {
         let (execution_request_data_account, _) =
             execution_address(&requester_account, execution_id.as_bytes());
         let (id, additional_accounts) = match callback_exec {
@@ -229,6 +402,18 @@ impl TransactionSender for RpcTransactionSender {
             AccountMeta::new(self.signer.pubkey(), true),
         ];
         accounts.extend(additional_accounts);
+        if let Some(refund_destination) = refund_destination {
+            accounts.push(AccountMeta::new(refund_destination, false));
+        }
+        // the claim account and every prover the claim split the tip between come last
+        let (execution_claim_account, _) =
+            execution_claim_address(execution_request_data_account.as_ref());
+        accounts.push(AccountMeta::new_readonly(execution_claim_account, false));
+        accounts.extend(
+            prover_shares
+                .iter()
+                .map(|share| AccountMeta::new(Pubkey::from(share.prover), false)),
+        );
         let mut fbb = FlatBufferBuilder::new();
         let proof_vec = fbb.create_vector(proof);
         let execution_digest = fbb.create_vector(execution_digest);
@@ -248,6 +433,7 @@ impl TransactionSender for RpcTransactionSender {
                 committed_outputs: Some(out),               //0-?? bytes lets say 32
                 exit_code_system,                           //4 byte
                 exit_code_user,                             //4 byte
+                dev_mode: risc0_zkvm::is_dev_mode(),        //1 byte
             }, //total ~408 bytes plenty of room for more stuff
         );
         fbb.finish(stat, None);
@@ -264,16 +450,248 @@ impl TransactionSender for RpcTransactionSender {
         );
         fbb2.finish(root, None);
         let ix_data = fbb2.finished_data();
-        let instruction = Instruction::new_with_bytes(self.bonsol_program, ix_data, accounts);
+        Instruction::new_with_bytes(self.bonsol_program, ix_data, accounts)
+    }
+
+    /// Data of the execution's claim account, `None` when it has never been claimed
+    async fn claim_account_data(&self, execution_account: Pubkey) -> Result<Option<Vec<u8>>> {
+        let (execution_claim_account, _) = execution_claim_address(execution_account.as_ref());
+        let account = self
+            .rpc_client
+            .get_account_with_commitment(&execution_claim_account, CommitmentConfig::confirmed())
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to get account: {:?}", e))?
+            .value;
+        Ok(account.map(|a| a.data).filter(|data| !data.is_empty()))
+    }
+
+    /// The provers the claim split the execution's tip between, in the order the program expects
+    /// them after the claim account. Empty when the claimer gets the whole tip.
+    async fn claim_shares(&self, execution_account: Pubkey) -> Result<Vec<ProverShareV1>> {
+        let data = self
+            .claim_account_data(execution_account)
+            .await?
+            .ok_or_else(|| anyhow::anyhow!("Execution has not been claimed"))?;
+        ClaimStateV1::load_shares(&data)
+            .map_err(|e| anyhow::anyhow!("Invalid claim account: {:?}", e))
+    }
+
+    /// Prefixes the instruction with the compute budget for `operation` and signs it against the
+    /// latest blockhash, returning the transaction and the block height it is valid until.
+    async fn compile_transaction(
+        &self,
+        operation: TransactionOperation,
+        instruction: Instruction,
+    ) -> Result<(VersionedTransaction, u64)> {
+        let fee = self.prioritization_fee().await?;
+        let mut instructions = create_compute_budget_instructions(operation, None, fee);
+        instructions.push(instruction);
         let (blockhash, last_valid) = self
             .rpc_client
             .get_latest_blockhash_with_commitment(self.rpc_client.commitment())
             .await
             .map_err(|e| anyhow::anyhow!("Failed to get blockhash: {:?}", e))?;
 
-        let msg = v0::Message::try_compile(&self.signer.pubkey(), &[instruction], &[], blockhash)?;
+        let msg = v0::Message::try_compile(
+            &self.signer.pubkey(),
+            &instructions,
+            &self.lookup_tables,
+            blockhash,
+        )?;
         let tx = VersionedTransaction::try_new(VersionedMessage::V0(msg), &[&self.signer])?;
+        Ok((tx, last_valid))
+    }
+
+    /// Builds the proof submission transaction for `instruction` without sending it
+    async fn dry_run_submit_proof(&self, instruction: Instruction) -> Result<ProofSubmissionDryRun> {
+        let accounts = instruction.accounts.clone();
+        let instruction_data_len = instruction.data.len();
+        let (transaction, last_valid_block_height) = self
+            .compile_transaction(TransactionOperation::SubmitProof, instruction)
+            .await?;
+        let summary = ProofSubmissionSummary {
+            accounts,
+            instruction_data_len,
+            message_len: transaction.message.serialize().len(),
+            compute_unit_limit: TransactionOperation::SubmitProof.default_compute_unit_limit(),
+            prioritization_fee: self.prioritization_fee().await?,
+            last_valid_block_height,
+        };
+        Ok(ProofSubmissionDryRun {
+            transaction,
+            summary,
+        })
+    }
+
+    /// Sends a claim and waits until it is confirmed or its blockhash expires. Confirmed claims
+    /// stay tracked in `sigs` like the ones sent with `claim`, an expired claim is returned as
+    /// `TransactionStatus::Expired`.
+    pub async fn claim_and_confirm(
+        &self,
+        execution_id: &str,
+        requester: Pubkey,
+        execution_account: Pubkey,
+        block_commitment: u64,
+    ) -> Result<(Signature, TransactionStatus)> {
+        let sig = self.claim(execution_id, requester, execution_account, block_commitment).await?;
+        let mut interval = tokio::time::interval(STATUS_POLL_INTERVAL);
+        loop {
+            interval.tick().await;
+            poll_signature_statuses(
+                &self.rpc_client,
+                &self.sigs,
+                &self.sent_at,
+                self.metrics.as_ref(),
+                self.status_updates.as_ref(),
+            )
+            .await;
+            match self.get_signature_status(&sig) {
+                Some(TransactionStatus::Pending { .. }) => continue,
+                Some(status) => return Ok((sig, status)),
+                None => return Ok((sig, TransactionStatus::Expired)),
+            }
+        }
+    }
+
+    fn track(&self, sig: Signature, expiry: u64) {
+        self.sigs.insert(sig, TransactionStatus::Pending { expiry });
+        self.sent_at.insert(sig, Instant::now());
+    }
+}
+
+/// Drops pending signatures whose blockhash has expired and records the status of the rest.
+async fn poll_signature_statuses(
+    rpc_client: &RpcClient,
+    sigs: &DashMap<Signature, TransactionStatus>,
+    sent_at: &DashMap<Signature, Instant>,
+    metrics: &dyn Metrics,
+    status_updates: Option<&UnboundedSender<StatusTransition>>,
+) {
+    let notify = |signature: Signature, status: TransactionStatus| {
+        if let Some(status_updates) = status_updates {
+            let _ = status_updates.send(StatusTransition { signature, status });
+        }
+    };
+    let current_block_height = rpc_client
+        .get_block_height_with_commitment(rpc_client.commitment())
+        .await;
+
+    if let Ok(current_block_height) = current_block_height {
+        sigs.retain(|k, v| {
+            if let TransactionStatus::Pending { expiry } = v {
+                if *expiry < current_block_height {
+                    info!("Transaction expired {}", k);
+                    sent_at.remove(k);
+                    metrics.txn_expired();
+                    notify(*k, TransactionStatus::Expired);
+                    return false;
+                }
+            }
+            true
+        });
+        let all_sigs = sigs.iter().map(|x| *x.key()).collect_vec();
+        let statuses = rpc_client.get_signature_statuses(&all_sigs).await;
+        if let Ok(statuses) = statuses {
+            for sig in all_sigs.into_iter().zip(statuses.value.into_iter()) {
+                if let Some(status) = sig.1 {
+                    if let Some((_, sent)) = sent_at.remove(&sig.0) {
+                        metrics.txn_confirmed(sent.elapsed());
+                    }
+                    let confirmed = TransactionStatus::Confirmed(status);
+                    let previous = sigs.insert(sig.0, confirmed.clone());
+                    if matches!(previous, Some(TransactionStatus::Pending { .. })) {
+                        notify(sig.0, confirmed);
+                    }
+                }
+            }
+        }
+    } else {
+        error!("Failed to get block height");
+    }
+}
+
+#[async_trait]
+impl TransactionSender for RpcTransactionSender {
+    fn get_signature_status(&self, sig: &Signature) -> Option<TransactionStatus> {
+        self.sigs.get(sig).map(|status| status.value().to_owned())
+    }
+
+    fn clear_signature_status(&self, sig: &Signature) {
+        self.sigs.remove(sig);
+        self.sent_at.remove(sig);
+    }
+
+    async fn claim(
+        &self,
+        execution_id: &str,
+        requester: Pubkey,
+        execution_account: Pubkey,
+        block_commitment: u64,
+    ) -> Result<Signature> {
+        let instruction =
+            self.claim_instruction(execution_id, requester, execution_account, block_commitment);
+        let (tx, last_valid) = self
+            .compile_transaction(TransactionOperation::Claim, instruction)
+            .await?;
+        let sig = self
+            .rpc_client
+            .send_transaction_with_config(
+                &tx,
+                RpcSendTransactionConfig {
+                    skip_preflight: true,
+                    ..Default::default()
+                },
+            )
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to send transaction: {:?}", e))?;
+        self.track(sig, last_valid);
+        self.metrics.claim_submitted();
+        Ok(sig)
+    }
 
+    async fn submit_proof(
+        &self,
+        execution_id: &str,
+        requester_account: Pubkey,
+        callback_exec: Option<ProgramExec>,
+        proof: &[u8],
+        execution_digest: &[u8],
+        input_digest: &[u8],
+        assumption_digest: &[u8],
+        committed_outputs: &[u8],
+        additional_accounts: Vec<AccountMeta>,
+        refund_destination: Option<Pubkey>,
+        exit_code_system: u32,
+        exit_code_user: u32,
+    ) -> Result<Signature> {
+        let (execution_account, _) = execution_address(&requester_account, execution_id.as_bytes());
+        let prover_shares = self.claim_shares(execution_account).await?;
+        let instruction = self.proof_instruction(
+            execution_id,
+            requester_account,
+            callback_exec,
+            proof,
+            execution_digest,
+            input_digest,
+            assumption_digest,
+            committed_outputs,
+            additional_accounts,
+            refund_destination,
+            &prover_shares,
+            exit_code_system,
+            exit_code_user,
+        );
+        if self.dry_run_proofs {
+            let dry_run = self.dry_run_submit_proof(instruction).await?;
+            info!(
+                "Dry run, not submitting proof for {}: {:?}",
+                execution_id, dry_run.summary
+            );
+            return Ok(dry_run.transaction.signatures[0]);
+        }
+        let (tx, last_valid) = self
+            .compile_transaction(TransactionOperation::SubmitProof, instruction)
+            .await?;
         let sig = self
             .rpc_client
             .send_and_confirm_transaction_with_spinner_and_config(
@@ -286,51 +704,54 @@ impl TransactionSender for RpcTransactionSender {
             )
             .await
             .map_err(|e| anyhow::anyhow!("Failed to send transaction: {:?}", e))?;
-        self.sigs
-            .insert(sig, TransactionStatus::Pending { expiry: last_valid });
+        self.track(sig, last_valid);
+        self.metrics.proof_submitted();
         Ok(sig)
     }
 
     fn start(&mut self) {
         let sigs_ref = self.sigs.clone();
+        let sent_at = self.sent_at.clone();
+        let metrics = self.metrics.clone();
+        let status_updates = self.status_updates.clone();
         let rpc_client = self.rpc_client.clone();
-        self.txn_status_handle = Some(tokio::spawn(async move {
-            let mut interval = tokio::time::interval(tokio::time::Duration::from_secs(1));
+        self.shutdown = CancellationToken::new();
+        let shutdown = self.shutdown.clone();
+        let handle = tokio::spawn(async move {
+            let mut interval = tokio::time::interval(STATUS_POLL_INTERVAL);
             loop {
-                interval.tick().await;
-                let current_block_height = rpc_client
-                    .get_block_height_with_commitment(rpc_client.commitment())
-                    .await;
-
-                if let Ok(current_block_height) = current_block_height {
-                    sigs_ref.retain(|k, v| {
-                        if let TransactionStatus::Pending { expiry } = v {
-                            if *expiry < current_block_height {
-                                info!("Transaction expired {}", k);
-                                return false;
-                            }
-                        }
-                        true
-                    });
-                    let all_sigs = sigs_ref.iter().map(|x| *x.key()).collect_vec();
-                    let statuses = rpc_client.get_signature_statuses(&all_sigs).await;
-                    if let Ok(statuses) = statuses {
-                        for sig in all_sigs.into_iter().zip(statuses.value.into_iter()) {
-                            if let Some(status) = sig.1 {
-                                sigs_ref.insert(sig.0, TransactionStatus::Confirmed(status));
-                            }
-                        }
-                    }
-                } else {
-                    error!("Failed to get block height");
+                tokio::select! {
+                    _ = shutdown.cancelled() => break,
+                    _ = interval.tick() => {}
                 }
+                poll_signature_statuses(
+                    &rpc_client,
+                    &sigs_ref,
+                    &sent_at,
+                    metrics.as_ref(),
+                    status_updates.as_ref(),
+                )
+                .await;
             }
-        }));
+            info!("Transaction status loop stopped");
+        });
+        *self.txn_status_handle.get_mut().unwrap() = Some(handle);
+    }
+
+    async fn stop(&self) -> Result<()> {
+        self.shutdown.cancel();
+        let handle = self.txn_status_handle.lock().unwrap().take();
+        if let Some(handle) = handle {
+            handle
+                .await
+                .map_err(|e| anyhow::anyhow!("Transaction status loop failed: {:?}", e))?;
+        }
+        Ok(())
     }
 
     async fn get_current_block(&self) -> Result<u64> {
         self.rpc_client
-            .get_block_height()
+            .get_slot()
             .await
             .map_err(|e| anyhow::anyhow!("{:?}", e))
     }
@@ -342,4 +763,611 @@ impl TransactionSender for RpcTransactionSender {
             .await
             .map_err(|e| anyhow::anyhow!("Failed to get account: {:?}", e))
     }
+
+    async fn get_claim(&self, execution_account: Pubkey) -> Result<Option<ClaimStateV1>> {
+        self.claim_account_data(execution_account)
+            .await?
+            .map(|data| {
+                ClaimStateV1::load_claim_owned(&data)
+                    .map_err(|e| anyhow::anyhow!("Invalid claim account: {:?}", e))
+            })
+            .transpose()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::collections::HashMap;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Mutex;
+    use std::time::Duration;
+
+    use serde_json::json;
+    use solana_rpc_client::rpc_client::RpcClientConfig;
+    use solana_rpc_client::rpc_sender::{RpcSender, RpcTransportStats};
+    use solana_rpc_client_api::request::RpcRequest;
+    use solana_sdk::bs58;
+    use solana_sdk::hash::Hash;
+
+    use super::fee_cache::DEFAULT_FEE_CACHE_TTL;
+    use super::*;
+
+    #[derive(Default)]
+    struct RecordingMetrics {
+        claims_submitted: AtomicUsize,
+        proofs_submitted: AtomicUsize,
+        txns_expired: AtomicUsize,
+        txns_confirmed: AtomicUsize,
+    }
+
+    impl Metrics for RecordingMetrics {
+        fn claim_submitted(&self) {
+            self.claims_submitted.fetch_add(1, Ordering::SeqCst);
+        }
+
+        fn proof_submitted(&self) {
+            self.proofs_submitted.fetch_add(1, Ordering::SeqCst);
+        }
+
+        fn txn_expired(&self) {
+            self.txns_expired.fetch_add(1, Ordering::SeqCst);
+        }
+
+        fn txn_confirmed(&self, _latency: Duration) {
+            self.txns_confirmed.fetch_add(1, Ordering::SeqCst);
+        }
+    }
+
+    fn mock_sender(
+        signer: Keypair,
+        mocks: HashMap<RpcRequest, serde_json::Value>,
+        metrics: Arc<RecordingMetrics>,
+    ) -> RpcTransactionSender {
+        RpcTransactionSender {
+            rpc_client: Arc::new(RpcClient::new_mock_with_mocks(
+                "succeeds".to_string(),
+                mocks,
+            )),
+            bonsol_program: bonsol_interface::ID,
+            signer,
+            txn_status_handle: Mutex::new(None),
+            shutdown: CancellationToken::new(),
+            sigs: Arc::new(DashMap::new()),
+            sent_at: Arc::new(DashMap::new()),
+            metrics,
+            fee_cache: Arc::new(FeeCache::default()),
+            lookup_tables: vec![],
+            status_updates: None,
+            prover_shares: vec![],
+            dry_run_proofs: false,
+        }
+    }
+
+    async fn poll(sender: &RpcTransactionSender) {
+        poll_signature_statuses(
+            &sender.rpc_client,
+            &sender.sigs,
+            &sender.sent_at,
+            sender.metrics.as_ref(),
+            sender.status_updates.as_ref(),
+        )
+        .await;
+    }
+
+    /// Mocks for a claim of execution "test" that lands at `block_height`, the blockhash is fixed
+    /// so the claim signature is known up front.
+    fn claim_mocks(
+        signer: &Keypair,
+        requester: Pubkey,
+        block_height: u64,
+    ) -> (HashMap<RpcRequest, serde_json::Value>, Signature) {
+        let (execution_account, _) = execution_address(&requester, b"test");
+        let sender = mock_sender(
+            Keypair::from_bytes(&signer.to_bytes()).unwrap(),
+            HashMap::new(),
+            Arc::default(),
+        );
+        let mut instructions =
+            create_compute_budget_instructions(TransactionOperation::Claim, None, 0);
+        instructions.push(sender.claim_instruction("test", requester, execution_account, 100));
+        let msg = v0::Message::try_compile(&signer.pubkey(), &instructions, &[], Hash::default())
+            .unwrap();
+        let tx = VersionedTransaction::try_new(VersionedMessage::V0(msg), &[signer]).unwrap();
+        let expected = tx.signatures[0];
+
+        let mut mocks = HashMap::new();
+        mocks.insert(RpcRequest::GetRecentPrioritizationFees, json!([]));
+        mocks.insert(
+            RpcRequest::GetLatestBlockhash,
+            json!({
+                "context": { "slot": 1 },
+                "value": {
+                    "blockhash": Hash::default().to_string(),
+                    "lastValidBlockHeight": 150
+                }
+            }),
+        );
+        mocks.insert(RpcRequest::SendTransaction, json!(expected.to_string()));
+        mocks.insert(RpcRequest::GetBlockHeight, json!(block_height));
+        mocks.insert(
+            RpcRequest::GetSignatureStatuses,
+            json!({
+                "context": { "slot": 1 },
+                "value": [{
+                    "slot": 1,
+                    "confirmations": null,
+                    "err": null,
+                    "status": { "Ok": null },
+                    "confirmationStatus": "confirmed"
+                }]
+            }),
+        );
+        (mocks, expected)
+    }
+
+    #[tokio::test]
+    async fn test_claim_records_metrics() {
+        let signer = Keypair::new();
+        let requester = Pubkey::new_unique();
+        let (execution_account, _) = execution_address(&requester, b"test");
+        let (mocks, expected) = claim_mocks(&signer, requester, 0);
+        let metrics = Arc::new(RecordingMetrics::default());
+        let sender = mock_sender(signer, mocks, metrics.clone());
+
+        let sig = sender
+            .claim("test", requester, execution_account, 100)
+            .await
+            .unwrap();
+        assert_eq!(sig, expected);
+        assert_eq!(metrics.claims_submitted.load(Ordering::SeqCst), 1);
+        assert_eq!(metrics.txns_confirmed.load(Ordering::SeqCst), 0);
+
+        poll(&sender).await;
+        assert!(matches!(
+            sender.get_signature_status(&sig),
+            Some(TransactionStatus::Confirmed(_))
+        ));
+        assert_eq!(metrics.txns_confirmed.load(Ordering::SeqCst), 1);
+
+        // later polls keep the confirmed status without counting it again
+        poll(&sender).await;
+        assert_eq!(metrics.txns_confirmed.load(Ordering::SeqCst), 1);
+        assert_eq!(metrics.txns_expired.load(Ordering::SeqCst), 0);
+        assert_eq!(metrics.proofs_submitted.load(Ordering::SeqCst), 0);
+    }
+
+    #[test]
+    fn test_claim_requests_fewer_compute_units_than_proof() {
+        let claim = create_compute_budget_instructions(TransactionOperation::Claim, None, 5);
+        let proof = create_compute_budget_instructions(TransactionOperation::SubmitProof, None, 5);
+        assert_eq!(
+            claim[0],
+            ComputeBudgetInstruction::set_compute_unit_limit(DEFAULT_CLAIM_COMPUTE_UNIT_LIMIT)
+        );
+        assert_eq!(
+            proof[0],
+            ComputeBudgetInstruction::set_compute_unit_limit(
+                DEFAULT_SUBMIT_PROOF_COMPUTE_UNIT_LIMIT
+            )
+        );
+        // the priority fee is shared between operations
+        assert_eq!(claim[1], proof[1]);
+        assert!(
+            TransactionOperation::Claim.default_compute_unit_limit()
+                < TransactionOperation::SubmitProof.default_compute_unit_limit()
+        );
+    }
+
+    #[test]
+    fn test_explicit_compute_unit_limit() {
+        let budget =
+            create_compute_budget_instructions(TransactionOperation::SubmitProof, Some(300_000), 0);
+        assert_eq!(budget[0], ComputeBudgetInstruction::set_compute_unit_limit(300_000));
+    }
+
+    /// Records every request and answers the ones needed to build a transaction
+    struct RecordingSender {
+        requests: Arc<Mutex<Vec<RpcRequest>>>,
+    }
+
+    #[async_trait::async_trait]
+    impl RpcSender for RecordingSender {
+        async fn send(
+            &self,
+            request: RpcRequest,
+            _params: serde_json::Value,
+        ) -> solana_rpc_client_api::client_error::Result<serde_json::Value> {
+            self.requests.lock().unwrap().push(request);
+            match request {
+                RpcRequest::GetRecentPrioritizationFees => Ok(json!([
+                    { "slot": 1, "prioritizationFee": 3 },
+                    { "slot": 2, "prioritizationFee": 7 },
+                    { "slot": 3, "prioritizationFee": 5 }
+                ])),
+                RpcRequest::GetLatestBlockhash => Ok(json!({
+                    "context": { "slot": 1 },
+                    "value": {
+                        "blockhash": Hash::default().to_string(),
+                        "lastValidBlockHeight": 150
+                    }
+                })),
+                RpcRequest::GetVersion => Ok(json!({ "solana-core": "2.0.0" })),
+                // a claim that pays the whole tip to the claimer
+                RpcRequest::GetAccountInfo => Ok(json!({
+                    "context": { "slot": 1 },
+                    "value": {
+                        "data": [
+                            bs58::encode(vec![1; ClaimStateV1::account_size(0)]).into_string(),
+                            "base58"
+                        ],
+                        "executable": false,
+                        "lamports": 1_000_000,
+                        "owner": Pubkey::new_unique().to_string(),
+                        "rentEpoch": 0,
+                        "space": ClaimStateV1::account_size(0)
+                    }
+                })),
+                _ => Ok(serde_json::Value::Null),
+            }
+        }
+
+        fn get_transport_stats(&self) -> RpcTransportStats {
+            RpcTransportStats::default()
+        }
+
+        fn url(&self) -> String {
+            "recording".to_string()
+        }
+    }
+
+    fn recording_sender(ttl: Duration) -> (RpcTransactionSender, Arc<Mutex<Vec<RpcRequest>>>) {
+        let requests = Arc::new(Mutex::new(vec![]));
+        let rpc_client = RpcClient::new_sender(
+            RecordingSender {
+                requests: requests.clone(),
+            },
+            RpcClientConfig::default(),
+        );
+        let sender = RpcTransactionSender {
+            rpc_client: Arc::new(rpc_client),
+            ..mock_sender(Keypair::new(), HashMap::new(), Arc::default())
+        }
+        .with_fee_cache_ttl(ttl);
+        (sender, requests)
+    }
+
+    fn count(requests: &Mutex<Vec<RpcRequest>>, request: RpcRequest) -> usize {
+        requests
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|r| **r == request)
+            .count()
+    }
+
+    #[tokio::test]
+    async fn test_prioritization_fee_is_cached_within_ttl() {
+        let (sender, requests) = recording_sender(Duration::from_secs(60));
+        assert_eq!(sender.prioritization_fee().await.unwrap(), 5);
+        assert_eq!(sender.prioritization_fee().await.unwrap(), 5);
+        assert_eq!(count(&requests, RpcRequest::GetRecentPrioritizationFees), 1);
+    }
+
+    #[tokio::test]
+    async fn test_prioritization_fee_is_refetched_after_ttl() {
+        let (sender, requests) = recording_sender(Duration::ZERO);
+        sender.prioritization_fee().await.unwrap();
+        sender.prioritization_fee().await.unwrap();
+        assert_eq!(count(&requests, RpcRequest::GetRecentPrioritizationFees), 2);
+    }
+
+    #[tokio::test]
+    async fn test_dry_run_submit_proof_does_not_send() {
+        let (sender, requests) = recording_sender(DEFAULT_FEE_CACHE_TTL);
+        let sender = sender.with_dry_run_proofs(true);
+        let callback = ProgramExec {
+            program_id: Pubkey::new_unique(),
+            instruction_prefix: vec![1],
+        };
+        let extra_accounts = vec![
+            AccountMeta::new(Pubkey::new_unique(), false),
+            AccountMeta::new_readonly(Pubkey::new_unique(), false),
+        ];
+        let requester = Pubkey::new_unique();
+        let sig = sender
+            .submit_proof(
+                "test",
+                requester,
+                Some(callback.clone()),
+                &[0; 256],
+                &[1; 32],
+                &[2; 32],
+                &[3; 32],
+                b"output",
+                extra_accounts.clone(),
+                None,
+                0,
+                0,
+            )
+            .await
+            .unwrap();
+        assert_eq!(count(&requests, RpcRequest::GetAccountInfo), 1);
+        assert_eq!(count(&requests, RpcRequest::SendTransaction), 0);
+        assert!(sender.get_signature_status(&sig).is_none());
+
+        let instruction = sender.proof_instruction(
+            "test",
+            requester,
+            Some(callback),
+            &[0; 256],
+            &[1; 32],
+            &[2; 32],
+            &[3; 32],
+            b"output",
+            extra_accounts.clone(),
+            None,
+            &[],
+            0,
+            0,
+        );
+        let dry_run = sender.dry_run_submit_proof(instruction).await.unwrap();
+        assert!(dry_run
+            .transaction
+            .verify_with_results()
+            .iter()
+            .all(|ok| *ok));
+        assert_eq!(dry_run.summary.accounts.len(), 7);
+        assert_eq!(dry_run.summary.accounts[4..6], extra_accounts[..]);
+        assert!(!dry_run.summary.accounts[6].is_writable);
+        assert_eq!(
+            dry_run.summary.compute_unit_limit,
+            DEFAULT_SUBMIT_PROOF_COMPUTE_UNIT_LIMIT
+        );
+        assert_eq!(dry_run.summary.prioritization_fee, 5);
+        assert_eq!(dry_run.summary.last_valid_block_height, 150);
+        assert_eq!(
+            dry_run.summary.message_len,
+            dry_run.transaction.message.serialize().len()
+        );
+        assert_eq!(count(&requests, RpcRequest::SendTransaction), 0);
+        assert!(sender.sigs.is_empty());
+    }
+
+    #[test]
+    fn test_prover_shares_in_claim_and_proof() {
+        let shares = vec![(Pubkey::new_unique(), 3), (Pubkey::new_unique(), 1)];
+        let sender = mock_sender(Keypair::new(), HashMap::new(), Arc::default())
+            .with_prover_shares(shares.clone());
+        let requester = Pubkey::new_unique();
+        let (execution_account, _) = execution_address(&requester, b"test");
+
+        let claim = sender.claim_instruction("test", requester, execution_account, 100);
+        let ix = bonsol_interface::bonsol_schema::parse_ix_data(&claim.data).unwrap();
+        let claim_shares = ix.claim_v1_nested_flatbuffer().unwrap().prover_shares().unwrap();
+        assert_eq!(
+            claim_shares
+                .iter()
+                .map(|s| (Pubkey::from(<[u8; 32]>::from(s.prover())), s.weight()))
+                .collect_vec(),
+            shares
+        );
+
+        // proofs pass the provers the claim account stored, not the ones the sender claims with
+        let stored_shares = shares
+            .iter()
+            .rev()
+            .map(|(prover, weight)| ProverShareV1 {
+                prover: prover.to_bytes(),
+                weight: *weight,
+            })
+            .collect_vec();
+        let proof = sender.proof_instruction(
+            "test",
+            requester,
+            None,
+            &[0; 256],
+            &[1; 32],
+            &[2; 32],
+            &[3; 32],
+            b"output",
+            vec![],
+            None,
+            &stored_shares,
+            0,
+            0,
+        );
+        let (execution_claim_account, _) = execution_claim_address(execution_account.as_ref());
+        assert_eq!(proof.accounts.len(), 7);
+        assert_eq!(
+            proof.accounts[4],
+            AccountMeta::new_readonly(execution_claim_account, false)
+        );
+        assert_eq!(proof.accounts[5], AccountMeta::new(shares[1].0, false));
+        assert_eq!(proof.accounts[6], AccountMeta::new(shares[0].0, false));
+    }
+
+    #[tokio::test]
+    async fn test_stop_ends_status_loop() {
+        let (mut sender, _) = recording_sender(DEFAULT_FEE_CACHE_TTL);
+        sender.start();
+        tokio::time::timeout(Duration::from_secs(5), sender.stop())
+            .await
+            .expect("status loop did not stop")
+            .unwrap();
+        assert!(sender.txn_status_handle.lock().unwrap().is_none());
+        assert!(sender.shutdown.is_cancelled());
+    }
+
+    #[tokio::test]
+    async fn test_lookup_tables_shrink_proof_transactions() {
+        let (sender, _) = recording_sender(DEFAULT_FEE_CACHE_TTL);
+        let callback = Pubkey::new_unique();
+        let extra_accounts = (0..20)
+            .map(|_| AccountMeta::new(Pubkey::new_unique(), false))
+            .collect_vec();
+        let instruction = sender.proof_instruction(
+            "test",
+            Pubkey::new_unique(),
+            Some(ProgramExec {
+                program_id: callback,
+                instruction_prefix: vec![1],
+            }),
+            &[0; 256],
+            &[1; 32],
+            &[2; 32],
+            &[3; 32],
+            b"output",
+            extra_accounts.clone(),
+            None,
+            &[],
+            0,
+            0,
+        );
+        let (without_table, _) = sender
+            .compile_transaction(TransactionOperation::SubmitProof, instruction.clone())
+            .await
+            .unwrap();
+
+        let table = AddressLookupTableAccount {
+            key: Pubkey::new_unique(),
+            addresses: std::iter::once(callback)
+                .chain(extra_accounts.iter().map(|a| a.pubkey))
+                .collect(),
+        };
+        let sender = sender.with_lookup_tables(vec![table]);
+        let (with_table, _) = sender
+            .compile_transaction(TransactionOperation::SubmitProof, instruction)
+            .await
+            .unwrap();
+
+        // the callback program is only passed as an account so it is looked up with the extras
+        assert_eq!(
+            without_table.message.static_account_keys().len()
+                - with_table.message.static_account_keys().len(),
+            21
+        );
+        assert_eq!(
+            with_table.message.address_table_lookups().map(|l| l.len()),
+            Some(1)
+        );
+        assert!(with_table.message.serialize().len() < without_table.message.serialize().len());
+    }
+
+    #[tokio::test]
+    async fn test_fetch_lookup_tables() {
+        let addresses = (0..3).map(|_| Pubkey::new_unique()).collect_vec();
+        let data = AddressLookupTable {
+            meta: Default::default(),
+            addresses: addresses.clone().into(),
+        }
+        .serialize_for_tests()
+        .unwrap();
+        let mut mocks = HashMap::new();
+        mocks.insert(
+            RpcRequest::GetAccountInfo,
+            json!({
+                "context": { "slot": 1 },
+                "value": {
+                    "data": [bs58::encode(&data).into_string(), "base58"],
+                    "executable": false,
+                    "lamports": 1_000_000,
+                    "owner": solana_sdk::address_lookup_table::program::id().to_string(),
+                    "rentEpoch": 0,
+                    "space": data.len()
+                }
+            }),
+        );
+        let sender = mock_sender(Keypair::new(), mocks, Arc::default());
+        let key = Pubkey::new_unique();
+        let tables = sender.fetch_lookup_tables(&[key]).await.unwrap();
+        assert_eq!(tables.len(), 1);
+        assert_eq!(tables[0].key, key);
+        assert_eq!(tables[0].addresses, addresses);
+    }
+
+    #[tokio::test]
+    async fn test_claim_and_confirm_confirmed() {
+        let signer = Keypair::new();
+        let requester = Pubkey::new_unique();
+        let (execution_account, _) = execution_address(&requester, b"test");
+        let (mocks, expected) = claim_mocks(&signer, requester, 10);
+        let sender = mock_sender(signer, mocks, Arc::default());
+        let (sig, status) = sender
+            .claim_and_confirm("test", requester, execution_account, 100)
+            .await
+            .unwrap();
+        assert_eq!(sig, expected);
+        assert!(matches!(status, TransactionStatus::Confirmed(s) if s.err.is_none()));
+        assert!(matches!(
+            sender.get_signature_status(&expected),
+            Some(TransactionStatus::Confirmed(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_claim_and_confirm_expired() {
+        let signer = Keypair::new();
+        let requester = Pubkey::new_unique();
+        let (execution_account, _) = execution_address(&requester, b"test");
+        let (mocks, expected) = claim_mocks(&signer, requester, 200);
+        let metrics = Arc::new(RecordingMetrics::default());
+        let sender = mock_sender(signer, mocks, metrics.clone());
+        let (sig, status) = sender
+            .claim_and_confirm("test", requester, execution_account, 100)
+            .await
+            .unwrap();
+        assert_eq!(sig, expected);
+        assert_eq!(status, TransactionStatus::Expired);
+        assert!(sender.get_signature_status(&expected).is_none());
+        assert_eq!(metrics.txns_expired.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn test_expired_transactions_are_recorded() {
+        let mut mocks = HashMap::new();
+        mocks.insert(RpcRequest::GetBlockHeight, json!(10));
+        let metrics = Arc::new(RecordingMetrics::default());
+        let sender = mock_sender(Keypair::new(), mocks, metrics.clone());
+        let sig = Signature::new_unique();
+        sender.track(sig, 5);
+
+        poll(&sender).await;
+        assert!(sender.get_signature_status(&sig).is_none());
+        assert!(sender.sent_at.is_empty());
+        assert_eq!(metrics.txns_expired.load(Ordering::SeqCst), 1);
+        assert_eq!(metrics.txns_confirmed.load(Ordering::SeqCst), 0);
+    }
+
+    #[tokio::test]
+    async fn test_status_transitions_are_reported() {
+        let signer = Keypair::new();
+        let requester = Pubkey::new_unique();
+        let (execution_account, _) = execution_address(&requester, b"test");
+        let (mocks, confirmed) = claim_mocks(&signer, requester, 10);
+        let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel();
+        let sender = mock_sender(signer, mocks, Arc::default()).with_status_updates(tx);
+        sender
+            .claim("test", requester, execution_account, 100)
+            .await
+            .unwrap();
+        let expired = Signature::new_unique();
+        sender.track(expired, 5);
+
+        poll(&sender).await;
+        assert_eq!(
+            rx.try_recv().unwrap(),
+            StatusTransition {
+                signature: expired,
+                status: TransactionStatus::Expired,
+            }
+        );
+        let transition = rx.try_recv().unwrap();
+        assert_eq!(transition.signature, confirmed);
+        assert!(matches!(transition.status, TransactionStatus::Confirmed(_)));
+        assert_eq!(
+            sender.get_signature_status(&confirmed),
+            Some(transition.status)
+        );
+        assert!(rx.try_recv().is_err());
+    }
 }
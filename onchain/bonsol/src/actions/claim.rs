@@ -1,6 +1,6 @@
 use bonsol_interface::{
     bonsol_schema::{root_as_execution_request_v1, ChannelInstruction, ClaimV1},
-    claim_state::ClaimStateV1,
+    claim_state::{ClaimStateV1, ProverShareV1, MAX_PROVER_SHARES},
     util::{execution_address_seeds, execution_claim_address_seeds},
 };
 
@@ -22,6 +22,27 @@ pub struct ClaimAccounts<'a, 'b> {
     pub existing_claim: bool,
     pub stake: u64,
     pub expired: bool,
+    pub shares: Vec<ProverShareV1>,
+}
+
+/// Reads the optional tip split from the claim, every prover needs a non-zero weight
+pub fn prover_shares(data: &ClaimV1) -> Result<Vec<ProverShareV1>, ChannelError> {
+    let shares = data
+        .prover_shares()
+        .map(|shares| {
+            shares
+                .iter()
+                .map(|s| ProverShareV1 {
+                    prover: s.prover().into(),
+                    weight: s.weight(),
+                })
+                .collect::<Vec<_>>()
+        })
+        .unwrap_or_default();
+    if shares.len() > MAX_PROVER_SHARES || shares.iter().any(|s| s.weight == 0) {
+        return Err(ChannelError::InvalidProverShares);
+    }
+    Ok(shares)
 }
 
 impl<'a, 'b> ClaimAccounts<'a, 'b> {
@@ -43,6 +64,7 @@ impl<'a, 'b> ClaimAccounts<'a, 'b> {
                 existing_claim: false,
                 stake: 0,
                 expired: false,
+                shares: prover_shares(data)?,
             };
             check_writable_signer(ca.payer, ChannelError::InvalidPayerAccount)?;
             check_writable_signer(ca.claimer, ChannelError::InvalidClaimerAccount)?;
@@ -91,7 +113,7 @@ impl<'a, 'b> ClaimAccounts<'a, 'b> {
                 create_program_account(
                     ca.exec_claim,
                     &exec_claim_seeds,
-                    std::mem::size_of::<ClaimStateV1>() as u64,
+                    ClaimStateV1::account_size(ca.shares.len()) as u64,
                     ca.payer,
                     ca.system_program,
                     None,
@@ -105,6 +127,12 @@ impl<'a, 'b> ClaimAccounts<'a, 'b> {
 
         Err(ChannelError::InvalidInstruction)
     }
+
+    /// Fits a reused claim account to the new claim's shares, the payer covers any extra rent
+    fn resize_claim_account(&self) -> Result<(), ProgramError> {
+        let size = ClaimStateV1::account_size(self.shares.len());
+        resize_program_account(self.exec_claim, self.payer, size)
+    }
 }
 
 pub fn process_claim_v1<'a>(
@@ -119,7 +147,12 @@ pub fn process_claim_v1<'a>(
     let current_block = solana_program::clock::Clock::get()?.slot;
     let ca = ClaimAccounts::from_instruction(accounts, &cl, current_block)?;
     if ca.expired {
-        cleanup_execution_account(ca.exec, ca.claimer, ChannelError::ExecutionExpired as u8)?;
+        cleanup_execution_account(
+            ca.exec,
+            ca.claimer,
+            None,
+            ChannelError::ExecutionExpired as u8,
+        )?;
         msg!("Execution expired");
         return Ok(());
     }
@@ -132,7 +165,9 @@ pub fn process_claim_v1<'a>(
             let claim =
                 ClaimStateV1::from_claim_ix(ca.claimer.key, current_block, ca.block_commitment);
             drop(data);
+            ca.resize_claim_account()?;
             ClaimStateV1::save_claim(&claim, ca.exec_claim);
+            ClaimStateV1::save_shares(&ca.shares, ca.exec_claim);
             transfer_unowned(ca.claimer, ca.exec_claim, ca.stake)
         } else {
             Err(ChannelError::ActiveClaimExists.into())
@@ -141,6 +176,84 @@ pub fn process_claim_v1<'a>(
         let claim = ClaimStateV1::from_claim_ix(ca.claimer.key, current_block, ca.block_commitment);
         transfer_unowned(ca.claimer, ca.exec_claim, ca.stake)?;
         ClaimStateV1::save_claim(&claim, ca.exec_claim);
+        ClaimStateV1::save_shares(&ca.shares, ca.exec_claim);
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use bonsol_interface::bonsol_schema::{root_as_claim_v1, ClaimV1T, ProverShareT};
+
+    use super::*;
+
+    // the generated object types are non_exhaustive so they are filled in after `default`
+    fn claim() -> ClaimV1T {
+        let mut claim = ClaimV1T::default();
+        claim.execution_id = Some("test".to_string());
+        claim.block_commitment = 10;
+        claim
+    }
+
+    fn claim_with_weights(weights: &[u64]) -> Vec<u8> {
+        let mut claim = claim();
+        claim.prover_shares = Some(
+            weights
+                .iter()
+                .enumerate()
+                .map(|(i, weight)| ProverShareT {
+                    weight: *weight,
+                    prover: [i as u8; 32],
+                })
+                .collect(),
+        );
+        let mut fbb = flatbuffers::FlatBufferBuilder::new();
+        let offset = claim.pack(&mut fbb);
+        fbb.finish(offset, None);
+        fbb.finished_data().to_vec()
+    }
+
+    #[test]
+    fn test_prover_shares() {
+        let data = claim_with_weights(&[3, 1]);
+        let shares = prover_shares(&root_as_claim_v1(&data).unwrap()).unwrap();
+        assert_eq!(
+            shares,
+            vec![
+                ProverShareV1 {
+                    prover: [0; 32],
+                    weight: 3,
+                },
+                ProverShareV1 {
+                    prover: [1; 32],
+                    weight: 1,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_claim_without_shares() {
+        let mut fbb = flatbuffers::FlatBufferBuilder::new();
+        let offset = claim().pack(&mut fbb);
+        fbb.finish(offset, None);
+        let data = fbb.finished_data();
+        assert!(prover_shares(&root_as_claim_v1(data).unwrap())
+            .unwrap()
+            .is_empty());
+    }
+
+    #[test]
+    fn test_invalid_prover_shares() {
+        let zero_weight = claim_with_weights(&[1, 0]);
+        assert_eq!(
+            prover_shares(&root_as_claim_v1(&zero_weight).unwrap()),
+            Err(ChannelError::InvalidProverShares)
+        );
+        let too_many = claim_with_weights(&[1; MAX_PROVER_SHARES + 1]);
+        assert_eq!(
+            prover_shares(&root_as_claim_v1(&too_many).unwrap()),
+            Err(ChannelError::InvalidProverShares)
+        );
+    }
+}
@@ -33,8 +33,11 @@ impl<'a, 'b> DeployAccounts<'a, 'b> {
                 extra_accounts: &accounts[4..],
                 deployment_bump: None,
                 image_id: imageid,
-                image_checksum: &[],
+                image_checksum: data.sha256().map(|s| s.bytes()).unwrap_or_default(),
             };
+            if !da.image_checksum.is_empty() && da.image_checksum.len() != 32 {
+                return Err(ChannelError::InvalidImageChecksum);
+            }
             let owner = data
                 .owner()
                 .map(|b| b.bytes())
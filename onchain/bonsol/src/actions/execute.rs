@@ -85,6 +85,10 @@ impl<'a, 'b> ExecuteAccounts<'a, 'b> {
                 return Err(ChannelError::InputDigestRequired);
             }
 
+            if data.refund_destination().is_some_and(|rd| rd.len() != 32) {
+                return Err(ChannelError::InvalidRefundDestination);
+            }
+
             or(
                 &[
                     check_key_match(
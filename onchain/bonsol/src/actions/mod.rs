@@ -1,9 +1,13 @@
 mod claim;
 mod deploy;
 mod execute;
+mod reclaim;
 mod status;
+mod update_deploy;
 
 pub use claim::*;
 pub use deploy::*;
 pub use execute::*;
+pub use reclaim::*;
 pub use status::*;
+pub use update_deploy::*;
@@ -0,0 +1,143 @@
+use bonsol_interface::{
+    bonsol_schema::{root_as_execution_request_v1, ExitCode},
+    claim_state::ClaimStateV1,
+    util::{execution_address_seeds, execution_claim_address_seeds},
+};
+
+use solana_program::{
+    account_info::AccountInfo, clock::Clock, msg, program_error::ProgramError, sysvar::Sysvar,
+};
+
+use crate::{actions::split_refund_account, assertions::*, error::ChannelError, utilities::*};
+
+pub struct ReclaimAccounts<'a> {
+    pub requester: &'a AccountInfo<'a>,
+    pub exec: &'a AccountInfo<'a>,
+    pub exec_claim: &'a AccountInfo<'a>,
+}
+
+impl<'a> ReclaimAccounts<'a> {
+    fn from_instruction(accounts: &'a [AccountInfo<'a>]) -> Result<Self, ChannelError> {
+        if accounts.len() < 3 {
+            return Err(ChannelError::InvalidInstruction);
+        }
+        let ra = ReclaimAccounts {
+            requester: &accounts[0],
+            exec: &accounts[1],
+            exec_claim: &accounts[2],
+        };
+        check_writable_signer(ra.requester, ChannelError::InvalidRequesterAccount)?;
+        check_writeable(ra.exec, ChannelError::InvalidExecutionAccount)?;
+        check_owner(
+            ra.exec,
+            &crate::ID,
+            ChannelError::InvalidExecutionAccountOwner,
+        )?;
+        check_pda(
+            &execution_claim_address_seeds(ra.exec.key.as_ref()),
+            ra.exec_claim.key,
+            ChannelError::InvalidClaimAccount,
+        )?;
+        Ok(ra)
+    }
+}
+
+/// An execution can be reclaimed once it has expired, unless a prover still holds a claim whose
+/// block commitment has not passed yet.
+pub fn check_reclaimable(
+    max_block_height: u64,
+    current_slot: u64,
+    claim: Option<&ClaimStateV1>,
+) -> Result<(), ChannelError> {
+    if current_slot <= max_block_height {
+        return Err(ChannelError::ExecutionNotExpired);
+    }
+    if let Some(claim) = claim {
+        if claim.block_commitment >= current_slot {
+            return Err(ChannelError::ActiveClaimExists);
+        }
+    }
+    Ok(())
+}
+
+pub fn process_reclaim_v1<'a>(accounts: &'a [AccountInfo<'a>]) -> Result<(), ProgramError> {
+    let ra = ReclaimAccounts::from_instruction(accounts)?;
+    let current_slot = Clock::get()?.slot;
+    let refund_destination = {
+        let exec_data = ra
+            .exec
+            .try_borrow_data()
+            .map_err(|_| ChannelError::CannotBorrowData)?;
+        let er = root_as_execution_request_v1(&exec_data)
+            .map_err(|_| ChannelError::InvalidExecutionAccountData)?;
+        let eid = er
+            .execution_id()
+            .ok_or(ChannelError::InvalidExecutionAccountData)?;
+        check_pda(
+            &execution_address_seeds(ra.requester.key, eid.as_bytes()),
+            ra.exec.key,
+            ChannelError::InvalidExecutionAccount,
+        )?;
+        let claim = if ra.exec_claim.owner == &crate::ID && ra.exec_claim.data_len() > 0 {
+            let claim_data = ra
+                .exec_claim
+                .try_borrow_data()
+                .map_err(|_| ChannelError::CannotBorrowData)?;
+            Some(
+                ClaimStateV1::load_claim_owned(&claim_data)
+                    .map_err(|_| ChannelError::InvalidClaimAccount)?,
+            )
+        } else {
+            None
+        };
+        check_reclaimable(er.max_block_height(), current_slot, claim.as_ref())?;
+        er.refund_destination().map(|rd| rd.bytes().to_vec())
+    };
+    let (refund_to, _) = split_refund_account(refund_destination.as_deref(), &accounts[3..])?;
+    cleanup_execution_account(ra.exec, ra.requester, refund_to, ExitCode::Expired as u8)?;
+    msg!("Execution reclaimed");
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn claim(block_commitment: u64) -> ClaimStateV1 {
+        ClaimStateV1 {
+            claimer: [1; 32],
+            claimed_at: 10,
+            block_commitment,
+        }
+    }
+
+    #[test]
+    fn test_reclaim_expired_unclaimed() {
+        assert_eq!(check_reclaimable(100, 101, None), Ok(()));
+    }
+
+    #[test]
+    fn test_reclaim_expired_with_lapsed_claim() {
+        assert_eq!(check_reclaimable(100, 101, Some(&claim(100))), Ok(()));
+    }
+
+    #[test]
+    fn test_reclaim_still_valid_rejected() {
+        assert_eq!(
+            check_reclaimable(100, 100, None),
+            Err(ChannelError::ExecutionNotExpired)
+        );
+        assert_eq!(
+            check_reclaimable(100, 50, Some(&claim(60))),
+            Err(ChannelError::ExecutionNotExpired)
+        );
+    }
+
+    #[test]
+    fn test_reclaim_active_claim_rejected() {
+        assert_eq!(
+            check_reclaimable(100, 101, Some(&claim(120))),
+            Err(ChannelError::ActiveClaimExists)
+        );
+    }
+}
@@ -1,19 +1,13 @@
-use crate::{
-    assertions::*,
-    error::ChannelError,
-    proof_handling::{
-        output_digest_v1_0_1, output_digest_v1_2_1, prepare_inputs_v1_0_1, prepare_inputs_v1_2_1,
-        verify_risc0_v1_0_1, verify_risc0_v1_2_1,
-    },
-    utilities::*,
-};
+use crate::{assertions::*, error::ChannelError, proof_handling::proof_preparer, utilities::*};
 
 use bonsol_interface::{
     bonsol_schema::{
-        root_as_execution_request_v1, ChannelInstruction, ExecutionRequestV1, ExitCode, StatusV1,
+        root_as_execution_request_v1, Account, ChannelInstruction, ExecutionRequestV1, ExitCode,
+        StatusV1,
     },
-    prover_version::{ProverVersion, VERSION_V1_0_1, VERSION_V1_2_1},
-    util::execution_address_seeds,
+    claim_state::{ClaimStateV1, ProverShareV1},
+    prover_version::ProverVersion,
+    util::{execution_address_seeds, execution_claim_address_seeds},
 };
 
 use solana_program::{
@@ -24,9 +18,16 @@ use solana_program::{
     program::invoke_signed,
     program_error::ProgramError,
     program_memory::sol_memcmp,
+    pubkey::Pubkey,
     sysvar::Sysvar,
 };
 
+/// Relaxes callback extra account checks for local development, see `assert_account_matches`
+const DEV_MODE: bool = cfg!(feature = "dev-mode");
+
+/// The runtime aborts cross program invocations with more instruction data than this
+pub const MAX_CALLBACK_PAYLOAD_LEN: usize = 10 * 1024;
+
 struct StatusAccounts<'a, 'b> {
     pub requester: &'a AccountInfo<'a>,
     pub exec: &'a AccountInfo<'a>,
@@ -37,33 +38,225 @@ struct StatusAccounts<'a, 'b> {
     pub eid: &'b str,
 }
 
+/// What `validate_status_accounts` derived from the instruction
+#[derive(Debug, PartialEq, Eq)]
+pub struct ValidatedStatusAccounts<'b> {
+    pub exec_bump: u8,
+    pub eid: &'b str,
+}
+
+/// Checks the status instruction has the requester, execution, callback program and prover
+/// accounts and that the execution account is the PDA for the requester and execution id.
+pub fn validate_status_accounts<'b>(
+    account_keys: &[&Pubkey],
+    execution_id: Option<&'b str>,
+) -> Result<ValidatedStatusAccounts<'b>, ChannelError> {
+    if account_keys.len() < 4 {
+        return Err(ChannelError::InvalidInstruction);
+    }
+    let eid = execution_id.ok_or(ChannelError::InvalidExecutionAccount)?;
+    let exec_bump = check_pda(
+        &execution_address_seeds(account_keys[0], eid.as_bytes()),
+        account_keys[1],
+        ChannelError::InvalidExecutionAccount,
+    )?;
+    Ok(ValidatedStatusAccounts { exec_bump, eid })
+}
+
 impl<'a, 'b> StatusAccounts<'a, 'b> {
     fn from_instruction(
         accounts: &'a [AccountInfo<'a>],
         data: &'b StatusV1<'b>,
     ) -> Result<Self, ChannelError> {
-        let ea = &accounts[1];
-        let prover = &accounts[3];
-        let callback_program = &accounts[2];
-        let eid = data
-            .execution_id()
-            .ok_or(ChannelError::InvalidExecutionAccount)?;
-        let bmp = Some(check_pda(
-            &execution_address_seeds(accounts[0].key, eid.as_bytes()),
-            ea.key,
-            ChannelError::InvalidExecutionAccount,
-        )?);
+        let keys = accounts.iter().map(|a| a.key).collect::<Vec<_>>();
+        let validated = validate_status_accounts(&keys, data.execution_id())?;
         let stat = StatusAccounts {
             requester: &accounts[0],
             exec: &accounts[1],
-            callback_program,
-            prover,
+            callback_program: &accounts[2],
+            prover: &accounts[3],
             extra_accounts: &accounts[4..],
-            exec_bump: bmp,
-            eid,
+            exec_bump: Some(validated.exec_bump),
+            eid: validated.eid,
         };
         Ok(stat)
     }
+
+    /// Splits off the accounts after the callback's extra accounts, the refund destination when the
+    /// request set one, then the claim account followed by the provers sharing the tip
+    fn take_trailing_accounts(&mut self, callback_extra_len: usize) -> &'a [AccountInfo<'a>] {
+        let (callback, shares) = self
+            .extra_accounts
+            .split_at(callback_extra_len.min(self.extra_accounts.len()));
+        self.extra_accounts = callback;
+        shares
+    }
+}
+
+/// Takes the request's refund destination off the front of the trailing accounts when it set one
+pub fn split_refund_account<'c, 'a>(
+    refund_destination: Option<&[u8]>,
+    trailing: &'c [AccountInfo<'a>],
+) -> Result<(Option<&'c AccountInfo<'a>>, &'c [AccountInfo<'a>]), ChannelError> {
+    let refund_destination = match refund_destination {
+        Some(refund_destination) => refund_destination,
+        None => return Ok((None, trailing)),
+    };
+    let (account, rest) = trailing
+        .split_first()
+        .ok_or(ChannelError::InvalidRefundDestination)?;
+    check_bytes_match(
+        account.key.as_ref(),
+        refund_destination,
+        ChannelError::InvalidRefundDestination,
+    )?;
+    Ok((Some(account), rest))
+}
+
+/// Number of accounts forwarded to the callback, provers only pass them when a callback is set
+fn callback_extra_len(er: &ExecutionRequestV1) -> usize {
+    if er.callback_program_id().is_none() {
+        return 0;
+    }
+    er.callback_extra_accounts().map_or(0, |a| a.len())
+}
+
+/// Metas for the callback's extra accounts in the order the request stored them. Outside of dev
+/// mode the supplied accounts must be exactly the stored ones, a count mismatch fails before any
+/// account is checked. Dev mode forwards the stored accounts that were supplied, dropping the rest.
+pub fn callback_account_metas(
+    expected: &[Account],
+    supplied: &[AccountInfo],
+    dev_mode: bool,
+) -> Result<Vec<AccountMeta>, ChannelError> {
+    if expected.len() != supplied.len() {
+        if !dev_mode {
+            return Err(ChannelError::InvalidCallbackExtraAccounts);
+        }
+        msg!(
+            "Expected {} callback accounts, got {}",
+            expected.len(),
+            supplied.len()
+        );
+    }
+    expected
+        .iter()
+        .zip(supplied)
+        .map(|(expected, actual)| assert_account_matches(expected, actual, dev_mode))
+        .collect()
+}
+
+/// When the request asked for its input hash to be verified, the input digest the proof commits
+/// to has to match the one stored in the request. A request without a stored digest cannot match.
+pub fn check_input_digest(
+    verify_input_hash: bool,
+    expected: Option<&[u8]>,
+    input_digest: &[u8],
+) -> Result<(), ChannelError> {
+    if !verify_input_hash {
+        return Ok(());
+    }
+    let expected = expected.ok_or(ChannelError::InputsDontMatch)?;
+    check_bytes_match(expected, input_digest, ChannelError::InputsDontMatch)
+}
+
+/// Instruction data for the callback, the prefix followed by the input digest and committed
+/// outputs when they are forwarded. Oversized outputs fail here with a clear error instead of
+/// aborting the invocation.
+pub fn callback_payload(
+    prefix: &[u8],
+    input_digest: &[u8],
+    forwarded_outputs: Option<&[u8]>,
+) -> Result<Vec<u8>, ChannelError> {
+    let payload = match forwarded_outputs {
+        Some(committed_outputs) => [prefix, input_digest, committed_outputs].concat(),
+        None => prefix.to_vec(),
+    };
+    if payload.len() > MAX_CALLBACK_PAYLOAD_LEN {
+        msg!(
+            "Callback payload is {} bytes, the limit is {}",
+            payload.len(),
+            MAX_CALLBACK_PAYLOAD_LEN
+        );
+        return Err(ChannelError::PayloadTooLarge);
+    }
+    Ok(payload)
+}
+
+/// A prover running in risc0 dev mode only makes fake receipts, which can never verify. Production
+/// builds reject them up front with a clear error and leave the execution open for another prover,
+/// rather than closing it as a proving failure.
+pub fn check_proof_mode(dev_mode_proof: bool, dev_mode_program: bool) -> Result<(), ChannelError> {
+    if dev_mode_proof && !dev_mode_program {
+        msg!("Proof was made in dev mode, which this program does not accept");
+        return Err(ChannelError::DevModeProof);
+    }
+    Ok(())
+}
+
+/// Each prover account has to be writable and match its share, in the order the claim stored them
+pub fn check_share_accounts(
+    shares: &[ProverShareV1],
+    provers: &[AccountInfo],
+) -> Result<(), ChannelError> {
+    if shares.len() != provers.len() {
+        return Err(ChannelError::InvalidProverShares);
+    }
+    for (share, prover) in shares.iter().zip(provers) {
+        if !prover.is_writable || sol_memcmp(prover.key.as_ref(), &share.prover, 32) != 0 {
+            return Err(ChannelError::InvalidProverShares);
+        }
+    }
+    Ok(())
+}
+
+/// The execution's claim account has to follow the callback and refund accounts, then exactly the
+/// provers the claim split the tip between. A claim without shares pays the whole tip to the prover.
+fn pay_tip<'a>(
+    sa: &StatusAccounts<'a, '_>,
+    share_accounts: &'a [AccountInfo<'a>],
+    tip: u64,
+) -> Result<(), ProgramError> {
+    let (exec_claim, provers) = share_accounts
+        .split_first()
+        .ok_or(ChannelError::InvalidClaimAccount)?;
+    check_pda(
+        &execution_claim_address_seeds(sa.exec.key.as_ref()),
+        exec_claim.key,
+        ChannelError::InvalidClaimAccount,
+    )?;
+    check_owner(exec_claim, &crate::ID, ChannelError::InvalidClaimAccount)?;
+    let shares = ClaimStateV1::load_shares(&exec_claim.try_borrow_data()?)
+        .map_err(|_| ChannelError::InvalidClaimAccount)?;
+    check_share_accounts(&shares, provers)?;
+    if shares.is_empty() {
+        return payout_tip(sa.exec, sa.prover, tip);
+    }
+    let weights = shares.iter().map(|s| s.weight).collect::<Vec<_>>();
+    payout_tip_shares(sa.exec, provers, &weights, tip)
+}
+
+/// What a status instruction needs for its proof to be verified. A program that commits nothing
+/// is proven with an empty committed outputs vector, only a missing field means proving failed.
+#[derive(Debug, PartialEq)]
+pub struct ProofParts<'b> {
+    pub proof: &'b [u8],
+    pub execution_digest: &'b [u8],
+    pub assumption_digest: &'b [u8],
+    pub input_digest: &'b [u8],
+    pub committed_outputs: &'b [u8],
+}
+
+impl<'b> ProofParts<'b> {
+    pub fn from_status(st: &StatusV1<'b>) -> Option<Self> {
+        Some(ProofParts {
+            proof: st.proof().filter(|x| x.len() == 256)?.bytes(),
+            execution_digest: st.execution_digest()?.bytes(),
+            assumption_digest: st.assumption_digest()?.bytes(),
+            input_digest: st.input_digest()?.bytes(),
+            committed_outputs: st.committed_outputs()?.bytes(),
+        })
+    }
 }
 
 pub fn process_status_v1<'a>(
@@ -75,33 +268,33 @@ pub fn process_status_v1<'a>(
         return Err(ChannelError::InvalidInstruction.into());
     }
     let st = st.unwrap();
-    let sa = StatusAccounts::from_instruction(accounts, &st)?;
+    check_proof_mode(st.dev_mode(), DEV_MODE)?;
+    let mut sa = StatusAccounts::from_instruction(accounts, &st)?;
     let er_ref = sa.exec.try_borrow_data()?;
     let er =
         root_as_execution_request_v1(&er_ref).map_err(|_| ChannelError::InvalidExecutionAccount)?;
-    let pr_v = st.proof().filter(|x| x.len() == 256);
+    let trailing = sa.take_trailing_accounts(callback_extra_len(&er));
+    let (refund_to, share_accounts) =
+        split_refund_account(er.refund_destination().map(|rd| rd.bytes()), trailing)?;
     if er.max_block_height() < Clock::get()?.slot {
         return Err(ChannelError::ExecutionExpired.into());
     }
-    let execution_digest_v = st.execution_digest().map(|x| x.bytes());
-    let input_digest_v = st.input_digest().map(|x| x.bytes());
-    let assumption_digest_v = st.assumption_digest().map(|x| x.bytes());
-    let committed_outputs_v = st.committed_outputs().map(|x| x.bytes());
-    if let (Some(proof), Some(exed), Some(asud), Some(input_digest), Some(co)) = (
-        pr_v,
-        execution_digest_v,
-        assumption_digest_v,
-        input_digest_v,
-        committed_outputs_v,
-    ) {
+    if let Some(ProofParts {
+        proof,
+        execution_digest: exed,
+        assumption_digest: asud,
+        input_digest,
+        committed_outputs: co,
+    }) = ProofParts::from_status(&st)
+    {
         let proof: &[u8; 256] = proof
-            .bytes()
             .try_into()
             .map_err(|_| ChannelError::InvalidInstruction)?;
-        if er.verify_input_hash() {
-            er.input_digest()
-                .map(|x| check_bytes_match(x.bytes(), input_digest, ChannelError::InputsDontMatch));
-        }
+        check_input_digest(
+            er.verify_input_hash(),
+            er.input_digest().map(|x| x.bytes()),
+            input_digest,
+        )?;
         let verified = verify_with_prover(input_digest, co, asud, er, exed, st, proof)?;
         let tip = er.tip();
 
@@ -129,40 +322,15 @@ pub fn process_status_v1<'a>(
                 // ER is the signer, it is reuired to save the execution id in the calling program
                 let mut accounts = vec![AccountMeta::new_readonly(*sa.exec.key, true)];
                 if let Some(extra_accounts) = er.callback_extra_accounts() {
-                    if extra_accounts.len() != sa.extra_accounts.len() {
-                        return Err(ChannelError::InvalidCallbackExtraAccounts.into());
-                    }
-                    for (i, a) in sa.extra_accounts.iter().enumerate() {
-                        let stored_a = extra_accounts.get(i);
-                        let key: [u8; 32] = stored_a.pubkey().into();
-                        if sol_memcmp(a.key.as_ref(), &key, 32) != 0 {
-                            return Err(ChannelError::InvalidCallbackExtraAccounts.into());
-                        }
-                        // dont cary feepayer signature through to callback we set all signer to false except the ER
-                        if a.is_writable {
-                            if !stored_a.writable() == 0 {
-                                return Err(ChannelError::InvalidCallbackExtraAccounts.into());
-                            }
-                            accounts.push(AccountMeta::new(*a.key, false));
-                        } else {
-                            if stored_a.writable() == 1 {
-                                //maybe relax this for devs?
-                                return Err(ChannelError::InvalidCallbackExtraAccounts.into());
-                            }
-                            accounts.push(AccountMeta::new_readonly(*a.key, false));
-                        }
-                    }
+                    let expected: Vec<Account> = extra_accounts.iter().copied().collect();
+                    let metas = callback_account_metas(&expected, sa.extra_accounts, DEV_MODE)?;
+                    accounts.extend(metas);
                 }
-                let payload = if er.forward_output() && st.committed_outputs().is_some() {
-                    [
-                        er.callback_instruction_prefix().unwrap().bytes(),
-                        input_digest,
-                        st.committed_outputs().unwrap().bytes(),
-                    ]
-                    .concat()
-                } else {
-                    er.callback_instruction_prefix().unwrap().bytes().to_vec()
-                };
+                let payload = callback_payload(
+                    er.callback_instruction_prefix().unwrap().bytes(),
+                    input_digest,
+                    er.forward_output().then_some(co),
+                )?;
                 let callback_ix =
                     Instruction::new_with_bytes(*sa.callback_program.key, &payload, accounts);
                 drop(er_ref);
@@ -176,17 +344,27 @@ pub fn process_status_v1<'a>(
             } else {
                 drop(er_ref);
             }
-            payout_tip(sa.exec, sa.prover, tip)?;
-            cleanup_execution_account(sa.exec, sa.requester, ExitCode::Success as u8)?;
+            pay_tip(&sa, share_accounts, tip)?;
+            cleanup_execution_account(sa.exec, sa.requester, refund_to, ExitCode::Success as u8)?;
         } else {
             drop(er_ref);
             msg!("{} Verifying Failed Cleaning up", sa.eid);
-            cleanup_execution_account(sa.exec, sa.requester, ExitCode::VerifyError as u8)?;
+            cleanup_execution_account(
+                sa.exec,
+                sa.requester,
+                refund_to,
+                ExitCode::VerifyError as u8,
+            )?;
         }
     } else {
         drop(er_ref);
         msg!("{} Proving Failed Cleaning up", sa.eid);
-        cleanup_execution_account(sa.exec, sa.requester, ExitCode::ProvingError as u8)?;
+        cleanup_execution_account(
+            sa.exec,
+            sa.requester,
+            refund_to,
+            ExitCode::ProvingError as u8,
+        )?;
     }
     Ok(())
 }
@@ -202,30 +380,294 @@ fn verify_with_prover(
 ) -> Result<bool, ProgramError> {
     let prover_version =
         ProverVersion::try_from(er.prover_version()).unwrap_or(ProverVersion::default());
-    let verified = match prover_version {
-        VERSION_V1_0_1 => {
-            let output_digest = output_digest_v1_0_1(input_digest, co, asud);
-            let proof_inputs = prepare_inputs_v1_0_1(
-                er.image_id().unwrap(),
-                exed,
-                output_digest.as_ref(),
-                st.exit_code_system(),
-                st.exit_code_user(),
-            )?;
-            verify_risc0_v1_0_1(proof, &proof_inputs)?
-        }
-        VERSION_V1_2_1 => {
-            let output_digest = output_digest_v1_2_1(input_digest, co, asud);
-            let proof_inputs = prepare_inputs_v1_2_1(
+    match proof_preparer(&prover_version) {
+        Some(preparer) => {
+            let output_digest = preparer.output_digest(input_digest, co, asud);
+            let proof_inputs = preparer.prepare_inputs(
                 er.image_id().unwrap(),
                 exed,
                 output_digest.as_ref(),
                 st.exit_code_system(),
                 st.exit_code_user(),
             )?;
-            verify_risc0_v1_2_1(proof, &proof_inputs)?
+            Ok(preparer.verify(proof, &proof_inputs)?)
         }
-        _ => false,
+        None => Ok(false),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bonsol_interface::{
+        bonsol_schema::{root_as_status_v1, StatusTypes, StatusV1T},
+        util::execution_address,
     };
-    Ok(verified)
+
+    use super::*;
+
+    #[test]
+    fn test_status_too_few_accounts() {
+        let keys = [
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+        ];
+        assert_eq!(
+            validate_status_accounts(&keys.iter().collect::<Vec<_>>(), Some("test")),
+            Err(ChannelError::InvalidInstruction)
+        );
+    }
+
+    #[test]
+    fn test_status_missing_execution_id() {
+        let requester = Pubkey::new_unique();
+        let (exec, _) = execution_address(&requester, b"test");
+        let other = Pubkey::new_unique();
+        assert_eq!(
+            validate_status_accounts(&[&requester, &exec, &other, &other], None),
+            Err(ChannelError::InvalidExecutionAccount)
+        );
+    }
+
+    #[test]
+    fn test_status_wrong_execution_pda() {
+        let requester = Pubkey::new_unique();
+        let (exec, _) = execution_address(&requester, b"other");
+        let other = Pubkey::new_unique();
+        assert_eq!(
+            validate_status_accounts(&[&requester, &exec, &other, &other], Some("test")),
+            Err(ChannelError::InvalidExecutionAccount)
+        );
+    }
+
+    #[test]
+    fn test_status_accounts_valid() {
+        let requester = Pubkey::new_unique();
+        let (exec, bump) = execution_address(&requester, b"test");
+        let other = Pubkey::new_unique();
+        assert_eq!(
+            validate_status_accounts(&[&requester, &exec, &other, &other], Some("test")),
+            Ok(ValidatedStatusAccounts {
+                exec_bump: bump,
+                eid: "test",
+            })
+        );
+    }
+
+    #[test]
+    fn test_share_accounts_match_claim_order() {
+        let keys = [Pubkey::new_unique(), Pubkey::new_unique()];
+        let owner = Pubkey::new_unique();
+        let mut lamports = [0u64; 2];
+        let mut data = [[0u8; 0]; 2];
+        let [l0, l1] = &mut lamports;
+        let [d0, d1] = &mut data;
+        let provers = [
+            AccountInfo::new(&keys[0], false, true, l0, d0, &owner, false, 0),
+            AccountInfo::new(&keys[1], false, true, l1, d1, &owner, false, 0),
+        ];
+        let shares = keys.map(|k| ProverShareV1 {
+            prover: k.to_bytes(),
+            weight: 1,
+        });
+        assert_eq!(check_share_accounts(&shares, &provers), Ok(()));
+
+        let reversed = [shares[1], shares[0]];
+        assert_eq!(
+            check_share_accounts(&reversed, &provers),
+            Err(ChannelError::InvalidProverShares)
+        );
+        assert_eq!(
+            check_share_accounts(&shares[..1], &provers),
+            Err(ChannelError::InvalidProverShares)
+        );
+        // a claim without shares pays the submitting prover, extra provers are not accepted
+        assert_eq!(check_share_accounts(&[], &[]), Ok(()));
+        assert_eq!(
+            check_share_accounts(&[], &provers),
+            Err(ChannelError::InvalidProverShares)
+        );
+    }
+
+    #[test]
+    fn test_refund_account_follows_callback_accounts() {
+        let keys = [Pubkey::new_unique(), Pubkey::new_unique()];
+        let owner = Pubkey::new_unique();
+        let mut lamports = [0u64; 2];
+        let mut data = [[0u8; 0]; 2];
+        let [l0, l1] = &mut lamports;
+        let [d0, d1] = &mut data;
+        let trailing = [
+            AccountInfo::new(&keys[0], false, true, l0, d0, &owner, false, 0),
+            AccountInfo::new(&keys[1], false, true, l1, d1, &owner, false, 0),
+        ];
+
+        let (refund_to, rest) = split_refund_account(None, &trailing).unwrap();
+        assert!(refund_to.is_none());
+        assert_eq!(rest.len(), 2);
+
+        let (refund_to, rest) = split_refund_account(Some(keys[0].as_ref()), &trailing).unwrap();
+        assert_eq!(refund_to.map(|a| a.key), Some(&keys[0]));
+        assert_eq!(rest.len(), 1);
+
+        assert_eq!(
+            split_refund_account(Some(keys[1].as_ref()), &trailing).unwrap_err(),
+            ChannelError::InvalidRefundDestination
+        );
+        assert_eq!(
+            split_refund_account(Some(keys[0].as_ref()), &[]).unwrap_err(),
+            ChannelError::InvalidRefundDestination
+        );
+    }
+
+    #[test]
+    fn test_callback_account_count_mismatch() {
+        let keys = [
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+        ];
+        let owner = Pubkey::new_unique();
+        let mut lamports = [0u64; 3];
+        let mut data = [[0u8; 0]; 3];
+        let [l0, l1, l2] = &mut lamports;
+        let [d0, d1, d2] = &mut data;
+        let supplied = [
+            AccountInfo::new(&keys[0], false, true, l0, d0, &owner, false, 0),
+            AccountInfo::new(&keys[1], false, false, l1, d1, &owner, false, 0),
+            AccountInfo::new(&keys[2], false, false, l2, d2, &owner, false, 0),
+        ];
+        let expected = [
+            Account::new(1, &keys[0].to_bytes()),
+            Account::new(0, &keys[1].to_bytes()),
+        ];
+        let metas = vec![
+            AccountMeta::new(keys[0], false),
+            AccountMeta::new_readonly(keys[1], false),
+        ];
+        assert_eq!(
+            callback_account_metas(&expected, &supplied[..2], false),
+            Ok(metas.clone())
+        );
+
+        // under and over supplied
+        for supplied in [&supplied[..1], &supplied[..]] {
+            assert_eq!(
+                callback_account_metas(&expected, supplied, false),
+                Err(ChannelError::InvalidCallbackExtraAccounts)
+            );
+        }
+        assert_eq!(
+            callback_account_metas(&expected, &supplied[..1], true),
+            Ok(metas[..1].to_vec())
+        );
+        assert_eq!(
+            callback_account_metas(&expected, &supplied, true),
+            Ok(metas)
+        );
+    }
+
+    #[test]
+    fn test_input_digest_enforced() {
+        assert_eq!(check_input_digest(true, Some(&[1; 32]), &[1; 32]), Ok(()));
+        assert_eq!(
+            check_input_digest(true, Some(&[1; 32]), &[2; 32]),
+            Err(ChannelError::InputsDontMatch)
+        );
+        assert_eq!(
+            check_input_digest(true, None, &[2; 32]),
+            Err(ChannelError::InputsDontMatch)
+        );
+    }
+
+    #[test]
+    fn test_input_digest_not_verified() {
+        assert_eq!(check_input_digest(false, Some(&[1; 32]), &[2; 32]), Ok(()));
+        assert_eq!(check_input_digest(false, None, &[2; 32]), Ok(()));
+    }
+
+    #[test]
+    fn test_callback_payload() {
+        assert_eq!(callback_payload(&[1], &[2; 32], None), Ok(vec![1]));
+        let payload = callback_payload(&[1], &[2; 32], Some(b"out")).unwrap();
+        assert_eq!(payload, [[1u8].as_slice(), &[2; 32], b"out"].concat());
+    }
+
+    #[test]
+    fn test_dev_mode_proof_rejected_in_production() {
+        assert_eq!(
+            check_proof_mode(true, false),
+            Err(ChannelError::DevModeProof)
+        );
+        assert_eq!(check_proof_mode(false, false), Ok(()));
+    }
+
+    #[test]
+    fn test_proof_modes_accepted_in_dev_mode() {
+        assert_eq!(check_proof_mode(true, true), Ok(()));
+        assert_eq!(check_proof_mode(false, true), Ok(()));
+    }
+
+    #[test]
+    fn test_status_carries_dev_mode() {
+        let mut st = StatusV1T::default();
+        st.dev_mode = true;
+        let mut fbb = flatbuffers::FlatBufferBuilder::new();
+        let offset = st.pack(&mut fbb);
+        fbb.finish(offset, None);
+        assert!(root_as_status_v1(fbb.finished_data()).unwrap().dev_mode());
+
+        // statuses from provers that predate the field are not dev mode
+        let data = status(Some(vec![]));
+        assert!(!root_as_status_v1(&data).unwrap().dev_mode());
+    }
+
+    fn status(committed_outputs: Option<Vec<u8>>) -> Vec<u8> {
+        let mut st = StatusV1T::default();
+        st.execution_id = Some("test".to_string());
+        st.status = StatusTypes::Completed;
+        st.proof = Some(vec![1; 256]);
+        st.execution_digest = Some(vec![2; 32]);
+        st.input_digest = Some(vec![3; 32]);
+        st.committed_outputs = committed_outputs;
+        st.assumption_digest = Some(vec![4; 32]);
+        let mut fbb = flatbuffers::FlatBufferBuilder::new();
+        let offset = st.pack(&mut fbb);
+        fbb.finish(offset, None);
+        fbb.finished_data().to_vec()
+    }
+
+    #[test]
+    fn test_empty_committed_outputs_are_verified() {
+        let data = status(Some(vec![]));
+        let st = root_as_status_v1(&data).unwrap();
+        let parts = ProofParts::from_status(&st).unwrap();
+        assert_eq!(parts.committed_outputs, &[] as &[u8]);
+        assert_eq!(parts.input_digest, &[3; 32]);
+
+        // the callback still receives the input digest when nothing was committed
+        let payload = callback_payload(&[1], parts.input_digest, Some(parts.committed_outputs));
+        assert_eq!(payload, Ok([[1u8].as_slice(), &[3; 32]].concat()));
+    }
+
+    #[test]
+    fn test_missing_committed_outputs_fail_proving() {
+        let data = status(None);
+        let st = root_as_status_v1(&data).unwrap();
+        assert_eq!(ProofParts::from_status(&st), None);
+    }
+
+    #[test]
+    fn test_oversized_callback_payload() {
+        let fits = vec![0; MAX_CALLBACK_PAYLOAD_LEN - 33];
+        assert_eq!(
+            callback_payload(&[1], &[2; 32], Some(&fits)).map(|p| p.len()),
+            Ok(MAX_CALLBACK_PAYLOAD_LEN)
+        );
+        let oversized = vec![0; MAX_CALLBACK_PAYLOAD_LEN];
+        assert_eq!(
+            callback_payload(&[1], &[2; 32], Some(&oversized)),
+            Err(ChannelError::PayloadTooLarge)
+        );
+    }
 }
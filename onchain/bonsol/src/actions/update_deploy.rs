@@ -0,0 +1,215 @@
+use crate::{assertions::*, error::ChannelError, utilities::*};
+
+use bonsol_interface::{
+    bonsol_schema::{root_as_deploy_v1, ChannelInstruction, DeployV1},
+    util::{deployment_address_seeds, img_id_hash},
+};
+
+use solana_program::{
+    account_info::AccountInfo, msg, program_error::ProgramError, program_memory::sol_memcpy,
+    pubkey::Pubkey, system_program,
+};
+
+pub struct UpdateDeployAccounts<'a> {
+    pub deployer: &'a AccountInfo<'a>,
+    pub payer: &'a AccountInfo<'a>,
+    pub deployment: &'a AccountInfo<'a>,
+    pub system_program: &'a AccountInfo<'a>,
+}
+
+impl<'a> UpdateDeployAccounts<'a> {
+    fn from_instruction(accounts: &'a [AccountInfo<'a>]) -> Result<Self, ChannelError> {
+        if accounts.len() < 4 {
+            return Err(ChannelError::InvalidInstruction);
+        }
+        let ua = UpdateDeployAccounts {
+            deployer: &accounts[0],
+            payer: &accounts[1],
+            deployment: &accounts[2],
+            system_program: &accounts[3],
+        };
+        check_writable_signer(ua.deployer, ChannelError::InvalidDeployerAccount)?;
+        check_writable_signer(ua.payer, ChannelError::InvalidPayerAccount)?;
+        check_writeable(ua.deployment, ChannelError::InvalidDeploymentAccount)?;
+        check_owner(
+            ua.deployment,
+            &crate::ID,
+            ChannelError::InvalidDeploymentAccount,
+        )?;
+        check_key_match(
+            ua.system_program,
+            &system_program::ID,
+            ChannelError::InvalidInstruction,
+        )?;
+        Ok(ua)
+    }
+}
+
+/// The original deployer may point a deployment at a new url, everything else is fixed because
+/// the image id and sha256 are digests of the program the deployment describes.
+pub fn check_deployment_update(
+    existing: &DeployV1,
+    update: &DeployV1,
+    authority: &Pubkey,
+) -> Result<(), ChannelError> {
+    let existing_owner = existing
+        .owner()
+        .ok_or(ChannelError::InvalidDeploymentAccount)?;
+    check_bytes_match(
+        existing_owner.bytes(),
+        authority.as_ref(),
+        ChannelError::InvalidDeployerAccount,
+    )?;
+    let update_owner = update.owner().ok_or(ChannelError::InvalidInstruction)?;
+    check_bytes_match(
+        update_owner.bytes(),
+        authority.as_ref(),
+        ChannelError::InvalidDeployerAccount,
+    )?;
+    if update.url().is_none() {
+        return Err(ChannelError::InvalidInstruction);
+    }
+    if existing.image_id() != update.image_id()
+        || existing.size_() != update.size_()
+        || existing.program_name() != update.program_name()
+        || existing.inputs().map(|i| i.bytes()) != update.inputs().map(|i| i.bytes())
+        || existing.sha256().map(|s| s.bytes()) != update.sha256().map(|s| s.bytes())
+    {
+        return Err(ChannelError::DeploymentImmutable);
+    }
+    Ok(())
+}
+
+pub fn process_update_deploy_v1<'a>(
+    accounts: &'a [AccountInfo<'a>],
+    ix: ChannelInstruction<'a>,
+) -> Result<(), ProgramError> {
+    let update = ix
+        .deploy_v1_nested_flatbuffer()
+        .ok_or(ChannelError::InvalidInstruction)?;
+    let ua = UpdateDeployAccounts::from_instruction(accounts)?;
+    let image_id = update.image_id().ok_or(ChannelError::InvalidInstruction)?;
+    check_pda(
+        &deployment_address_seeds(&img_id_hash(image_id)),
+        ua.deployment.key,
+        ChannelError::InvalidDeploymentAccountPDA,
+    )?;
+    {
+        let deploy_data = ua
+            .deployment
+            .try_borrow_data()
+            .map_err(|_| ChannelError::CannotBorrowData)?;
+        let existing =
+            root_as_deploy_v1(&deploy_data).map_err(|_| ChannelError::InvalidDeploymentAccount)?;
+        check_deployment_update(&existing, &update, ua.deployer.key)?;
+    }
+    let dp_bytes = ix.deploy_v1().unwrap().bytes();
+    resize_program_account(ua.deployment, ua.payer, dp_bytes.len())?;
+    sol_memcpy(
+        &mut ua.deployment.data.borrow_mut(),
+        dp_bytes,
+        dp_bytes.len(),
+    );
+    msg!("Deployment url updated");
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use bonsol_interface::bonsol_schema::{DeployV1T, ProgramInputType};
+
+    use super::*;
+
+    const IMAGE_ID: &str = "68f4b0c5f9ce034aa60ceb264a18d6c410a3af68fafd931bcfd9ebe7c1e42960";
+
+    fn deployment(owner: &Pubkey, image_id: &str, url: &str) -> Vec<u8> {
+        deployment_with_sha256(owner, image_id, url, None)
+    }
+
+    fn deployment_with_sha256(
+        owner: &Pubkey,
+        image_id: &str,
+        url: &str,
+        sha256: Option<[u8; 32]>,
+    ) -> Vec<u8> {
+        let mut deployment = DeployV1T::default();
+        deployment.owner = Some(owner.to_bytes().to_vec());
+        deployment.image_id = Some(image_id.to_string());
+        deployment.program_name = Some("test".to_string());
+        deployment.url = Some(url.to_string());
+        deployment.size_ = 1024;
+        deployment.inputs = Some(vec![ProgramInputType::Public]);
+        deployment.sha256 = sha256.map(|s| s.to_vec());
+        let mut fbb = flatbuffers::FlatBufferBuilder::new();
+        let offset = deployment.pack(&mut fbb);
+        fbb.finish(offset, None);
+        fbb.finished_data().to_vec()
+    }
+
+    fn check(existing: &[u8], update: &[u8], authority: &Pubkey) -> Result<(), ChannelError> {
+        check_deployment_update(
+            &root_as_deploy_v1(existing).unwrap(),
+            &root_as_deploy_v1(update).unwrap(),
+            authority,
+        )
+    }
+
+    #[test]
+    fn test_deployer_updates_url() {
+        let deployer = Pubkey::new_unique();
+        let existing = deployment(&deployer, IMAGE_ID, "https://a/p");
+        let update = deployment(&deployer, IMAGE_ID, "https://b/p");
+        assert_eq!(check(&existing, &update, &deployer), Ok(()));
+    }
+
+    #[test]
+    fn test_only_deployer_updates() {
+        let deployer = Pubkey::new_unique();
+        let other = Pubkey::new_unique();
+        let existing = deployment(&deployer, IMAGE_ID, "https://a/p");
+        let update = deployment(&other, IMAGE_ID, "https://b/p");
+        assert_eq!(
+            check(&existing, &update, &other),
+            Err(ChannelError::InvalidDeployerAccount)
+        );
+        // the stored owner cannot be swapped out either
+        let update = deployment(&other, IMAGE_ID, "https://b/p");
+        assert_eq!(
+            check(&existing, &update, &deployer),
+            Err(ChannelError::InvalidDeployerAccount)
+        );
+    }
+
+    #[test]
+    fn test_image_id_is_immutable() {
+        let deployer = Pubkey::new_unique();
+        let existing = deployment(&deployer, IMAGE_ID, "https://a/p");
+        let update = deployment(
+            &deployer,
+            "310fe598e8e3e92fa805bc272d7f587898bb8b68c4d5d7938db884abaa76e15c",
+            "https://b/p",
+        );
+        assert_eq!(
+            check(&existing, &update, &deployer),
+            Err(ChannelError::DeploymentImmutable)
+        );
+    }
+
+    #[test]
+    fn test_sha256_is_immutable() {
+        let deployer = Pubkey::new_unique();
+        let existing = deployment_with_sha256(&deployer, IMAGE_ID, "https://a/p", Some([1; 32]));
+        let update = deployment_with_sha256(&deployer, IMAGE_ID, "https://b/p", Some([1; 32]));
+        assert_eq!(check(&existing, &update, &deployer), Ok(()));
+        let update = deployment_with_sha256(&deployer, IMAGE_ID, "https://b/p", Some([2; 32]));
+        assert_eq!(
+            check(&existing, &update, &deployer),
+            Err(ChannelError::DeploymentImmutable)
+        );
+        let update = deployment(&deployer, IMAGE_ID, "https://b/p");
+        assert_eq!(
+            check(&existing, &update, &deployer),
+            Err(ChannelError::DeploymentImmutable)
+        );
+    }
+}
@@ -1,6 +1,8 @@
+use bonsol_interface::bonsol_schema::Account;
 use solana_program::{
     account_info::AccountInfo,
-    program_memory,
+    instruction::AccountMeta,
+    msg, program_memory,
     pubkey::{Pubkey, PUBKEY_BYTES},
 };
 
@@ -83,3 +85,103 @@ pub fn check_owner(
     }
     Ok(())
 }
+
+/// Checks a callback extra account against the one stored in the execution request and returns
+/// the meta to pass it to the callback with, never as a signer. An account passed writable that
+/// was requested read only is downgraded, since it may be writable elsewhere in the transaction.
+/// One requested writable but passed read only is rejected, or passed read only in dev mode.
+pub fn assert_account_matches(
+    expected: &Account,
+    actual: &AccountInfo,
+    dev_mode: bool,
+) -> Result<AccountMeta, ChannelError> {
+    let key: [u8; 32] = expected.pubkey().into();
+    check_bytes_match(
+        actual.key.as_ref(),
+        &key,
+        ChannelError::InvalidCallbackExtraAccounts,
+    )?;
+    let expected_writable = expected.writable() == 1;
+    if expected_writable && !actual.is_writable {
+        if !dev_mode {
+            return Err(ChannelError::InvalidCallbackExtraAccounts);
+        }
+        msg!("Callback account {} should be writable", actual.key);
+    }
+    if expected_writable && actual.is_writable {
+        Ok(AccountMeta::new(*actual.key, false))
+    } else {
+        Ok(AccountMeta::new_readonly(*actual.key, false))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn check(
+        expected_writable: bool,
+        actual_key: &Pubkey,
+        actual_writable: bool,
+        dev_mode: bool,
+    ) -> Result<AccountMeta, ChannelError> {
+        let expected_key = Pubkey::new_from_array([1; 32]);
+        let expected = Account::new(expected_writable as u8, &expected_key.to_bytes());
+        let owner = Pubkey::new_unique();
+        let mut lamports = 0;
+        let mut data: Vec<u8> = vec![];
+        let actual = AccountInfo::new(
+            actual_key,
+            false,
+            actual_writable,
+            &mut lamports,
+            &mut data,
+            &owner,
+            false,
+            0,
+        );
+        assert_account_matches(&expected, &actual, dev_mode)
+    }
+
+    #[test]
+    fn test_account_matches() {
+        let key = Pubkey::new_from_array([1; 32]);
+        assert_eq!(
+            check(true, &key, true, false),
+            Ok(AccountMeta::new(key, false))
+        );
+        assert_eq!(
+            check(false, &key, false, false),
+            Ok(AccountMeta::new_readonly(key, false))
+        );
+        // write access the request did not ask for is not forwarded
+        assert_eq!(
+            check(false, &key, true, false),
+            Ok(AccountMeta::new_readonly(key, false))
+        );
+    }
+
+    #[test]
+    fn test_account_key_mismatch() {
+        let other = Pubkey::new_unique();
+        for dev_mode in [false, true] {
+            assert_eq!(
+                check(true, &other, true, dev_mode),
+                Err(ChannelError::InvalidCallbackExtraAccounts)
+            );
+        }
+    }
+
+    #[test]
+    fn test_account_writability_mismatch() {
+        let key = Pubkey::new_from_array([1; 32]);
+        assert_eq!(
+            check(true, &key, false, false),
+            Err(ChannelError::InvalidCallbackExtraAccounts)
+        );
+        assert_eq!(
+            check(true, &key, false, true),
+            Ok(AccountMeta::new_readonly(key, false))
+        );
+    }
+}
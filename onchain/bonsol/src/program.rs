@@ -22,6 +22,12 @@ pub fn program<'a>(
         ChannelInstructionIxType::StatusV1 => {
             process_status_v1(accounts, ix)?;
         }
+        ChannelInstructionIxType::ReclaimV1 => {
+            process_reclaim_v1(accounts)?;
+        }
+        ChannelInstructionIxType::UpdateDeployV1 => {
+            process_update_deploy_v1(accounts, ix)?;
+        }
         _ => return Err(ChannelError::InvalidInstruction.into()),
     };
     Ok(())
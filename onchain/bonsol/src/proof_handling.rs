@@ -1,37 +1,112 @@
 use std::ops::Neg;
 
 use ark_serialize::{CanonicalDeserialize, CanonicalSerialize, Compress, Validate};
+use bonsol_interface::prover_version::{ProverVersion, VERSION_V1_0_1, VERSION_V1_2_1};
 use groth16_solana::groth16::{Groth16Verifier, Groth16Verifyingkey};
 use solana_program::hash::hashv;
 
 use crate::{
     error::ChannelError,
-    prover::{PROVER_CONSTANTS_V1_0_1, PROVER_CONSTANTS_V1_2_1},
+    prover::{ProverConstants, PROVER_CONSTANTS_V1_0_1, PROVER_CONSTANTS_V1_2_1},
     verifying_key::VERIFYINGKEY,
 };
 
 type G1 = ark_bn254::g1::G1Affine;
 
-pub fn verify_risc0_v1_0_1(proof: &[u8], inputs: &[u8]) -> Result<bool, ChannelError> {
-    let ins: [[u8; 32]; 5] = [
-        sized_range::<32>(&inputs[0..32])?,
-        sized_range::<32>(&inputs[32..64])?,
-        sized_range::<32>(&inputs[64..96])?,
-        sized_range::<32>(&inputs[96..128])?,
-        sized_range::<32>(&inputs[128..160])?,
-    ];
-    verify_proof::<5>(proof, ins, &VERIFYINGKEY)
+/// Prepares the groth16 public inputs of a risc0 prover version and verifies proofs against them.
+/// Versions differ only in their constants, so supporting another version is a new implementation
+/// and an arm in `proof_preparer`.
+pub trait ProofPreparer {
+    fn constants(&self) -> ProverConstants;
+
+    fn output_digest(
+        &self,
+        input_digest: &[u8],
+        committed_outputs: &[u8],
+        assumption_digest: &[u8],
+    ) -> [u8; 32] {
+        let jbytes = [input_digest, committed_outputs].concat(); // bad copy here
+        let journal = hashv(&[jbytes.as_slice()]);
+        hashv(&[
+            self.constants().output_hash.as_ref(),
+            journal.as_ref(),
+            assumption_digest,
+            &2u16.to_le_bytes(),
+        ])
+        .to_bytes()
+    }
+
+    fn prepare_inputs(
+        &self,
+        image_id: &str,
+        execution_digest: &[u8],
+        output_digest: &[u8],
+        system_exit_code: u32,
+        user_exit_code: u32,
+    ) -> Result<Vec<u8>, ChannelError> {
+        let constants = self.constants();
+        let imgbytes = hex::decode(image_id).map_err(|_| ChannelError::InvalidFieldElement)?;
+        let mut digest = hashv(&[
+            constants.receipt_claim_hash.as_ref(),
+            &[0u8; 32],
+            &imgbytes,
+            execution_digest,
+            output_digest,
+            &system_exit_code.to_le_bytes(),
+            &user_exit_code.to_le_bytes(),
+            &4u16.to_le_bytes(),
+        ])
+        .to_bytes();
+        let (c0, c1) = split_digest_reversed(&mut constants.control_root.clone())
+            .map_err(|_| ChannelError::InvalidFieldElement)?;
+        let (half1_bytes, half2_bytes) =
+            split_digest_reversed(&mut digest).map_err(|_| ChannelError::InvalidFieldElement)?;
+        let inputs = [
+            c0,
+            c1,
+            half1_bytes,
+            half2_bytes,
+            constants.bn254_control_id_bytes,
+        ]
+        .concat();
+        Ok(inputs)
+    }
+
+    fn verify(&self, proof: &[u8], inputs: &[u8]) -> Result<bool, ChannelError> {
+        let ins: [[u8; 32]; 5] = [
+            sized_range::<32>(&inputs[0..32])?,
+            sized_range::<32>(&inputs[32..64])?,
+            sized_range::<32>(&inputs[64..96])?,
+            sized_range::<32>(&inputs[96..128])?,
+            sized_range::<32>(&inputs[128..160])?,
+        ];
+        verify_proof::<5>(proof, ins, &VERIFYINGKEY)
+    }
 }
 
-pub fn verify_risc0_v1_2_1(proof: &[u8], inputs: &[u8]) -> Result<bool, ChannelError> {
-    let ins: [[u8; 32]; 5] = [
-        sized_range::<32>(&inputs[0..32])?,
-        sized_range::<32>(&inputs[32..64])?,
-        sized_range::<32>(&inputs[64..96])?,
-        sized_range::<32>(&inputs[96..128])?,
-        sized_range::<32>(&inputs[128..160])?,
-    ];
-    verify_proof::<5>(proof, ins, &VERIFYINGKEY)
+pub struct Risc0V1_0_1;
+
+impl ProofPreparer for Risc0V1_0_1 {
+    fn constants(&self) -> ProverConstants {
+        PROVER_CONSTANTS_V1_0_1
+    }
+}
+
+pub struct Risc0V1_2_1;
+
+impl ProofPreparer for Risc0V1_2_1 {
+    fn constants(&self) -> ProverConstants {
+        PROVER_CONSTANTS_V1_2_1
+    }
+}
+
+/// The preparer for proofs made by `version`, `None` for versions the program cannot verify
+pub fn proof_preparer(version: &ProverVersion) -> Option<&'static dyn ProofPreparer> {
+    match *version {
+        VERSION_V1_0_1 => Some(&Risc0V1_0_1),
+        VERSION_V1_2_1 => Some(&Risc0V1_2_1),
+        _ => None,
+    }
 }
 
 fn verify_proof<const NI: usize>(
@@ -66,106 +141,6 @@ fn verify_proof<const NI: usize>(
         .map_err(|_| ChannelError::ProofVerificationFailed)
 }
 
-pub fn output_digest_v1_0_1(
-    input_digest: &[u8],
-    committed_outputs: &[u8],
-    assumption_digest: &[u8],
-) -> [u8; 32] {
-    let jbytes = [input_digest, committed_outputs].concat(); // bad copy here
-    let journal = hashv(&[jbytes.as_slice()]);
-    hashv(&[
-        PROVER_CONSTANTS_V1_0_1.output_hash.as_ref(),
-        journal.as_ref(),
-        assumption_digest,
-        &2u16.to_le_bytes(),
-    ])
-    .to_bytes()
-}
-
-pub fn prepare_inputs_v1_0_1(
-    image_id: &str,
-    execution_digest: &[u8],
-    output_digest: &[u8],
-    system_exit_code: u32,
-    user_exit_code: u32,
-) -> Result<Vec<u8>, ChannelError> {
-    let imgbytes = hex::decode(image_id).map_err(|_| ChannelError::InvalidFieldElement)?;
-    let mut digest = hashv(&[
-        PROVER_CONSTANTS_V1_0_1.receipt_claim_hash.as_ref(),
-        &[0u8; 32],
-        &imgbytes,
-        execution_digest,
-        output_digest,
-        &system_exit_code.to_le_bytes(),
-        &user_exit_code.to_le_bytes(),
-        &4u16.to_le_bytes(),
-    ])
-    .to_bytes();
-    let (c0, c1) = split_digest_reversed(&mut PROVER_CONSTANTS_V1_0_1.control_root.clone())
-        .map_err(|_| ChannelError::InvalidFieldElement)?;
-    let (half1_bytes, half2_bytes) =
-        split_digest_reversed(&mut digest).map_err(|_| ChannelError::InvalidFieldElement)?;
-    let inputs = [
-        c0,
-        c1,
-        half1_bytes.try_into().unwrap(),
-        half2_bytes.try_into().unwrap(),
-        PROVER_CONSTANTS_V1_0_1.bn254_control_id_bytes,
-    ]
-    .concat();
-    Ok(inputs)
-}
-
-pub fn output_digest_v1_2_1(
-    input_digest: &[u8],
-    committed_outputs: &[u8],
-    assumption_digest: &[u8],
-) -> [u8; 32] {
-    let jbytes = [input_digest, committed_outputs].concat(); // bad copy here
-    let journal = hashv(&[jbytes.as_slice()]);
-    hashv(&[
-        PROVER_CONSTANTS_V1_2_1.output_hash.as_ref(),
-        journal.as_ref(),
-        assumption_digest,
-        &2u16.to_le_bytes(),
-    ])
-    .to_bytes()
-}
-
-pub fn prepare_inputs_v1_2_1(
-    image_id: &str,
-    execution_digest: &[u8],
-    output_digest: &[u8],
-    system_exit_code: u32,
-    user_exit_code: u32,
-) -> Result<Vec<u8>, ChannelError> {
-    let imgbytes = hex::decode(image_id).map_err(|_| ChannelError::InvalidFieldElement)?;
-    let mut digest = hashv(&[
-        PROVER_CONSTANTS_V1_2_1.receipt_claim_hash.as_ref(),
-        &[0u8; 32],
-        &imgbytes,
-        execution_digest,
-        output_digest,
-        &system_exit_code.to_le_bytes(),
-        &user_exit_code.to_le_bytes(),
-        &4u16.to_le_bytes(),
-    ])
-    .to_bytes();
-    let (c0, c1) = split_digest_reversed(&mut PROVER_CONSTANTS_V1_2_1.control_root.clone())
-        .map_err(|_| ChannelError::InvalidFieldElement)?;
-    let (half1_bytes, half2_bytes) =
-        split_digest_reversed(&mut digest).map_err(|_| ChannelError::InvalidFieldElement)?;
-    let inputs = [
-        c0,
-        c1,
-        half1_bytes.try_into().unwrap(),
-        half2_bytes.try_into().unwrap(),
-        PROVER_CONSTANTS_V1_2_1.bn254_control_id_bytes,
-    ]
-    .concat();
-    Ok(inputs)
-}
-
 /**
  * Reverse and split a digest into two halves
  * The first half is the left half of the digest
@@ -228,8 +203,65 @@ fn toggle_endianness<const N: usize>(bytes: &[u8]) -> Vec<u8> {
 
 #[cfg(test)]
 mod tests {
+    use hex_literal::hex;
+
     use super::*;
 
+    const IMAGE_ID: &str = "68f4b0c5f9ce034aa60ceb264a18d6c410a3af68fafd931bcfd9ebe7c1e42960";
+
+    /// Public inputs for `IMAGE_ID` with committed outputs "hello", a user exit code of 1 and
+    /// fixed digests, as produced by the per version functions the preparers replaced
+    fn proof_inputs(preparer: &dyn ProofPreparer) -> Vec<u8> {
+        let output_digest = preparer.output_digest(&[2; 32], b"hello", &[0; 32]);
+        assert_eq!(
+            output_digest,
+            hex!("a942112e53a1f98c0be31401d43851f98e86a9069358ac06885f9b8f98f6c3d5")
+        );
+        preparer
+            .prepare_inputs(IMAGE_ID, &[1; 32], &output_digest, 0, 1)
+            .unwrap()
+    }
+
+    #[test]
+    fn test_prepare_inputs_v1_0_1() {
+        let expected = hex!(
+            "000000000000000000000000000000000e8ed4340930069162f5fbc957a016a5"
+            "000000000000000000000000000000002e7edecb6fd9ca4733501ee430425d77"
+            "000000000000000000000000000000008b6b24b1216fe06b8c31abdf6c51f535"
+            "00000000000000000000000000000000ab547b028b30f62604f20540dd7295c9"
+            "0eb6febcf06c5df079111be116f79bd8c7e85dc9448776ef9a59aaf2624ab551"
+        );
+        assert_eq!(proof_inputs(&Risc0V1_0_1), expected);
+    }
+
+    #[test]
+    fn test_prepare_inputs_v1_2_1() {
+        let expected = hex!(
+            "000000000000000000000000000000004d5a42c577a3ab1231be642624d9da8c"
+            "0000000000000000000000000000000069042c9355281d562b4766691ceb35f7"
+            "000000000000000000000000000000008b6b24b1216fe06b8c31abdf6c51f535"
+            "00000000000000000000000000000000ab547b028b30f62604f20540dd7295c9"
+            "04446e66d300eb7fb45c9726bb53c793dda407a62e9601618bb43c5c14657ac0"
+        );
+        assert_eq!(proof_inputs(&Risc0V1_2_1), expected);
+    }
+
+    #[test]
+    fn test_output_digest_without_outputs() {
+        let expected = hex!("b684ac4f589e9cd4f53e022331e433f9f70c4f5062f78033a5fda86d0a7fa7f8");
+        assert_eq!(Risc0V1_0_1.output_digest(&[7; 32], &[], &[9; 32]), expected);
+        assert_eq!(Risc0V1_2_1.output_digest(&[7; 32], &[], &[9; 32]), expected);
+    }
+
+    #[test]
+    fn test_proof_preparer() {
+        let v1_0_1 = proof_preparer(&VERSION_V1_0_1).unwrap();
+        assert_eq!(v1_0_1.constants(), PROVER_CONSTANTS_V1_0_1);
+        let v1_2_1 = proof_preparer(&VERSION_V1_2_1).unwrap();
+        assert_eq!(v1_2_1.constants(), PROVER_CONSTANTS_V1_2_1);
+        assert!(proof_preparer(&ProverVersion::UnsupportedVersion).is_none());
+    }
+
     #[test]
     fn test_toggle_endianness() {
         let bytes = [1u8, 2, 3, 4, 5, 6, 7, 8];
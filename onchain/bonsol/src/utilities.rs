@@ -7,15 +7,32 @@ use solana_program::{
     system_instruction,
 };
 
-use crate::error::ChannelError;
-pub fn cleanup_execution_account(
+use crate::{assertions::check_writeable, error::ChannelError};
+pub fn cleanup_execution_account<'a>(
     exec: &AccountInfo,
-    requester: &AccountInfo,
+    requester: &AccountInfo<'a>,
+    refund_destination: Option<&AccountInfo<'a>>,
     exit_code: u8,
 ) -> Result<(), ProgramError> {
+    let refund_to = refund_account(requester, refund_destination)?;
     exec.realloc(1, false)?;
     sol_memset(&mut exec.data.borrow_mut(), exit_code, 1);
-    refund(exec, requester)
+    refund(exec, refund_to)
+}
+
+/// The account refunded when an execution is cleaned up, the requester unless a writable
+/// destination is given
+pub fn refund_account<'c, 'a>(
+    requester: &'c AccountInfo<'a>,
+    refund_destination: Option<&'c AccountInfo<'a>>,
+) -> Result<&'c AccountInfo<'a>, ChannelError> {
+    match refund_destination {
+        Some(destination) => {
+            check_writeable(destination, ChannelError::InvalidRefundDestination)?;
+            Ok(destination)
+        }
+        None => Ok(requester),
+    }
 }
 
 pub fn refund(exec: &AccountInfo, requester: &AccountInfo) -> Result<(), ProgramError> {
@@ -33,6 +50,33 @@ pub fn payout_tip(exec: &AccountInfo, prover: &AccountInfo, tip: u64) -> Result<
     Ok(())
 }
 
+/// Splits `tip` in proportion to `weights`, the integer remainder goes to the first prover so the
+/// parts always add up to `tip`
+pub fn split_tip(tip: u64, weights: &[u64]) -> Result<Vec<u64>, ChannelError> {
+    let total: u128 = weights.iter().map(|w| *w as u128).sum();
+    if total == 0 {
+        return Err(ChannelError::InvalidProverShares);
+    }
+    let mut parts = weights
+        .iter()
+        .map(|w| (tip as u128 * *w as u128 / total) as u64)
+        .collect::<Vec<_>>();
+    parts[0] += tip - parts.iter().sum::<u64>();
+    Ok(parts)
+}
+
+pub fn payout_tip_shares(
+    exec: &AccountInfo,
+    provers: &[AccountInfo],
+    weights: &[u64],
+    tip: u64,
+) -> Result<(), ProgramError> {
+    for (prover, part) in provers.iter().zip(split_tip(tip, weights)?) {
+        payout_tip(exec, prover, part)?;
+    }
+    Ok(())
+}
+
 pub fn transfer_unowned<'a>(
     from: &AccountInfo<'a>,
     to: &AccountInfo<'a>,
@@ -52,6 +96,26 @@ pub fn transfer_owned(
     Ok(())
 }
 
+/// Reallocates a program owned account to `size` bytes, the payer covers any extra rent
+pub fn resize_program_account<'a>(
+    account: &AccountInfo<'a>,
+    payer: &AccountInfo<'a>,
+    size: usize,
+) -> Result<(), ProgramError> {
+    let current = account.data_len();
+    if size == current {
+        return Ok(());
+    }
+    let rent = Rent::default();
+    let extra_rent = rent
+        .minimum_balance(size)
+        .saturating_sub(rent.minimum_balance(current));
+    if extra_rent > 0 {
+        transfer_unowned(payer, account, extra_rent)?;
+    }
+    account.realloc(size, false)
+}
+
 pub fn save_structure<'a>(
     account: &'a AccountInfo<'a>,
     seeds: &[&[u8]],
@@ -85,3 +149,111 @@ pub fn create_program_account<'a>(
     )
     .map_err(|_e| ChannelError::InvalidSystemProgram)
 }
+
+#[cfg(test)]
+mod tests {
+    use solana_program::pubkey::Pubkey;
+
+    use super::*;
+
+    fn refund_with(
+        destination_writable: bool,
+        redirect: bool,
+    ) -> Result<(u64, u64, u64), ProgramError> {
+        let keys = [
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+        ];
+        let rent = Rent::default().minimum_balance(1);
+        let mut lamports = [rent + 100, 0, 0];
+        let mut data = [[0u8; 0]; 3];
+        let [exec_lamports, requester_lamports, destination_lamports] = &mut lamports;
+        let [exec_data, requester_data, destination_data] = &mut data;
+        let exec = AccountInfo::new(
+            &keys[0],
+            false,
+            true,
+            exec_lamports,
+            exec_data,
+            &crate::ID,
+            false,
+            0,
+        );
+        let requester = AccountInfo::new(
+            &keys[1],
+            true,
+            true,
+            requester_lamports,
+            requester_data,
+            &keys[1],
+            false,
+            0,
+        );
+        let destination = AccountInfo::new(
+            &keys[2],
+            false,
+            destination_writable,
+            destination_lamports,
+            destination_data,
+            &keys[2],
+            false,
+            0,
+        );
+        let refund_to = refund_account(&requester, redirect.then_some(&destination))?;
+        refund(&exec, refund_to)?;
+        Ok((
+            exec.lamports(),
+            requester.lamports(),
+            destination.lamports(),
+        ))
+    }
+
+    #[test]
+    fn test_refund_defaults_to_requester() {
+        let rent = Rent::default().minimum_balance(1);
+        assert_eq!(refund_with(true, false), Ok((rent, 100, 0)));
+    }
+
+    #[test]
+    fn test_refund_to_destination() {
+        let rent = Rent::default().minimum_balance(1);
+        assert_eq!(refund_with(true, true), Ok((rent, 0, 100)));
+    }
+
+    #[test]
+    fn test_refund_destination_must_be_writable() {
+        assert_eq!(
+            refund_with(false, true),
+            Err(ChannelError::InvalidRefundDestination.into())
+        );
+    }
+
+    #[test]
+    fn test_split_tip_sums_to_tip() {
+        let weights: [&[u64]; 5] = [&[1], &[1, 1], &[1, 1, 1], &[3, 2, 5, 7], &[u64::MAX, 1]];
+        for tip in [0, 1, 7, 1_000, 999_999_937, u64::MAX] {
+            for weights in weights {
+                let parts = split_tip(tip, weights).unwrap();
+                assert_eq!(parts.len(), weights.len());
+                assert_eq!(parts.iter().map(|p| *p as u128).sum::<u128>(), tip as u128);
+            }
+        }
+    }
+
+    #[test]
+    fn test_split_tip_remainder_to_first() {
+        assert_eq!(split_tip(10, &[1, 1, 1]).unwrap(), vec![4, 3, 3]);
+        assert_eq!(split_tip(100, &[1, 3]).unwrap(), vec![25, 75]);
+        assert_eq!(split_tip(5, &[0, 1, 1]).unwrap(), vec![1, 2, 2]);
+    }
+
+    #[test]
+    fn test_split_tip_without_weight() {
+        assert_eq!(split_tip(10, &[]), Err(ChannelError::InvalidProverShares));
+        assert_eq!(
+            split_tip(10, &[0, 0]),
+            Err(ChannelError::InvalidProverShares)
+        );
+    }
+}
@@ -77,6 +77,7 @@ fn main<'a>(
                     verify_input_hash: true,
                     input_hash: Some(input_hash),
                     forward_output: true,
+                    refund_destination: None,
                 },
                 Some(CallbackConfig {
                     program_id: crate::id(),
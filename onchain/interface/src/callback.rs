@@ -2,6 +2,7 @@ use crate::error::ClientError;
 use crate::util::execution_address;
 use bonsol_schema::root_as_execution_request_v1;
 use solana_program::account_info::AccountInfo;
+use solana_program::entrypoint::ProgramResult;
 use solana_program::program_error::ProgramError;
 use solana_program::program_memory::sol_memcmp;
 use solana_program::pubkey::Pubkey;
@@ -10,6 +11,66 @@ pub struct BonsolCallback<'a> {
     pub input_digest: &'a [u8],
     pub committed_outputs: &'a [u8],
 }
+
+impl<'a> BonsolCallback<'a> {
+    /// Position of the first `marker` byte in the committed outputs
+    pub fn find_marker(&self, marker: u8) -> Option<usize> {
+        self.committed_outputs.iter().position(|b| *b == marker)
+    }
+
+    /// Reader over the committed outputs that follow the first `marker` byte
+    pub fn read_after_marker(&self, marker: u8) -> Option<OutputReader<'a>> {
+        self.find_marker(marker)
+            .map(|i| OutputReader::new(&self.committed_outputs[i + 1..]))
+    }
+
+    /// The committed outputs in consecutive `frame_size` byte frames, the last frame is shorter
+    /// when the outputs are not a multiple of `frame_size`. Panics if `frame_size` is 0.
+    pub fn frames(&self, frame_size: usize) -> impl Iterator<Item = &'a [u8]> {
+        self.committed_outputs.chunks(frame_size)
+    }
+}
+
+/// Reads fields out of committed outputs in order, every read fails with
+/// `ClientError::InvalidCallbackData` instead of panicking when the outputs are too short
+pub struct OutputReader<'a> {
+    data: &'a [u8],
+}
+
+impl<'a> OutputReader<'a> {
+    pub fn new(data: &'a [u8]) -> Self {
+        OutputReader { data }
+    }
+
+    /// Bytes that have not been read yet
+    pub fn remaining(&self) -> &'a [u8] {
+        self.data
+    }
+
+    pub fn read_bytes(&mut self, len: usize) -> Result<&'a [u8], ProgramError> {
+        if self.data.len() < len {
+            return Err(ClientError::InvalidCallbackData.into());
+        }
+        let (field, rest) = self.data.split_at(len);
+        self.data = rest;
+        Ok(field)
+    }
+
+    pub fn read_u8(&mut self) -> Result<u8, ProgramError> {
+        Ok(self.read_bytes(1)?[0])
+    }
+
+    pub fn read_u32(&mut self) -> Result<u32, ProgramError> {
+        let bytes = self.read_bytes(4)?;
+        Ok(u32::from_le_bytes(bytes.try_into().unwrap()))
+    }
+
+    /// Reads a field prefixed with its length as a little endian u32
+    pub fn read_length_prefixed(&mut self) -> Result<&'a [u8], ProgramError> {
+        let len = self.read_u32()?;
+        self.read_bytes(len as usize)
+    }
+}
 /// This is the callback handler for the bonsol program, use this to properly validate an incoming callback from bonsol
 /// Ensure you strip the instruction prefix from the data before passing it to this function and that the Execution Id
 /// matches the one in the execution request account
@@ -18,6 +79,17 @@ pub fn handle_callback<'a>(
     execution_account: &Pubkey,
     accounts: &[AccountInfo],
     stripped_data: &'a [u8],
+) -> Result<BonsolCallback<'a>, ProgramError> {
+    handle_callback_expecting(&[image_id], execution_account, accounts, stripped_data)
+}
+
+/// Same as `handle_callback` but accepts a callback from any of `image_ids`, so a program can
+/// keep accepting the previous image while its guest is being upgraded
+pub fn handle_callback_expecting<'a>(
+    image_ids: &[&str],
+    execution_account: &Pubkey,
+    accounts: &[AccountInfo],
+    stripped_data: &'a [u8],
 ) -> Result<BonsolCallback<'a>, ProgramError> {
     let er_info = accounts
         .first()
@@ -39,9 +111,13 @@ pub fn handle_callback<'a>(
     // Ensure this is a valid execution request data
     let er =
         root_as_execution_request_v1(er_data).map_err(|_| ProgramError::InvalidInstructionData)?;
-    if er.image_id() != Some(image_id) {
+    if !er.image_id().is_some_and(|id| image_ids.contains(&id)) {
         return Err(ClientError::InvalidCallbackImageId.into());
     }
+    // the data starts with the 32 byte input digest
+    if stripped_data.len() < 32 {
+        return Err(ClientError::TruncatedCallbackData.into());
+    }
     let (input_digest, committed_outputs) = stripped_data.split_at(32);
     Ok(BonsolCallback {
         input_digest,
@@ -49,6 +125,53 @@ pub fn handle_callback<'a>(
     })
 }
 
+/// Routes an instruction to `on_callback` when its data starts with `callback_prefix`, the
+/// instruction prefix set in the `CallbackConfig` of the execution. The callback is validated with
+/// `handle_callback` against the account returned by `execution_account` first, which is only
+/// called for callbacks. Any other instruction is passed to `on_instruction` unchanged.
+pub fn dispatch_callback<'a, E, C, I>(
+    image_id: &str,
+    callback_prefix: &[u8],
+    accounts: &'a [AccountInfo<'a>],
+    instruction_data: &'a [u8],
+    execution_account: E,
+    on_callback: C,
+    on_instruction: I,
+) -> ProgramResult
+where
+    E: FnOnce(&'a [AccountInfo<'a>]) -> Result<Pubkey, ProgramError>,
+    C: FnOnce(BonsolCallback<'a>, &'a [AccountInfo<'a>]) -> ProgramResult,
+    I: FnOnce(&'a [AccountInfo<'a>], &'a [u8]) -> ProgramResult,
+{
+    match instruction_data.strip_prefix(callback_prefix) {
+        Some(stripped_data) => {
+            let execution_account = execution_account(accounts)?;
+            let callback = handle_callback(image_id, &execution_account, accounts, stripped_data)?;
+            on_callback(callback, accounts)
+        }
+        None => on_instruction(accounts, instruction_data),
+    }
+}
+
+/// Finds the account at the address derived from `seeds` and `program_id` among `accounts` and
+/// checks that `program_id` owns it, returning it with its bump. Use this rather than taking the
+/// first account the program owns, which can be any account the caller chose to pass.
+pub fn find_pda_account<'a, 'b>(
+    accounts: &'b [AccountInfo<'a>],
+    seeds: &[&[u8]],
+    program_id: &Pubkey,
+) -> Result<(&'b AccountInfo<'a>, u8), ProgramError> {
+    let (address, bump) = Pubkey::find_program_address(seeds, program_id);
+    let account = accounts
+        .iter()
+        .find(|a| sol_memcmp(a.key.as_ref(), address.as_ref(), 32) == 0)
+        .ok_or::<ProgramError>(ClientError::CallbackAccountNotFound.into())?;
+    if sol_memcmp(account.owner.as_ref(), program_id.as_ref(), 32) != 0 {
+        return Err(ClientError::InvalidCallbackAccountOwner.into());
+    }
+    Ok((account, bump))
+}
+
 pub fn handle_callback_id<'a>(
     image_id: &str,
     execution_id: &str,
@@ -59,3 +182,278 @@ pub fn handle_callback_id<'a>(
     let (execution_account, _) = execution_address(request_account, execution_id.as_bytes());
     handle_callback(image_id, &execution_account, accounts, data)
 }
+
+#[cfg(test)]
+mod tests {
+    use std::cell::RefCell;
+
+    use bonsol_schema::ExecutionRequestV1T;
+
+    use super::*;
+
+    const IMAGE_ID: &str = "68f4b0c5f9ce034aa60ceb264a18d6c410a3af68fafd931bcfd9ebe7c1e42960";
+    const PREVIOUS_IMAGE_ID: &str =
+        "310fe598e8e3e92fa805bc272d7f587898bb8b68c4d5d7938db884abaa76e15c";
+
+    fn execution_request(image_id: &str) -> Vec<u8> {
+        let mut er = ExecutionRequestV1T::default();
+        er.execution_id = Some("test".to_string());
+        er.image_id = Some(image_id.to_string());
+        let mut fbb = flatbuffers::FlatBufferBuilder::new();
+        let offset = er.pack(&mut fbb);
+        fbb.finish(offset, None);
+        fbb.finished_data().to_vec()
+    }
+
+    /// Runs `handle_callback_expecting` with a signed execution account owned by bonsol
+    fn callback_for(
+        image_ids: &[&str],
+        er_image_id: &str,
+        stripped_data: &[u8],
+    ) -> Result<usize, ProgramError> {
+        let key = Pubkey::new_unique();
+        let mut lamports = 0;
+        let mut data = execution_request(er_image_id);
+        let er_info = AccountInfo::new(
+            &key,
+            true,
+            false,
+            &mut lamports,
+            &mut data,
+            &crate::util::ID,
+            false,
+            0,
+        );
+        handle_callback_expecting(image_ids, &key, &[er_info], stripped_data)
+            .map(|cb| cb.committed_outputs.len())
+    }
+
+    fn callback_data() -> Vec<u8> {
+        [[1u8; 32].as_slice(), b"output"].concat()
+    }
+
+    #[test]
+    fn test_callback_from_any_expected_image() {
+        let data = callback_data();
+        assert_eq!(callback_for(&[IMAGE_ID], IMAGE_ID, &data), Ok(6));
+        assert_eq!(
+            callback_for(&[PREVIOUS_IMAGE_ID, IMAGE_ID], IMAGE_ID, &data),
+            Ok(6)
+        );
+    }
+
+    #[test]
+    fn test_callback_from_unexpected_image() {
+        let data = callback_data();
+        assert_eq!(
+            callback_for(&[PREVIOUS_IMAGE_ID], IMAGE_ID, &data),
+            Err(ClientError::InvalidCallbackImageId.into())
+        );
+        assert_eq!(
+            callback_for(&[], IMAGE_ID, &data),
+            Err(ClientError::InvalidCallbackImageId.into())
+        );
+    }
+
+    #[test]
+    fn test_truncated_callback_data() {
+        assert_eq!(
+            callback_for(&[IMAGE_ID], IMAGE_ID, &[0; 10]),
+            Err(ClientError::TruncatedCallbackData.into())
+        );
+        assert_eq!(callback_for(&[IMAGE_ID], IMAGE_ID, &[0; 32]), Ok(0));
+    }
+
+    fn callback(committed_outputs: &[u8]) -> BonsolCallback {
+        BonsolCallback {
+            input_digest: &[0; 32],
+            committed_outputs,
+        }
+    }
+
+    #[test]
+    fn test_read_after_marker() {
+        let outputs = [1, 2, 0xaa, 7, 3, 0, 0, 0, b'a', b'b', b'c', 9];
+        let cb = callback(&outputs);
+        assert_eq!(cb.find_marker(0xaa), Some(2));
+        let mut reader = cb.read_after_marker(0xaa).unwrap();
+        assert_eq!(reader.read_u8().unwrap(), 7);
+        assert_eq!(reader.read_length_prefixed().unwrap(), b"abc");
+        assert_eq!(reader.remaining(), &[9]);
+    }
+
+    #[test]
+    fn test_frames_exact_multiple() {
+        let outputs = [1, 2, 3, 4, 5, 6];
+        let cb = callback(&outputs);
+        let frames: Vec<&[u8]> = cb.frames(2).collect();
+        assert_eq!(frames, [[1, 2], [3, 4], [5, 6]]);
+        assert_eq!(cb.frames(6).collect::<Vec<_>>(), [&outputs]);
+    }
+
+    #[test]
+    fn test_frames_with_remainder() {
+        let cb = callback(&[1, 2, 3, 4, 5]);
+        let frames: Vec<&[u8]> = cb.frames(2).collect();
+        assert_eq!(frames, [&[1, 2][..], &[3, 4], &[5]]);
+        assert_eq!(cb.frames(8).collect::<Vec<_>>(), [&[1, 2, 3, 4, 5]]);
+        assert_eq!(callback(&[]).frames(4).count(), 0);
+    }
+
+    #[test]
+    fn test_missing_marker() {
+        let cb = callback(&[1, 2, 3]);
+        assert_eq!(cb.find_marker(0xaa), None);
+        assert!(cb.read_after_marker(0xaa).is_none());
+    }
+
+    #[test]
+    fn test_truncated_after_marker() {
+        let cb = callback(&[1, 0xaa]);
+        let mut reader = cb.read_after_marker(0xaa).unwrap();
+        assert_eq!(
+            reader.read_u8(),
+            Err(ClientError::InvalidCallbackData.into())
+        );
+
+        // the length prefix promises more bytes than were committed
+        let cb = callback(&[0xaa, 4, 0, 0, 0, 1, 2]);
+        let mut reader = cb.read_after_marker(0xaa).unwrap();
+        assert_eq!(
+            reader.read_length_prefixed(),
+            Err(ClientError::InvalidCallbackData.into())
+        );
+        assert_eq!(reader.remaining(), &[1, 2]);
+    }
+
+    type Dispatched = (&'static str, Vec<u8>);
+
+    /// Dispatches `instruction_data` with a signed execution account owned by bonsol, returning
+    /// which branch ran and the data it was given. `expected` overrides the execution account the
+    /// program expects, which is otherwise the one passed in.
+    fn dispatch(
+        instruction_data: &[u8],
+        expected: Option<Pubkey>,
+    ) -> Result<Option<Dispatched>, ProgramError> {
+        let key = Pubkey::new_unique();
+        let mut lamports = 0;
+        let mut data = execution_request(IMAGE_ID);
+        let accounts = [AccountInfo::new(
+            &key,
+            true,
+            false,
+            &mut lamports,
+            &mut data,
+            &crate::util::ID,
+            false,
+            0,
+        )];
+        let dispatched = RefCell::new(None);
+        dispatch_callback(
+            IMAGE_ID,
+            &[1],
+            &accounts,
+            instruction_data,
+            |accounts| Ok(expected.unwrap_or(*accounts[0].key)),
+            |cb, _| {
+                *dispatched.borrow_mut() = Some(("callback", cb.committed_outputs.to_vec()));
+                Ok(())
+            },
+            |_, data| {
+                *dispatched.borrow_mut() = Some(("instruction", data.to_vec()));
+                Ok(())
+            },
+        )?;
+        Ok(dispatched.into_inner())
+    }
+
+    #[test]
+    fn test_dispatch_callback() {
+        let data = [[1u8].as_slice(), &callback_data()].concat();
+        assert_eq!(
+            dispatch(&data, None),
+            Ok(Some(("callback", b"output".to_vec())))
+        );
+    }
+
+    #[test]
+    fn test_dispatch_instruction() {
+        assert_eq!(
+            dispatch(&[0, 7, 7], None),
+            Ok(Some(("instruction", vec![0, 7, 7])))
+        );
+    }
+
+    #[test]
+    fn test_dispatch_invalid_callback() {
+        let data = [[1u8].as_slice(), &callback_data()].concat();
+        assert_eq!(
+            dispatch(&data, Some(Pubkey::new_unique())),
+            Err(ClientError::InvalidCallbackInstructionAccounts.into())
+        );
+        assert_eq!(
+            dispatch(&[1, 0], None),
+            Err(ClientError::TruncatedCallbackData.into())
+        );
+    }
+
+    #[test]
+    fn test_find_pda_account_skips_decoy() {
+        let program_id = Pubkey::new_unique();
+        let seeds: &[&[u8]] = &[b"storage", b"test"];
+        let (address, bump) = Pubkey::find_program_address(seeds, &program_id);
+        let decoy = Pubkey::new_unique();
+        let (mut decoy_lamports, mut lamports) = (0, 0);
+        let (mut decoy_data, mut data) = (vec![0u8; 8], vec![0u8; 8]);
+        let accounts = [
+            AccountInfo::new(
+                &decoy,
+                false,
+                true,
+                &mut decoy_lamports,
+                &mut decoy_data,
+                &program_id,
+                false,
+                0,
+            ),
+            AccountInfo::new(
+                &address,
+                false,
+                true,
+                &mut lamports,
+                &mut data,
+                &program_id,
+                false,
+                0,
+            ),
+        ];
+        let (account, found_bump) = find_pda_account(&accounts, seeds, &program_id).unwrap();
+        assert_eq!(account.key, &address);
+        assert_eq!(found_bump, bump);
+
+        let err = find_pda_account(&accounts[..1], seeds, &program_id).unwrap_err();
+        assert_eq!(err, ClientError::CallbackAccountNotFound.into());
+    }
+
+    #[test]
+    fn test_find_pda_account_checks_owner() {
+        let program_id = Pubkey::new_unique();
+        let seeds: &[&[u8]] = &[b"storage"];
+        let (address, _) = Pubkey::find_program_address(seeds, &program_id);
+        let other_owner = Pubkey::new_unique();
+        let mut lamports = 0;
+        let mut data: Vec<u8> = vec![];
+        let accounts = [AccountInfo::new(
+            &address,
+            false,
+            true,
+            &mut lamports,
+            &mut data,
+            &other_owner,
+            false,
+            0,
+        )];
+        let err = find_pda_account(&accounts, seeds, &program_id).unwrap_err();
+        assert_eq!(err, ClientError::InvalidCallbackAccountOwner.into());
+    }
+}
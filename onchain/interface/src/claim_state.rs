@@ -1,3 +1,5 @@
+use std::mem::size_of;
+
 use bytemuck::{Pod, Zeroable};
 
 use crate::error::ClientError;
@@ -19,6 +21,17 @@ pub struct ClaimStateV1 {
     pub block_commitment: u64,
 }
 
+/// Most provers a claim can split its tip between
+pub const MAX_PROVER_SHARES: usize = 8;
+
+/// A prover's weight in the tip split, stored after the `ClaimStateV1` in the claim account
+#[repr(C)]
+#[derive(Pod, Copy, Clone, Zeroable, Debug, PartialEq)]
+pub struct ProverShareV1 {
+    pub prover: [u8; 32],
+    pub weight: u64,
+}
+
 pub struct ClaimStateHolder {
     data: Vec<u8>,
 }
@@ -31,21 +44,53 @@ impl ClaimStateHolder {
     }
 
     pub fn claim(&self) -> Result<&ClaimStateV1, ClientError> {
-        bytemuck::try_from_bytes(&self.data).map_err(|_| ClientError::InvalidClaimAccount)
+        bytemuck::try_from_bytes(ClaimStateV1::header(&self.data)?)
+            .map_err(|_| ClientError::InvalidClaimAccount)
+    }
+
+    pub fn shares(&self) -> Result<Vec<ProverShareV1>, ClientError> {
+        ClaimStateV1::load_shares(&self.data)
     }
 }
 
 impl ClaimStateV1 {
+    /// Size of a claim account holding `shares` prover shares
+    pub fn account_size(shares: usize) -> usize {
+        size_of::<Self>() + shares * size_of::<ProverShareV1>()
+    }
+
+    fn header(ca_data: &[u8]) -> Result<&[u8], ClientError> {
+        ca_data
+            .get(..size_of::<Self>())
+            .ok_or(ClientError::InvalidClaimAccount)
+    }
+
     pub fn load_claim(ca_data: &mut [u8]) -> Result<&Self, ClientError> {
-        bytemuck::try_from_bytes::<ClaimStateV1>(ca_data)
+        bytemuck::try_from_bytes::<ClaimStateV1>(Self::header(ca_data)?)
             .map_err(|_| ClientError::InvalidClaimAccount)
     }
 
     pub fn load_claim_owned(ca_data: &[u8]) -> Result<Self, ClientError> {
-        bytemuck::try_pod_read_unaligned::<ClaimStateV1>(ca_data)
+        bytemuck::try_pod_read_unaligned::<ClaimStateV1>(Self::header(ca_data)?)
             .map_err(|_| ClientError::InvalidClaimAccount)
     }
 
+    /// Prover shares stored after the claim, empty when the tip goes to the claimer alone
+    pub fn load_shares(ca_data: &[u8]) -> Result<Vec<ProverShareV1>, ClientError> {
+        let tail = ca_data
+            .get(size_of::<Self>()..)
+            .ok_or(ClientError::InvalidClaimAccount)?;
+        if tail.len() % size_of::<ProverShareV1>() != 0 {
+            return Err(ClientError::InvalidClaimAccount);
+        }
+        tail.chunks_exact(size_of::<ProverShareV1>())
+            .map(|share| {
+                bytemuck::try_pod_read_unaligned::<ProverShareV1>(share)
+                    .map_err(|_| ClientError::InvalidClaimAccount)
+            })
+            .collect()
+    }
+
     pub fn from_claim_ix(claimer: &Pubkey, slot: u64, block_commitment: u64) -> Self {
         ClaimStateV1 {
             claimer: claimer.to_bytes(),
@@ -59,4 +104,56 @@ impl ClaimStateV1 {
         let claim_data = bytemuck::bytes_of(claim);
         sol_memcpy(&mut ca.data.borrow_mut(), claim_data, claim_data.len());
     }
+
+    /// Writes `shares` after the claim, the account must be `account_size(shares.len())` bytes
+    #[cfg(feature = "on-chain")]
+    pub fn save_shares(shares: &[ProverShareV1], ca: &AccountInfo) {
+        let share_data: &[u8] = bytemuck::cast_slice(shares);
+        sol_memcpy(
+            &mut ca.data.borrow_mut()[size_of::<Self>()..],
+            share_data,
+            share_data.len(),
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_claim_with_shares_round_trip() {
+        let claim = ClaimStateV1 {
+            claimer: [1; 32],
+            claimed_at: 10,
+            block_commitment: 20,
+        };
+        let shares = [
+            ProverShareV1 {
+                prover: [2; 32],
+                weight: 3,
+            },
+            ProverShareV1 {
+                prover: [3; 32],
+                weight: 1,
+            },
+        ];
+        let data = [bytemuck::bytes_of(&claim), bytemuck::cast_slice(&shares)].concat();
+        assert_eq!(data.len(), ClaimStateV1::account_size(2));
+
+        let holder = ClaimStateHolder::new(data.clone());
+        assert_eq!(holder.claim().unwrap().block_commitment, 20);
+        assert_eq!(holder.shares().unwrap(), shares);
+        let owned = ClaimStateV1::load_claim_owned(&data).unwrap();
+        assert_eq!(owned.claimed_at, 10);
+    }
+
+    #[test]
+    fn test_claim_without_shares() {
+        let claim = ClaimStateV1::from_claim_ix(&Pubkey::new_unique(), 1, 2);
+        let data = bytemuck::bytes_of(&claim);
+        assert!(ClaimStateV1::load_shares(data).unwrap().is_empty());
+        assert!(ClaimStateV1::load_shares(&data[..10]).is_err());
+        assert!(ClaimStateV1::load_shares(&[data, &[0u8; 3]].concat()).is_err());
+    }
 }
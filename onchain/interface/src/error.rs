@@ -1,3 +1,4 @@
+use num_derive::{FromPrimitive, ToPrimitive};
 #[cfg(feature = "on-chain")]
 use solana_program::program_error::ProgramError;
 
@@ -24,6 +25,12 @@ pub enum ClientError {
     InvalidCallbackImageId,
     #[error("Execution Request Reused")]
     ExecutionRequestReused,
+    #[error("CallbackAccountNotFound")]
+    CallbackAccountNotFound,
+    #[error("InvalidCallbackAccountOwner")]
+    InvalidCallbackAccountOwner,
+    #[error("TruncatedCallbackData")]
+    TruncatedCallbackData,
 }
 
 impl From<ClientError> for ProgramError {
@@ -31,3 +38,312 @@ impl From<ClientError> for ProgramError {
         ProgramError::Custom(val as u32)
     }
 }
+
+/// Errors returned by the bonsol program, they reach clients as `ProgramError::Custom` with the
+/// variant index as the code so variants must only ever be appended.
+#[derive(thiserror::Error, Debug, Clone, Copy, PartialEq, Eq, FromPrimitive, ToPrimitive)]
+pub enum ChannelError {
+    #[error("Invalid Requester Account")]
+    InvalidRequesterAccount,
+    #[error("Invalid Execution Account")]
+    InvalidExecutionAccount,
+    #[error("Invalid Instruction")]
+    InvalidInstruction,
+    #[error("Invalid Input Data")]
+    InvalidInputs,
+    #[error("Invalid Input Length")]
+    InvalidInputLength,
+    #[error("Invalid Instruction Parsing")]
+    InvalidInstructionParse,
+    #[error("Invalid Callback Account")]
+    InvalidCallbackAccount,
+    #[error("Invalid system program")]
+    InvalidSystemProgram,
+    #[error("Cannot borrow data from account")]
+    CannotBorrowData,
+    #[error("Invalid Conversion")]
+    InvalidConversion,
+    #[error("Invalid Callback Program")]
+    InvalidCallbackProgram,
+    #[error("Invalid Proof")]
+    InvalidProof,
+    #[error("Proof Verification Failed")]
+    ProofVerificationFailed,
+    #[error("Invalid Public Inputs")]
+    InvalidPublicInputs,
+    #[error("Max block height required")]
+    MaxBlockHeightRequired,
+    #[error("Verify input digest requires digest")]
+    InputDigestRequired,
+    #[error("Invalid Payer Account")]
+    InvalidPayerAccount,
+    #[error("Invalid Deployer Account")]
+    InvalidDeployerAccount,
+    #[error("Invalid Deployment Account")]
+    InvalidDeploymentAccount,
+    #[error("Invalid Claimer Account")]
+    InvalidClaimerAccount,
+    #[error("Invalid Claim Account")]
+    InvalidClaimAccount,
+    #[error("Active claim already exists")]
+    ActiveClaimExists,
+    #[error("Invalid Stake Account")]
+    InvalidStakeAccount,
+    #[error("Insufficient Stake")]
+    InsufficientStake,
+    #[error("Inputs dont match")]
+    InputsDontMatch,
+    #[error("Invalid Field Element")]
+    InvalidFieldElement,
+    #[error("Missing Image Checksum")]
+    MissingImageChecksum,
+    #[error("Invalid Image Checksum")]
+    InvalidImageChecksum,
+    #[error("Transfer Error")]
+    TransferError,
+    #[error("Execution expired")]
+    ExecutionExpired,
+    #[error("Invalid Deployment Account PDA")]
+    InvalidDeploymentAccountPDA,
+    #[error("Invalid Callback Extra Accounts")]
+    InvalidCallbackExtraAccounts,
+    #[error("Invalid Input Type")]
+    InvalidInputType,
+    #[error("Deployment Already Exists")]
+    DeploymentAlreadyExists,
+    #[error("Invalid Execution Account Data")]
+    InvalidExecutionAccountData,
+    #[error("Invalid Execution Id")]
+    InvalidExecutionId,
+    #[error("Invalid Execution Account Owner")]
+    InvalidExecutionAccountOwner,
+    #[error("Unexpected Proof System")]
+    UnexpectedProofSystem,
+    #[error("Execution not expired")]
+    ExecutionNotExpired,
+    /// Reserved, status verification cannot tell prover versions apart from the proof
+    #[error("Prover Version Mismatch")]
+    ProverVersionMismatch,
+    #[error("Invalid Prover Shares")]
+    InvalidProverShares,
+    #[error("Invalid Refund Destination")]
+    InvalidRefundDestination,
+    #[error("Only The Deployment Url Can Be Updated")]
+    DeploymentImmutable,
+    #[error("Callback Payload Too Large")]
+    PayloadTooLarge,
+    #[error("Dev Mode Proof Not Accepted")]
+    DevModeProof,
+}
+
+impl ChannelError {
+    /// The error a `ProgramError::Custom` code stands for, `None` for codes this version of the
+    /// program does not return
+    pub fn from_code(code: u32) -> Option<Self> {
+        num_traits::FromPrimitive::from_u32(code).filter(|e: &Self| !e.is_reserved())
+    }
+
+    /// Reserved variants only hold their code, the program never returns them
+    pub const fn is_reserved(self) -> bool {
+        matches!(self, ChannelError::ProverVersionMismatch)
+    }
+
+    pub const fn code(self) -> u32 {
+        self as u32
+    }
+
+    /// What usually causes the error and what to change before retrying
+    pub const fn description(self) -> &'static str {
+        match self {
+            ChannelError::InvalidRequesterAccount => {
+                "the requester account must sign and be writable, check the transaction signers"
+            }
+            ChannelError::InvalidExecutionAccount => {
+                "the execution account is not the PDA of the requester and execution id, derive \
+                 it with `bonsol address --kind execution`"
+            }
+            ChannelError::InvalidInstruction => {
+                "the instruction data is not a bonsol instruction, rebuild it with the sdk or \
+                 interface crate matching the deployed program"
+            }
+            ChannelError::InvalidInputs => {
+                "the inputs do not match what the deployment expects, check their order and types"
+            }
+            ChannelError::InvalidInputLength => {
+                "an input is too long or empty, check the input size against the deployment"
+            }
+            ChannelError::InvalidInstructionParse => {
+                "the instruction data could not be parsed, upgrade the client to the program's \
+                 interface version"
+            }
+            ChannelError::InvalidCallbackAccount => {
+                "the callback accounts do not match the execution request, pass the accounts \
+                 listed in the request"
+            }
+            ChannelError::InvalidSystemProgram => {
+                "the system program account is wrong, pass the system program id"
+            }
+            ChannelError::CannotBorrowData => {
+                "an account was passed twice or is already borrowed, remove duplicate accounts"
+            }
+            ChannelError::InvalidConversion => {
+                "a value in the instruction is out of range, check numeric fields such as \
+                 tip and expiry"
+            }
+            ChannelError::InvalidCallbackProgram => {
+                "the callback program is not the one named in the execution request"
+            }
+            ChannelError::InvalidProof => {
+                "the proof is malformed, check the prover produced a groth16 proof for this image"
+            }
+            ChannelError::ProofVerificationFailed => {
+                "the proof does not verify against the image id and inputs, check the prover \
+                 used the deployed image"
+            }
+            ChannelError::InvalidPublicInputs => {
+                "the public inputs of the proof are malformed, regenerate the proof"
+            }
+            ChannelError::MaxBlockHeightRequired => {
+                "the execution request has no expiry, set an expiry or max block height"
+            }
+            ChannelError::InputDigestRequired => {
+                "verify input hash is set but no input digest was given, pass the digest or \
+                 disable input verification"
+            }
+            ChannelError::InvalidPayerAccount => {
+                "the payer must sign and be writable, check the transaction signers"
+            }
+            ChannelError::InvalidDeployerAccount => {
+                "the deployer must sign the deployment, check the transaction signers"
+            }
+            ChannelError::InvalidDeploymentAccount => {
+                "the deployment account is not the PDA of the image id, derive it with \
+                 `bonsol address --kind deployment`"
+            }
+            ChannelError::InvalidClaimerAccount => {
+                "the claimer must sign and be writable, check the node keypair"
+            }
+            ChannelError::InvalidClaimAccount => {
+                "the claim account is not the PDA of the execution account, derive it with \
+                 `bonsol address --kind claim`"
+            }
+            ChannelError::ActiveClaimExists => {
+                "another prover holds an active claim, wait for it to expire before claiming"
+            }
+            ChannelError::InvalidStakeAccount => "the stake account is wrong for this prover",
+            ChannelError::InsufficientStake => {
+                "the prover does not have enough stake, add stake before claiming"
+            }
+            ChannelError::InputsDontMatch => {
+                "the proven inputs do not match the input digest, prove with the inputs of the \
+                 request"
+            }
+            ChannelError::InvalidFieldElement => {
+                "a proof value is not a valid field element, regenerate the proof"
+            }
+            ChannelError::MissingImageChecksum => {
+                "the deployment has no image checksum, redeploy with the program checksum"
+            }
+            ChannelError::InvalidImageChecksum => {
+                "the image checksum is malformed, it must be a 32 byte sha256 of the program"
+            }
+            ChannelError::TransferError => {
+                "moving lamports failed, check the payer can cover the tip and rent"
+            }
+            ChannelError::ExecutionExpired => {
+                "execution expired before it was proven, increase the expiry or the tip"
+            }
+            ChannelError::InvalidDeploymentAccountPDA => {
+                "the deployment account address does not match the image id, derive it with \
+                 `bonsol address --kind deployment`"
+            }
+            ChannelError::InvalidCallbackExtraAccounts => {
+                "the callback extra accounts differ from the request, pass them in the same \
+                 order with the same writability"
+            }
+            ChannelError::InvalidInputType => {
+                "an input type is not allowed here, check the deployment's input types"
+            }
+            ChannelError::DeploymentAlreadyExists => {
+                "the image is already deployed, use the existing deployment or update its url"
+            }
+            ChannelError::InvalidExecutionAccountData => {
+                "the execution account does not hold an execution request, check the execution \
+                 id and requester"
+            }
+            ChannelError::InvalidExecutionId => {
+                "the execution id is empty or too long, use a shorter unique id"
+            }
+            ChannelError::InvalidExecutionAccountOwner => {
+                "the execution account is not owned by the bonsol program, check the address"
+            }
+            ChannelError::UnexpectedProofSystem => {
+                "the proof is for a different proof system than the deployment"
+            }
+            ChannelError::ExecutionNotExpired => {
+                "the execution has not expired yet, wait past its expiry before reclaiming"
+            }
+            ChannelError::ProverVersionMismatch => {
+                "reserved, the program does not return this error"
+            }
+            ChannelError::InvalidProverShares => {
+                "the prover shares do not add up, check they sum to the whole tip"
+            }
+            ChannelError::InvalidRefundDestination => {
+                "the refund destination is not the requester, pass the original requester"
+            }
+            ChannelError::DeploymentImmutable => {
+                "only the deployment url can be updated, deploy a new image for other changes"
+            }
+            ChannelError::PayloadTooLarge => {
+                "the callback payload is too large, commit fewer outputs from the guest"
+            }
+            ChannelError::DevModeProof => {
+                "the prover ran with RISC0_DEV_MODE set and cannot make real proofs, unset it on \
+                 the prover"
+            }
+        }
+    }
+}
+
+impl From<ChannelError> for ProgramError {
+    fn from(e: ChannelError) -> Self {
+        ProgramError::Custom(e as u32)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use super::*;
+
+    #[test]
+    fn test_every_code_has_a_description() {
+        let mut descriptions = HashSet::new();
+        for code in 0..=ChannelError::DevModeProof.code() {
+            let Some(error) = ChannelError::from_code(code) else {
+                assert_eq!(code, ChannelError::ProverVersionMismatch.code());
+                continue;
+            };
+            assert_eq!(error.code(), code);
+            assert_eq!(ProgramError::from(error), ProgramError::Custom(code));
+            assert!(!error.description().is_empty());
+            assert!(descriptions.insert(error.description()), "{:?}", error);
+        }
+        assert_eq!(
+            ChannelError::from_code(ChannelError::DevModeProof.code() + 1),
+            None
+        );
+    }
+
+    #[test]
+    fn test_codes_are_stable() {
+        assert_eq!(ChannelError::InvalidRequesterAccount.code(), 0);
+        assert_eq!(ChannelError::ProofVerificationFailed.code(), 12);
+        assert_eq!(ChannelError::ExecutionExpired.code(), 29);
+        assert_eq!(ChannelError::ProverVersionMismatch.code(), 39);
+        assert_eq!(ChannelError::PayloadTooLarge.code(), 43);
+        assert_eq!(ChannelError::DevModeProof.code(), 44);
+    }
+}
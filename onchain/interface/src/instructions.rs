@@ -6,7 +6,7 @@ use bonsol_schema::{
 use flatbuffers::{FlatBufferBuilder, WIPOffset};
 
 use crate::error::ClientError;
-use crate::util::{deployment_address, execution_address};
+use crate::util::{deployment_address, execution_address, execution_claim_address};
 
 #[cfg(feature = "on-chain")]
 use {
@@ -27,6 +27,53 @@ pub fn deploy_v1(
     program_name: &str,
     url: &str,
     inputs: Vec<ProgramInputType>,
+    sha256: Option<&[u8]>,
+) -> Result<Instruction, ClientError> {
+    deploy_instruction(
+        ChannelInstructionIxType::DeployV1,
+        signer,
+        image_id,
+        image_size,
+        program_name,
+        url,
+        inputs,
+        sha256,
+    )
+}
+
+/// Points an existing deployment at a new url. Everything but the url has to match the stored
+/// deployment, including `sha256`, and the signer has to be its original deployer.
+pub fn update_deploy_v1(
+    signer: &Pubkey,
+    image_id: &str,
+    image_size: u64,
+    program_name: &str,
+    url: &str,
+    inputs: Vec<ProgramInputType>,
+    sha256: Option<&[u8]>,
+) -> Result<Instruction, ClientError> {
+    deploy_instruction(
+        ChannelInstructionIxType::UpdateDeployV1,
+        signer,
+        image_id,
+        image_size,
+        program_name,
+        url,
+        inputs,
+        sha256,
+    )
+}
+
+#[allow(clippy::too_many_arguments)]
+fn deploy_instruction(
+    ix_type: ChannelInstructionIxType,
+    signer: &Pubkey,
+    image_id: &str,
+    image_size: u64,
+    program_name: &str,
+    url: &str,
+    inputs: Vec<ProgramInputType>,
+    sha256: Option<&[u8]>,
 ) -> Result<Instruction, ClientError> {
     let (deployment_account, _) = deployment_address(image_id);
     let accounts = vec![
@@ -41,6 +88,7 @@ pub fn deploy_v1(
     let name = fbb.create_string(program_name);
     let owner = fbb.create_vector(signer.as_ref());
     let fb_inputs = fbb.create_vector(inputs.as_slice());
+    let sha256 = sha256.map(|sha256| fbb.create_vector(sha256));
     let fbb_deploy = DeployV1::create(
         &mut fbb,
         &DeployV1Args {
@@ -50,6 +98,7 @@ pub fn deploy_v1(
             url: Some(url),
             size_: image_size,
             inputs: Some(fb_inputs),
+            sha256,
         },
     );
     fbb.finish(fbb_deploy, None);
@@ -59,7 +108,7 @@ pub fn deploy_v1(
     let fbb_ix = ChannelInstruction::create(
         &mut fbb,
         &ChannelInstructionArgs {
-            ix_type: ChannelInstructionIxType::DeployV1,
+            ix_type,
             deploy_v1: Some(ix),
             ..Default::default()
         },
@@ -69,6 +118,38 @@ pub fn deploy_v1(
     Ok(Instruction::new_with_bytes(crate::ID, ix_data, accounts))
 }
 
+/// Reclaims the lamports held by an expired execution request.
+/// The execution id is read from the execution account, so the instruction carries no payload.
+/// `refund_destination` must be the one the request was made with, it is refunded instead of the
+/// requester.
+pub fn reclaim_expired_v1(
+    requester: &Pubkey,
+    execution_id: &str,
+    refund_destination: Option<&Pubkey>,
+) -> Result<Instruction, ClientError> {
+    let (execution_account, _) = execution_address(requester, execution_id.as_bytes());
+    let (claim_account, _) = execution_claim_address(execution_account.as_ref());
+    let mut accounts = vec![
+        AccountMeta::new(*requester, true),
+        AccountMeta::new(execution_account, false),
+        AccountMeta::new_readonly(claim_account, false),
+    ];
+    if let Some(refund_destination) = refund_destination {
+        accounts.push(AccountMeta::new(*refund_destination, false));
+    }
+    let mut fbb = FlatBufferBuilder::new();
+    let fbb_ix = ChannelInstruction::create(
+        &mut fbb,
+        &ChannelInstructionArgs {
+            ix_type: ChannelInstructionIxType::ReclaimV1,
+            ..Default::default()
+        },
+    );
+    fbb.finish(fbb_ix, None);
+    let ix_data = fbb.finished_data();
+    Ok(Instruction::new_with_bytes(crate::ID, ix_data, accounts))
+}
+
 // todo hold attributes for scheme and versions selection
 #[derive(Debug, Clone)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
@@ -77,6 +158,9 @@ pub struct ExecutionConfig<'a> {
     pub verify_input_hash: bool,
     pub input_hash: Option<&'a [u8]>,
     pub forward_output: bool,
+    /// Refunded instead of the requester when the execution account is cleaned up
+    #[cfg_attr(feature = "serde", serde(default, with = "serde_helpers::optpubkey"))]
+    pub refund_destination: Option<Pubkey>,
 }
 
 #[cfg(feature = "serde")]
@@ -146,6 +230,7 @@ impl Default for ExecutionConfig<'_> {
             verify_input_hash: true,
             input_hash: None,
             forward_output: false,
+            refund_destination: None,
         }
     }
 }
@@ -296,6 +381,9 @@ pub fn execute_v1_with_accounts<'a>(
     let execution_id = fbb.create_string(execution_id);
 
     let input_digest = config.input_hash.map(|ih| fbb.create_vector(ih));
+    let refund_destination = config
+        .refund_destination
+        .map(|rd| fbb.create_vector(rd.as_ref()));
 
     // typically cli will pass None for the optional prover_version indicating bonsol should handle
     // the default case here
@@ -315,6 +403,7 @@ pub fn execute_v1_with_accounts<'a>(
             input_digest,
             callback_extra_accounts: extra_accounts,
             prover_version,
+            refund_destination,
         },
     );
     fbb.finish(fbb_execute, None);
@@ -0,0 +1,77 @@
+//! Symmetric encryption for private inputs published at a url. Inputs are encrypted with
+//! ChaCha20-Poly1305 and stored as the 12 byte nonce followed by the ciphertext and tag.
+//!
+//! Bonsol does not exchange keys, the requester shares the key out of band with the provers that
+//! may read the input. A prover holding the key resolves such inputs with
+//! `InputResolver::resolve_encrypted_inputs`.
+
+use anyhow::{anyhow, Result};
+use chacha20poly1305::aead::{Aead, AeadCore, KeyInit, OsRng};
+use chacha20poly1305::{ChaCha20Poly1305, Key, Nonce};
+
+pub const NONCE_LEN: usize = 12;
+
+#[derive(Clone)]
+pub struct InputKey(Key);
+
+impl InputKey {
+    pub fn new(key: [u8; 32]) -> Self {
+        InputKey(key.into())
+    }
+
+    pub fn generate() -> Self {
+        InputKey(ChaCha20Poly1305::generate_key(&mut OsRng))
+    }
+
+    /// Encrypts `plaintext` under a random nonce
+    pub fn encrypt(&self, plaintext: &[u8]) -> Result<Vec<u8>> {
+        let nonce = ChaCha20Poly1305::generate_nonce(&mut OsRng);
+        let ciphertext = ChaCha20Poly1305::new(&self.0)
+            .encrypt(&nonce, plaintext)
+            .map_err(|_| anyhow!("Failed to encrypt input"))?;
+        Ok([nonce.as_slice(), &ciphertext].concat())
+    }
+
+    pub fn decrypt(&self, data: &[u8]) -> Result<Vec<u8>> {
+        if data.len() < NONCE_LEN {
+            return Err(anyhow!("Encrypted input is too short"));
+        }
+        let (nonce, ciphertext) = data.split_at(NONCE_LEN);
+        ChaCha20Poly1305::new(&self.0)
+            .decrypt(Nonce::from_slice(nonce), ciphertext)
+            .map_err(|_| anyhow!("Failed to decrypt input, wrong key or corrupt data"))
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+
+    /// "private input" encrypted under `fixture_key` with the nonce 0..12
+    pub(crate) const ENCRYPTED_FIXTURE: &str =
+        "000102030405060708090a0b5c0798550defca0c7e1871bfcb1a35a05a34acc0e892b4d4a7844920e3";
+
+    pub(crate) fn fixture_key() -> InputKey {
+        InputKey::new([7; 32])
+    }
+
+    #[test]
+    fn test_decrypt_fixture() {
+        let data = hex::decode(ENCRYPTED_FIXTURE).unwrap();
+        assert_eq!(fixture_key().decrypt(&data).unwrap(), b"private input");
+    }
+
+    #[test]
+    fn test_encrypt_round_trip() {
+        let key = InputKey::generate();
+        let data = key.encrypt(b"private input").unwrap();
+        assert_eq!(key.decrypt(&data).unwrap(), b"private input");
+    }
+
+    #[test]
+    fn test_decrypt_with_wrong_key() {
+        let data = hex::decode(ENCRYPTED_FIXTURE).unwrap();
+        assert!(InputKey::new([8; 32]).decrypt(&data).is_err());
+        assert!(fixture_key().decrypt(&data[..NONCE_LEN - 1]).is_err());
+    }
+}
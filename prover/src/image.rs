@@ -1,3 +1,4 @@
+use std::fmt;
 use std::path::PathBuf;
 
 use anyhow::Result;
@@ -6,6 +7,28 @@ use risc0_binfmt::{MemoryImage, Program};
 use risc0_zkvm::{GUEST_MAX_MEM, PAGE_SIZE};
 use tokio::fs::read;
 
+/// Pages a memory image may hold unless configured otherwise, everything a guest can address
+pub const DEFAULT_MAX_PAGES: usize = GUEST_MAX_MEM / PAGE_SIZE;
+
+/// Returned when loading a program whose segments span more pages than allowed
+#[derive(Debug)]
+pub struct ImageTooLarge {
+    pub pages: usize,
+    pub max_pages: usize,
+}
+
+impl fmt::Display for ImageTooLarge {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Image has {} pages, more than the limit of {}",
+            self.pages, self.max_pages
+        )
+    }
+}
+
+impl std::error::Error for ImageTooLarge {}
+
 pub struct Image {
     pub id: String,
     pub data: Option<Program>,
@@ -26,36 +49,70 @@ impl Image {
         Ok(image)
     }
 
-    pub fn bytes(&self) -> Option<&Bytes> {
-        self.bytes.as_ref()
+    /// The pages the program's segments are loaded into, known before its memory image is built
+    fn program_pages(program: &Program) -> usize {
+        let mut pages = 0;
+        let mut last_page = None;
+        // image addresses are sorted, so the words of a page are next to each other
+        for page in program.image.keys().map(|addr| addr / PAGE_SIZE as u32) {
+            if last_page != Some(page) {
+                pages += 1;
+                last_page = Some(page);
+            }
+        }
+        pages
     }
 
-    pub fn from_bytes(bytes: Bytes) -> Result<Image> {
-        let program = Image::load_elf(&bytes)?;
+    fn check_pages(program: &Program, max_pages: usize) -> Result<()> {
+        let pages = Image::program_pages(program);
+        if pages > max_pages {
+            return Err(ImageTooLarge { pages, max_pages }.into());
+        }
+        Ok(())
+    }
+
+    fn from_program(
+        program: Program,
+        bytes: Bytes,
+        path: PathBuf,
+        max_pages: usize,
+    ) -> Result<Image> {
+        Image::check_pages(&program, max_pages)?;
         let img = Image::mem_img(&program)?;
         Ok(Image {
             id: img.compute_id().to_string(),
             bytes: Some(bytes),
             data: Some(program),
             size: img.pages.len() as u64 * PAGE_SIZE as u64,
-            path: PathBuf::new(),
+            path,
             last_used: 0,
         })
     }
 
+    pub fn bytes(&self) -> Option<&Bytes> {
+        self.bytes.as_ref()
+    }
+
+    pub fn from_bytes(bytes: Bytes) -> Result<Image> {
+        Image::from_bytes_with_max_pages(bytes, DEFAULT_MAX_PAGES)
+    }
+
+    /// Loads a program, failing with `ImageTooLarge` when its segments span more than `max_pages`
+    /// pages. The check runs before the memory image is built, so a huge deployment cannot take
+    /// the prover's memory.
+    pub fn from_bytes_with_max_pages(bytes: Bytes, max_pages: usize) -> Result<Image> {
+        let program = Image::load_elf(&bytes)?;
+        Image::from_program(program, bytes, PathBuf::new(), max_pages)
+    }
+
     pub async fn new(path: PathBuf) -> Result<Image> {
+        Image::new_with_max_pages(path, DEFAULT_MAX_PAGES).await
+    }
+
+    pub async fn new_with_max_pages(path: PathBuf, max_pages: usize) -> Result<Image> {
         let data = read(&path).await?;
         let program = Image::load_elf(&data)?;
-        let img = Image::mem_img(&program)?;
-
-        Ok(Image {
-            id: img.compute_id().to_string(),
-            bytes: Some(Bytes::from(data)),
-            data: Some(program),
-            size: img.pages.len() as u64 * PAGE_SIZE as u64,
-            path,
-            last_used: 0,
-        })
+        Image::from_program(program, Bytes::from(data), path, max_pages)
     }
 
     pub fn compress(&mut self) {
@@ -83,3 +140,55 @@ impl Image {
         Ok(image)
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const SIMPLE_IMAGE: &str = concat!(
+        env!("CARGO_MANIFEST_DIR"),
+        "/../elf/68f4b0c5f9ce034aa60ceb264a18d6c410a3af68fafd931bcfd9ebe7c1e42960"
+    );
+
+    /// A program with one word on each of `pages` pages, a sparse program whose memory image is
+    /// far larger than the program itself
+    fn sparse_program(pages: u32) -> Program {
+        let base = 0x0001_0000;
+        Program {
+            entry: base,
+            image: (0..pages)
+                .map(|page| (base + page * PAGE_SIZE as u32, 0))
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn test_max_pages() {
+        let elf = Bytes::from(std::fs::read(SIMPLE_IMAGE).unwrap());
+        let pages = Image::program_pages(&Image::load_elf(&elf).unwrap());
+        assert!(Image::from_bytes_with_max_pages(elf.clone(), pages).is_ok());
+
+        let err = Image::from_bytes_with_max_pages(elf, pages - 1)
+            .err()
+            .unwrap();
+        let err = err.downcast_ref::<ImageTooLarge>().unwrap();
+        assert_eq!(err.pages, pages);
+        assert_eq!(err.max_pages, pages - 1);
+    }
+
+    #[test]
+    fn test_oversized_program_rejected_before_its_memory_image() {
+        let mut program = sparse_program(4096);
+        // a word no memory image can hold, building one would fail with a different error
+        program.image.insert(GUEST_MAX_MEM as u32, 0);
+        let err = Image::from_program(program, Bytes::new(), PathBuf::new(), 4096)
+            .err()
+            .unwrap();
+        let err = err.downcast_ref::<ImageTooLarge>().unwrap();
+        assert_eq!(err.pages, 4097);
+        assert_eq!(err.max_pages, 4096);
+
+        let image = Image::from_program(sparse_program(4096), Bytes::new(), PathBuf::new(), 4096);
+        assert!(image.unwrap().size >= 4096 * PAGE_SIZE as u64);
+    }
+}
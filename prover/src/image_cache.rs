@@ -0,0 +1,137 @@
+use std::fs;
+use std::io::Write;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use anyhow::Result;
+use risc0_binfmt::MemoryImage;
+use tempfile::NamedTempFile;
+use tracing::warn;
+
+use crate::image::Image;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ImageCacheStats {
+    pub hits: u64,
+    pub misses: u64,
+}
+
+/// Memory images kept on disk keyed by image id, so proving the same program again skips
+/// rebuilding its memory image from the elf.
+pub struct MemoryImageCache {
+    dir: PathBuf,
+    hits: AtomicU64,
+    misses: AtomicU64,
+}
+
+impl MemoryImageCache {
+    pub fn new(dir: impl Into<PathBuf>) -> Result<Self> {
+        let dir = dir.into();
+        fs::create_dir_all(&dir)?;
+        Ok(MemoryImageCache {
+            dir,
+            hits: AtomicU64::new(0),
+            misses: AtomicU64::new(0),
+        })
+    }
+
+    fn path(&self, image_id: &str) -> PathBuf {
+        self.dir.join(format!("{}.memimg", image_id))
+    }
+
+    /// Returns the memory image of `image`, read from disk when cached. A cached memory image whose
+    /// id does not match `image.id` is discarded and rebuilt.
+    pub fn get_memory_image(&self, image: &Image) -> Result<MemoryImage> {
+        if let Some(memory_image) = self.read(&image.id) {
+            self.hits.fetch_add(1, Ordering::Relaxed);
+            return Ok(memory_image);
+        }
+        self.misses.fetch_add(1, Ordering::Relaxed);
+        let memory_image = image.get_memory_image()?;
+        if let Err(e) = self.write(&image.id, &memory_image) {
+            warn!(image_id = %image.id, "Failed to cache memory image: {}", e);
+        }
+        Ok(memory_image)
+    }
+
+    fn read(&self, image_id: &str) -> Option<MemoryImage> {
+        let path = self.path(image_id);
+        let bytes = fs::read(&path).ok()?;
+        match bincode::deserialize::<MemoryImage>(&bytes) {
+            Ok(memory_image) if memory_image.compute_id().to_string() == image_id => {
+                Some(memory_image)
+            }
+            _ => {
+                warn!(image_id, "Discarding invalid cached memory image");
+                let _ = fs::remove_file(&path);
+                None
+            }
+        }
+    }
+
+    fn write(&self, image_id: &str, memory_image: &MemoryImage) -> Result<()> {
+        // written aside and renamed so a concurrent read never sees a partial file, each writer
+        // gets its own temporary file so concurrent misses of the same image do not clash
+        let mut tmp = NamedTempFile::new_in(&self.dir)?;
+        tmp.write_all(&bincode::serialize(memory_image)?)?;
+        tmp.persist(self.path(image_id))?;
+        Ok(())
+    }
+
+    pub fn stats(&self) -> ImageCacheStats {
+        ImageCacheStats {
+            hits: self.hits.load(Ordering::Relaxed),
+            misses: self.misses.load(Ordering::Relaxed),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use bytes::Bytes;
+
+    use super::*;
+
+    const SIMPLE_IMAGE: &str = concat!(
+        env!("CARGO_MANIFEST_DIR"),
+        "/../elf/68f4b0c5f9ce034aa60ceb264a18d6c410a3af68fafd931bcfd9ebe7c1e42960"
+    );
+
+    fn simple_image() -> Image {
+        let elf = fs::read(SIMPLE_IMAGE).unwrap();
+        Image::from_bytes(Bytes::from(elf)).unwrap()
+    }
+
+    #[test]
+    fn test_second_load_reads_from_cache() {
+        let dir = tempfile::tempdir().unwrap();
+        let cache = MemoryImageCache::new(dir.path()).unwrap();
+        let mut image = simple_image();
+        let first = cache.get_memory_image(&image).unwrap();
+        // without the program the memory image can only come from the cache
+        image.compress();
+        let second = cache.get_memory_image(&image).unwrap();
+        assert_eq!(second.compute_id(), first.compute_id());
+        assert_eq!(cache.stats(), ImageCacheStats { hits: 1, misses: 1 });
+
+        let reopened = MemoryImageCache::new(dir.path()).unwrap();
+        assert!(reopened.get_memory_image(&image).is_ok());
+        assert_eq!(reopened.stats().hits, 1);
+    }
+
+    #[test]
+    fn test_mismatched_entry_is_rebuilt() {
+        let dir = tempfile::tempdir().unwrap();
+        let cache = MemoryImageCache::new(dir.path()).unwrap();
+        let image = simple_image();
+        cache.get_memory_image(&image).unwrap();
+        fs::write(cache.path(&image.id), b"not a memory image").unwrap();
+        assert!(cache.get_memory_image(&image).is_ok());
+
+        let other_id = "ab".repeat(32);
+        fs::copy(cache.path(&image.id), cache.path(&other_id)).unwrap();
+        assert!(cache.read(&other_id).is_none());
+        assert!(!cache.path(&other_id).exists());
+        assert_eq!(cache.stats(), ImageCacheStats { hits: 0, misses: 2 });
+    }
+}
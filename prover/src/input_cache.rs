@@ -0,0 +1,187 @@
+use std::collections::{HashMap, VecDeque};
+use std::sync::Mutex;
+
+use reqwest::Url;
+use sha2::{Digest, Sha256};
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct InputCacheStats {
+    pub hits: u64,
+    pub misses: u64,
+    pub entries: usize,
+    pub bytes: usize,
+}
+
+struct CacheEntry {
+    data: Vec<u8>,
+    hash: [u8; 32],
+}
+
+#[derive(Default)]
+struct CacheState {
+    entries: HashMap<Url, CacheEntry>,
+    // least recently used at the front
+    order: VecDeque<Url>,
+    bytes: usize,
+    hits: u64,
+    misses: u64,
+}
+
+impl CacheState {
+    fn touch(&mut self, url: &Url) {
+        if let Some(pos) = self.order.iter().position(|u| u == url) {
+            if let Some(u) = self.order.remove(pos) {
+                self.order.push_back(u);
+            }
+        }
+    }
+
+    fn remove(&mut self, url: &Url) {
+        if let Some(entry) = self.entries.remove(url) {
+            self.bytes -= entry.data.len();
+            self.order.retain(|u| u != url);
+        }
+    }
+
+    fn evict_oldest(&mut self) {
+        if let Some(url) = self.order.pop_front() {
+            if let Some(entry) = self.entries.remove(&url) {
+                self.bytes -= entry.data.len();
+            }
+        }
+    }
+}
+
+/// Least recently used cache of downloaded public inputs keyed by url, bounded by both the number
+/// of entries and the total size of the cached data.
+pub struct InputCache {
+    max_entries: usize,
+    max_bytes: usize,
+    state: Mutex<CacheState>,
+}
+
+impl InputCache {
+    pub fn new(max_entries: usize, max_bytes: usize) -> Self {
+        InputCache {
+            max_entries,
+            max_bytes,
+            state: Mutex::new(CacheState::default()),
+        }
+    }
+
+    /// Returns the cached data for `url`. When `expected_hash` is given the entry only counts as a
+    /// hit if the sha256 of the cached data matches it.
+    pub fn get(&self, url: &Url, expected_hash: Option<&[u8; 32]>) -> Option<Vec<u8>> {
+        let mut state = self.state.lock().unwrap();
+        let data = state
+            .entries
+            .get(url)
+            .filter(|e| expected_hash.map_or(true, |h| h == &e.hash))
+            .map(|e| e.data.clone());
+        if data.is_some() {
+            state.hits += 1;
+            state.touch(url);
+        } else {
+            state.misses += 1;
+        }
+        data
+    }
+
+    /// Caches `data` for `url`, evicting the least recently used entries to make room. Data larger
+    /// than the whole byte budget is not cached.
+    pub fn insert(&self, url: Url, data: Vec<u8>) {
+        if self.max_entries == 0 || data.len() > self.max_bytes {
+            return;
+        }
+        let mut state = self.state.lock().unwrap();
+        state.remove(&url);
+        while state.entries.len() >= self.max_entries || state.bytes + data.len() > self.max_bytes {
+            state.evict_oldest();
+        }
+        let hash = Sha256::digest(&data).into();
+        state.bytes += data.len();
+        state.order.push_back(url.clone());
+        state.entries.insert(url, CacheEntry { data, hash });
+    }
+
+    pub fn stats(&self) -> InputCacheStats {
+        let state = self.state.lock().unwrap();
+        InputCacheStats {
+            hits: state.hits,
+            misses: state.misses,
+            entries: state.entries.len(),
+            bytes: state.bytes,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn url(path: &str) -> Url {
+        Url::parse(&format!("https://inputs.example.com/{}", path)).unwrap()
+    }
+
+    #[test]
+    fn test_hit_and_miss() {
+        let cache = InputCache::new(4, 1024);
+        assert_eq!(cache.get(&url("a"), None), None);
+        cache.insert(url("a"), vec![1, 2, 3]);
+        assert_eq!(cache.get(&url("a"), None), Some(vec![1, 2, 3]));
+        assert_eq!(
+            cache.stats(),
+            InputCacheStats {
+                hits: 1,
+                misses: 1,
+                entries: 1,
+                bytes: 3
+            }
+        );
+    }
+
+    #[test]
+    fn test_expected_hash() {
+        let cache = InputCache::new(4, 1024);
+        cache.insert(url("a"), b"input".to_vec());
+        let hash: [u8; 32] = Sha256::digest(b"input").into();
+        assert!(cache.get(&url("a"), Some(&hash)).is_some());
+        assert!(cache.get(&url("a"), Some(&[0u8; 32])).is_none());
+    }
+
+    #[test]
+    fn test_evicts_least_recently_used_entry() {
+        let cache = InputCache::new(2, 1024);
+        cache.insert(url("a"), vec![1]);
+        cache.insert(url("b"), vec![2]);
+        // a is now more recently used than b
+        cache.get(&url("a"), None);
+        cache.insert(url("c"), vec![3]);
+        assert!(cache.get(&url("a"), None).is_some());
+        assert!(cache.get(&url("b"), None).is_none());
+        assert!(cache.get(&url("c"), None).is_some());
+    }
+
+    #[test]
+    fn test_byte_budget() {
+        let cache = InputCache::new(10, 10);
+        cache.insert(url("a"), vec![0; 6]);
+        cache.insert(url("b"), vec![0; 6]);
+        assert!(cache.get(&url("a"), None).is_none());
+        assert_eq!(cache.stats().bytes, 6);
+
+        cache.insert(url("too-big"), vec![0; 11]);
+        assert!(cache.get(&url("too-big"), None).is_none());
+        assert!(cache.get(&url("b"), None).is_some());
+    }
+
+    #[test]
+    fn test_reinsert_replaces_entry() {
+        let cache = InputCache::new(10, 10);
+        cache.insert(url("a"), vec![0; 4]);
+        cache.insert(url("a"), vec![1; 2]);
+        assert_eq!(cache.get(&url("a"), None), Some(vec![1; 2]));
+        assert_eq!(cache.stats().entries, 1);
+        assert_eq!(cache.stats().bytes, 2);
+    }
+}
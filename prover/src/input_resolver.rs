@@ -1,3 +1,4 @@
+use std::collections::HashMap;
 use std::str::from_utf8;
 use std::sync::Arc;
 use std::time::{Duration, SystemTime, UNIX_EPOCH};
@@ -6,12 +7,17 @@ use anyhow::Result;
 use arrayref::array_ref;
 use async_trait::async_trait;
 use bonsol_schema::{InputT, InputType, ProgramInputType};
+use futures_util::future::BoxFuture;
+use futures_util::stream::{self, StreamExt};
+use reqwest::header::CONTENT_TYPE;
 use reqwest::Url;
 use serde::{Deserialize, Serialize};
 use solana_sdk::pubkey::Pubkey;
 use solana_sdk::signer::Signer;
 use tokio::task::{JoinHandle, JoinSet};
 
+use crate::encryption::InputKey;
+use crate::input_cache::{InputCache, InputCacheStats};
 use crate::util::get_body_max_size;
 
 #[derive(Debug, Clone, PartialEq)]
@@ -66,6 +72,98 @@ pub trait InputResolver: Send + Sync {
         inputs: &mut Vec<ProgramInput>,
         signer: Arc<&(dyn Signer + Send + Sync)>,
     ) -> Result<(), anyhow::Error>;
+
+    /// Resolves private inputs published encrypted at a url by downloading and decrypting them
+    /// with `key`, see `encryption` for how the key reaches the prover
+    async fn resolve_encrypted_inputs(
+        &self,
+        _inputs: &mut Vec<ProgramInput>,
+        _key: &InputKey,
+    ) -> Result<(), anyhow::Error> {
+        Err(anyhow::anyhow!("Encrypted inputs are not supported"))
+    }
+}
+
+/// Public inputs downloaded at the same time unless configured otherwise
+pub const DEFAULT_MAX_CONCURRENT_DOWNLOADS: usize = 8;
+
+/// Largest public or private input downloaded unless configured otherwise
+pub const DEFAULT_MAX_INPUT_SIZE_MB: u32 = 10;
+
+/// Returns extra headers to send when downloading a public input from `url`, use this to attach
+/// bearer tokens for authenticated gateways or signatures for S3 style urls
+pub type UrlHeaders = Arc<dyn Fn(&Url) -> HashMap<String, String> + Send + Sync>;
+
+/// A downloaded public input, passed to an `InputValidator` before it is accepted
+pub struct DownloadedInput<'a> {
+    pub url: &'a Url,
+    pub input_type: ProgramInputType,
+    pub content_type: Option<&'a str>,
+    pub data: &'a [u8],
+}
+
+/// Checks a downloaded public input, returning an error rejects the input. Validators decide for
+/// themselves which inputs they apply to, e.g. by url or input type.
+pub type InputValidator = Arc<dyn Fn(&DownloadedInput) -> Result<()> + Send + Sync>;
+
+/// Rejects html responses, which are usually error pages served in place of the input
+pub fn reject_html(input: &DownloadedInput) -> Result<()> {
+    let html_content_type = input.content_type.is_some_and(|ct| {
+        ct.trim_start()
+            .to_ascii_lowercase()
+            .starts_with("text/html")
+    });
+    let start = &input.data[..input.data.len().min(64)];
+    let start = String::from_utf8_lossy(start)
+        .trim_start()
+        .to_ascii_lowercase();
+    if html_content_type || start.starts_with("<!doctype html") || start.starts_with("<html") {
+        return Err(anyhow::anyhow!(
+            "Input at {} is an html document, expected {:?} data",
+            input.url,
+            input.input_type
+        ));
+    }
+    Ok(())
+}
+
+/// How many times a public input download is retried after a timeout, connection error or 5xx
+/// response. The delay before retry `n` is `base_delay * 2^n`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DownloadRetryPolicy {
+    pub retries: u8,
+    pub base_delay: Duration,
+}
+
+impl Default for DownloadRetryPolicy {
+    fn default() -> Self {
+        DownloadRetryPolicy {
+            retries: 2,
+            base_delay: Duration::from_millis(500),
+        }
+    }
+}
+
+impl DownloadRetryPolicy {
+    pub const fn none() -> Self {
+        DownloadRetryPolicy {
+            retries: 0,
+            base_delay: Duration::ZERO,
+        }
+    }
+
+    fn delay(&self, attempt: u8) -> Duration {
+        self.base_delay
+            .saturating_mul(2u32.saturating_pow(attempt as u32))
+    }
+}
+
+/// The sha256 a url input declares for its content with a `#sha256=<hex>` fragment
+pub fn url_content_hash(url: &Url) -> Option<[u8; 32]> {
+    let hash = url.fragment()?.strip_prefix("sha256=")?;
+    let mut digest = [0u8; 32];
+    hex::decode_to_slice(hash, &mut digest).ok()?;
+    Some(digest)
 }
 
 // naive resolver that downloads inputs just in time
@@ -74,6 +172,11 @@ pub struct DefaultInputResolver {
     solana_rpc_client: Arc<solana_rpc_client::nonblocking::rpc_client::RpcClient>,
     max_input_size_mb: u32,
     timeout: Duration,
+    url_headers: Option<UrlHeaders>,
+    retry_policy: DownloadRetryPolicy,
+    max_concurrent_downloads: usize,
+    input_validator: Option<InputValidator>,
+    input_cache: Option<Arc<InputCache>>,
 }
 
 impl DefaultInputResolver {
@@ -84,8 +187,13 @@ impl DefaultInputResolver {
         DefaultInputResolver {
             http_client,
             solana_rpc_client,
-            max_input_size_mb: 10,
+            max_input_size_mb: DEFAULT_MAX_INPUT_SIZE_MB,
             timeout: Duration::from_secs(30),
+            url_headers: None,
+            retry_policy: DownloadRetryPolicy::default(),
+            max_concurrent_downloads: DEFAULT_MAX_CONCURRENT_DOWNLOADS,
+            input_validator: None,
+            input_cache: None,
         }
     }
 
@@ -98,36 +206,124 @@ impl DefaultInputResolver {
         DefaultInputResolver {
             http_client,
             solana_rpc_client,
-            max_input_size_mb: max_input_size_mb.unwrap_or(10),
+            max_input_size_mb: max_input_size_mb.unwrap_or(DEFAULT_MAX_INPUT_SIZE_MB),
             timeout: timeout.unwrap_or(Duration::from_secs(30)),
+            url_headers: None,
+            retry_policy: DownloadRetryPolicy::default(),
+            max_concurrent_downloads: DEFAULT_MAX_CONCURRENT_DOWNLOADS,
+            input_validator: None,
+            input_cache: None,
         }
     }
 
+    pub fn with_url_headers(mut self, url_headers: UrlHeaders) -> Self {
+        self.url_headers = Some(url_headers);
+        self
+    }
+
+    pub const fn with_retry_policy(mut self, retry_policy: DownloadRetryPolicy) -> Self {
+        self.retry_policy = retry_policy;
+        self
+    }
+
+    /// Limits how many public inputs are downloaded at the same time
+    pub const fn with_max_concurrent_downloads(mut self, max_concurrent_downloads: usize) -> Self {
+        self.max_concurrent_downloads = max_concurrent_downloads;
+        self
+    }
+
+    /// Runs `input_validator` on every downloaded public input, see `reject_html`
+    pub fn with_input_validator(mut self, input_validator: InputValidator) -> Self {
+        self.input_validator = Some(input_validator);
+        self
+    }
+
+    /// Caches downloaded url inputs so executions sharing an input only download it once. Account
+    /// inputs are never cached since their data can change between executions. A url input can
+    /// pin its content with a `#sha256=<hex>` fragment, a cached entry that does not hash to it
+    /// is downloaded again.
+    pub fn with_input_cache(mut self, max_entries: usize, max_bytes: usize) -> Self {
+        self.input_cache = Some(Arc::new(InputCache::new(max_entries, max_bytes)));
+        self
+    }
+
+    /// Returns `None` when caching is disabled
+    pub fn input_cache_stats(&self) -> Option<InputCacheStats> {
+        self.input_cache.as_ref().map(|c| c.stats())
+    }
+
+    fn download_opts(&self, url: &Url) -> PublicDownloadOpts {
+        PublicDownloadOpts {
+            max_size_mb: self.max_input_size_mb as usize,
+            timeout: self.timeout,
+            headers: self
+                .url_headers
+                .as_ref()
+                .map(|f| f(url))
+                .unwrap_or_default(),
+            retry_policy: self.retry_policy,
+            validator: self.input_validator.clone(),
+        }
+    }
+
+    /// Resolves from the input cache when possible, otherwise queues a download that fills the
+    /// cache once it completes
+    fn resolve_url_input(
+        &self,
+        client: Arc<reqwest::Client>,
+        index: u8,
+        input: InputT,
+        input_type: ProgramInputType,
+        downloads: &mut Vec<BoxFuture<'static, Result<ResolvedInput>>>,
+    ) -> Result<ProgramInput> {
+        let url = input.data.ok_or(anyhow::anyhow!("Invalid data"))?;
+        let url = from_utf8(&url)?;
+        let url = Url::parse(url)?;
+        if let Some(cache) = &self.input_cache {
+            if let Some(data) = cache.get(&url, url_content_hash(&url).as_ref()) {
+                return Ok(ProgramInput::Resolved(ResolvedInput {
+                    index,
+                    data,
+                    input_type,
+                }));
+            }
+        }
+        let download = download_public_input(
+            client,
+            index,
+            url.clone(),
+            input_type,
+            self.download_opts(&url),
+        );
+        match &self.input_cache {
+            Some(cache) => {
+                let cache = cache.clone();
+                let url = url.clone();
+                downloads.push(Box::pin(async move {
+                    let ri = download.await?;
+                    cache.insert(url, ri.data.clone());
+                    Ok(ri)
+                }));
+            }
+            None => downloads.push(Box::pin(download)),
+        }
+        Ok(ProgramInput::Unresolved(UnresolvedInput {
+            index,
+            url,
+            input_type,
+        }))
+    }
+
     fn par_resolve_input(
         &self,
         client: Arc<reqwest::Client>,
         index: u8,
         input: InputT,
-        task_set: &mut JoinSet<Result<ResolvedInput>>,
+        downloads: &mut Vec<BoxFuture<'static, Result<ResolvedInput>>>,
     ) -> Result<ProgramInput> {
         match input.input_type {
             InputType::PublicUrl => {
-                let url = input.data.ok_or(anyhow::anyhow!("Invalid data"))?;
-                let url = from_utf8(&url)?;
-                let url = Url::parse(url)?;
-                task_set.spawn(download_public_input(
-                    client,
-                    index,
-                    url.clone(),
-                    self.max_input_size_mb as usize,
-                    ProgramInputType::Public,
-                    self.timeout,
-                ));
-                Ok(ProgramInput::Unresolved(UnresolvedInput {
-                    index,
-                    url,
-                    input_type: ProgramInputType::Public,
-                }))
+                self.resolve_url_input(client, index, input, ProgramInputType::Public, downloads)
             }
             InputType::Private => {
                 let url = input.data.ok_or(anyhow::anyhow!("Invalid data"))?;
@@ -148,24 +344,13 @@ impl DefaultInputResolver {
                     input_type: ProgramInputType::Public,
                 }))
             }
-            InputType::PublicProof => {
-                let url = input.data.ok_or(anyhow::anyhow!("Invalid data"))?;
-                let url = from_utf8(&url)?;
-                let url = Url::parse(url)?;
-                task_set.spawn(download_public_input(
-                    client,
-                    index,
-                    url.clone(),
-                    self.max_input_size_mb as usize,
-                    ProgramInputType::PublicProof,
-                    self.timeout,
-                ));
-                Ok(ProgramInput::Unresolved(UnresolvedInput {
-                    index,
-                    url,
-                    input_type: ProgramInputType::PublicProof,
-                }))
-            }
+            InputType::PublicProof => self.resolve_url_input(
+                client,
+                index,
+                input,
+                ProgramInputType::PublicProof,
+                downloads,
+            ),
             InputType::PublicAccountData => {
                 let pubkey = input.data.ok_or(anyhow::anyhow!("Invalid data"))?;
                 if pubkey.len() != 32 {
@@ -173,12 +358,12 @@ impl DefaultInputResolver {
                 }
                 let pubkey = Pubkey::new_from_array(*array_ref!(pubkey, 0, 32));
                 let rpc_client_clone = self.solana_rpc_client.clone();
-                task_set.spawn(download_public_account(
+                downloads.push(Box::pin(download_public_account(
                     rpc_client_clone,
                     index,
                     pubkey,
                     self.max_input_size_mb as usize,
-                ));
+                )));
                 Ok(ProgramInput::Unresolved(UnresolvedInput {
                     index,
                     url: format!("solana://{}", pubkey).parse()?,
@@ -210,23 +395,13 @@ impl InputResolver for DefaultInputResolver {
         &self,
         inputs: Vec<InputT>,
     ) -> Result<Vec<ProgramInput>, anyhow::Error> {
-        let mut url_set = JoinSet::new();
+        let mut downloads = Vec::new();
         let mut res = vec![ProgramInput::Empty; inputs.len()];
         for (index, input) in inputs.into_iter().enumerate() {
             let client = self.http_client.clone();
-            res[index] = self.par_resolve_input(client, index as u8, input, &mut url_set)?;
-        }
-        while let Some(url) = url_set.join_next().await {
-            match url {
-                Ok(Ok(ri)) => {
-                    let index = ri.index as usize;
-                    res[index] = ProgramInput::Resolved(ri);
-                }
-                e => {
-                    return Err(anyhow::anyhow!("Error downloading input: {:?}", e));
-                }
-            }
+            res[index] = self.par_resolve_input(client, index as u8, input, &mut downloads)?;
         }
+        resolve_concurrently(downloads, self.max_concurrent_downloads, &mut res).await?;
         Ok(res)
     }
 
@@ -272,6 +447,47 @@ impl InputResolver for DefaultInputResolver {
         }
         Ok(())
     }
+
+    async fn resolve_encrypted_inputs(
+        &self,
+        inputs: &mut Vec<ProgramInput>,
+        key: &InputKey,
+    ) -> Result<(), anyhow::Error> {
+        let mut downloads: Vec<BoxFuture<'static, Result<ResolvedInput>>> = Vec::new();
+        for input in inputs.iter() {
+            if let ProgramInput::Unresolved(ui) = input {
+                let download = download_public_input(
+                    self.http_client.clone(),
+                    ui.index,
+                    ui.url.clone(),
+                    ui.input_type,
+                    self.download_opts(&ui.url),
+                );
+                let key = key.clone();
+                downloads.push(Box::pin(async move {
+                    let mut ri = download.await?;
+                    ri.data = key.decrypt(&ri.data)?;
+                    Ok(ri)
+                }));
+            }
+        }
+        resolve_concurrently(downloads, self.max_concurrent_downloads, inputs).await
+    }
+}
+
+/// Runs the downloads with at most `limit` in flight, placing each result at its input index
+async fn resolve_concurrently(
+    downloads: Vec<BoxFuture<'static, Result<ResolvedInput>>>,
+    limit: usize,
+    res: &mut [ProgramInput],
+) -> Result<()> {
+    let mut results = stream::iter(downloads).buffer_unordered(limit.max(1));
+    while let Some(ri) = results.next().await {
+        let ri = ri.map_err(|e| anyhow::anyhow!("Error downloading input: {:?}", e))?;
+        let index = ri.index as usize;
+        res[index] = ProgramInput::Resolved(ri);
+    }
+    Ok(())
 }
 
 pub fn resolve_public_data(index: usize, data: &[u8]) -> Result<ProgramInput> {
@@ -296,9 +512,8 @@ pub fn resolve_remote_public_data(
         client,
         index as u8,
         url,
-        max_input_size_mb as usize,
         ProgramInputType::Public,
-        timeout,
+        PublicDownloadOpts::new(max_input_size_mb as usize, timeout),
     )))
 }
 
@@ -310,26 +525,91 @@ pub struct PrivateInputRequest {
     now_utc: u64,
 }
 
+/// Settings applied to a single public input download
+#[derive(Clone)]
+struct PublicDownloadOpts {
+    max_size_mb: usize,
+    timeout: Duration,
+    headers: HashMap<String, String>,
+    retry_policy: DownloadRetryPolicy,
+    validator: Option<InputValidator>,
+}
+
+impl PublicDownloadOpts {
+    fn new(max_size_mb: usize, timeout: Duration) -> Self {
+        PublicDownloadOpts {
+            max_size_mb,
+            timeout,
+            headers: HashMap::new(),
+            retry_policy: DownloadRetryPolicy::none(),
+            validator: None,
+        }
+    }
+}
+
 async fn download_public_input(
     client: Arc<reqwest::Client>,
     index: u8,
     url: Url,
-    max_size_mb: usize,
     input_type: ProgramInputType,
-    timeout: Duration,
+    opts: PublicDownloadOpts,
 ) -> Result<ResolvedInput> {
-    let resp = client
-        .get(url)
-        .timeout(timeout)
-        .send()
-        .await?
-        .error_for_status()?;
-    let byte = get_body_max_size(resp.bytes_stream(), max_size_mb * 1024 * 1024).await?;
-    Ok(ResolvedInput {
-        index,
-        data: byte.to_vec(),
-        input_type,
-    })
+    let mut attempt = 0;
+    loop {
+        match try_download_public_input(&client, &url, input_type, &opts).await {
+            Ok(data) => {
+                return Ok(ResolvedInput {
+                    index,
+                    data,
+                    input_type,
+                })
+            }
+            Err(e) if attempt < opts.retry_policy.retries && is_retryable(&e) => {
+                tokio::time::sleep(opts.retry_policy.delay(attempt)).await;
+                attempt += 1;
+            }
+            Err(e) => return Err(e),
+        }
+    }
+}
+
+async fn try_download_public_input(
+    client: &reqwest::Client,
+    url: &Url,
+    input_type: ProgramInputType,
+    opts: &PublicDownloadOpts,
+) -> Result<Vec<u8>> {
+    let mut req = client.get(url.clone()).timeout(opts.timeout);
+    for (name, value) in &opts.headers {
+        req = req.header(name, value);
+    }
+    let resp = req.send().await?.error_for_status()?;
+    let content_type = resp
+        .headers()
+        .get(CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .map(|v| v.to_string());
+    let byte = get_body_max_size(resp.bytes_stream(), opts.max_size_mb * 1024 * 1024).await?;
+    if let Some(validator) = &opts.validator {
+        validator(&DownloadedInput {
+            url,
+            input_type,
+            content_type: content_type.as_deref(),
+            data: &byte,
+        })?;
+    }
+    Ok(byte.to_vec())
+}
+
+/// 4xx responses and oversized bodies will fail the same way again so only transport errors and
+/// 5xx responses are retried
+fn is_retryable(e: &anyhow::Error) -> bool {
+    match e.downcast_ref::<reqwest::Error>() {
+        Some(e) => {
+            e.is_timeout() || e.is_connect() || e.status().is_some_and(|s| s.is_server_error())
+        }
+        None => false,
+    }
 }
 
 async fn download_public_account(
@@ -379,9 +659,13 @@ async fn download_private_input(
 #[cfg(test)]
 mod test {
     use super::*;
+    use crate::encryption::tests::{fixture_key, ENCRYPTED_FIXTURE};
     use mockito::Mock;
     use reqwest::{Client, Url};
+    use sha2::{Digest, Sha256};
+    use solana_rpc_client::nonblocking::rpc_client::RpcClient;
 
+    use std::sync::atomic::{AtomicUsize, Ordering};
     use std::sync::Arc;
 
     // Modified to return the server along with the mock and URL
@@ -414,9 +698,8 @@ mod test {
             client.clone(),
             1u8,
             url,
-            max_size_mb,
             ProgramInputType::Public,
-            Duration::from_secs(30),
+            PublicDownloadOpts::new(max_size_mb, Duration::from_secs(30)),
         )
         .await;
 
@@ -446,9 +729,8 @@ mod test {
             client.clone(),
             1u8,
             url,
-            max_size_mb,
             ProgramInputType::Public,
-            Duration::from_secs(30),
+            PublicDownloadOpts::new(max_size_mb, Duration::from_secs(30)),
         )
         .await;
 
@@ -457,4 +739,457 @@ mod test {
 
         mock.assert();
     }
+
+    #[tokio::test]
+    async fn test_download_public_input_with_auth_header() {
+        let mut server = mockito::Server::new_async().await;
+        let url = Url::parse(&format!("{}/private-bucket/input", server.url())).unwrap();
+        let authorized = server
+            .mock("GET", "/private-bucket/input")
+            .match_header("authorization", "Bearer gateway-token")
+            .with_status(200)
+            .with_body(b"input")
+            .create_async()
+            .await;
+        let forbidden = server
+            .mock("GET", "/private-bucket/input")
+            .match_header("authorization", mockito::Matcher::Missing)
+            .with_status(403)
+            .create_async()
+            .await;
+        let client = Arc::new(Client::new());
+
+        let result = download_public_input(
+            client.clone(),
+            0,
+            url.clone(),
+            ProgramInputType::Public,
+            PublicDownloadOpts::new(1, Duration::from_secs(30)),
+        )
+        .await;
+        assert!(result.unwrap_err().to_string().contains("403"));
+
+        let url_headers: UrlHeaders = Arc::new(|url: &Url| {
+            let mut headers = HashMap::new();
+            if url.path().starts_with("/private-bucket") {
+                headers.insert(
+                    "Authorization".to_string(),
+                    "Bearer gateway-token".to_string(),
+                );
+            }
+            headers
+        });
+        let result = download_public_input(
+            client,
+            0,
+            url.clone(),
+            ProgramInputType::Public,
+            PublicDownloadOpts {
+                headers: url_headers(&url),
+                ..PublicDownloadOpts::new(1, Duration::from_secs(30))
+            },
+        )
+        .await
+        .unwrap();
+        assert_eq!(result.data, b"input");
+
+        authorized.assert_async().await;
+        forbidden.assert_async().await;
+    }
+
+    const TEST_RETRY_POLICY: DownloadRetryPolicy = DownloadRetryPolicy {
+        retries: 3,
+        base_delay: Duration::from_millis(1),
+    };
+
+    #[tokio::test]
+    async fn test_download_public_input_retries_server_errors() {
+        let mut server = mockito::Server::new_async().await;
+        let url = Url::parse(&format!("{}/flaky", server.url())).unwrap();
+        let failing = server
+            .mock("GET", "/flaky")
+            .with_status(503)
+            .expect(2)
+            .create_async()
+            .await;
+        let succeeding = server
+            .mock("GET", "/flaky")
+            .with_status(200)
+            .with_body(b"input")
+            .expect(1)
+            .create_async()
+            .await;
+
+        let result = download_public_input(
+            Arc::new(Client::new()),
+            0,
+            url,
+            ProgramInputType::Public,
+            PublicDownloadOpts {
+                retry_policy: TEST_RETRY_POLICY,
+                ..PublicDownloadOpts::new(1, Duration::from_secs(30))
+            },
+        )
+        .await
+        .unwrap();
+        assert_eq!(result.data, b"input");
+
+        failing.assert_async().await;
+        succeeding.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn test_download_public_input_does_not_retry_client_errors() {
+        let mut server = mockito::Server::new_async().await;
+        let url = Url::parse(&format!("{}/missing", server.url())).unwrap();
+        let mock = server
+            .mock("GET", "/missing")
+            .with_status(404)
+            .expect(1)
+            .create_async()
+            .await;
+
+        let result = download_public_input(
+            Arc::new(Client::new()),
+            0,
+            url,
+            ProgramInputType::Public,
+            PublicDownloadOpts {
+                retry_policy: TEST_RETRY_POLICY,
+                ..PublicDownloadOpts::new(1, Duration::from_secs(30))
+            },
+        )
+        .await;
+        assert!(result.unwrap_err().to_string().contains("404"));
+
+        mock.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn test_download_public_input_gives_up_after_retries() {
+        let mut server = mockito::Server::new_async().await;
+        let url = Url::parse(&format!("{}/down", server.url())).unwrap();
+        let mock = server
+            .mock("GET", "/down")
+            .with_status(500)
+            .expect(4)
+            .create_async()
+            .await;
+
+        let result = download_public_input(
+            Arc::new(Client::new()),
+            0,
+            url,
+            ProgramInputType::Public,
+            PublicDownloadOpts {
+                retry_policy: TEST_RETRY_POLICY,
+                ..PublicDownloadOpts::new(1, Duration::from_secs(30))
+            },
+        )
+        .await;
+        assert!(result.unwrap_err().to_string().contains("500"));
+
+        mock.assert_async().await;
+    }
+
+    #[test]
+    fn test_retry_policy_delay() {
+        let policy = DownloadRetryPolicy {
+            retries: 3,
+            base_delay: Duration::from_millis(100),
+        };
+        assert_eq!(policy.delay(0), Duration::from_millis(100));
+        assert_eq!(policy.delay(1), Duration::from_millis(200));
+        assert_eq!(policy.delay(2), Duration::from_millis(400));
+    }
+
+    #[tokio::test]
+    async fn test_resolve_public_inputs_preserves_order() {
+        let mut server = mockito::Server::new_async().await;
+        let mut mocks = vec![];
+        for i in 0..4 {
+            mocks.push(
+                server
+                    .mock("GET", format!("/input/{}", i).as_str())
+                    .with_status(200)
+                    .with_body(format!("url input {}", i))
+                    .create_async()
+                    .await,
+            );
+        }
+        let mut inputs = vec![];
+        for i in 0..4 {
+            inputs.push(InputT::new(
+                InputType::PublicUrl,
+                Some(format!("{}/input/{}", server.url(), i).into_bytes()),
+            ));
+            inputs.push(InputT::new(
+                InputType::PublicData,
+                Some(format!("data input {}", i).into_bytes()),
+            ));
+        }
+        let rpc_client = Arc::new(RpcClient::new_mock("succeeds".to_string()));
+        let resolver = DefaultInputResolver::new(Arc::new(Client::new()), rpc_client)
+            .with_max_concurrent_downloads(2);
+
+        let resolved = resolver.resolve_public_inputs(inputs).await.unwrap();
+        assert_eq!(resolved.len(), 8);
+        for (index, input) in resolved.into_iter().enumerate() {
+            let expected = if index % 2 == 0 {
+                format!("url input {}", index / 2)
+            } else {
+                format!("data input {}", index / 2)
+            };
+            match input {
+                ProgramInput::Resolved(ri) => {
+                    assert_eq!(ri.index as usize, index);
+                    assert_eq!(ri.data, expected.into_bytes());
+                }
+                other => panic!("input {} was not resolved: {:?}", index, other),
+            }
+        }
+        for mock in mocks {
+            mock.assert_async().await;
+        }
+    }
+
+    #[tokio::test]
+    async fn test_resolve_concurrently_is_bounded() {
+        let in_flight = Arc::new(AtomicUsize::new(0));
+        let max_in_flight = Arc::new(AtomicUsize::new(0));
+        let mut downloads: Vec<BoxFuture<'static, Result<ResolvedInput>>> = vec![];
+        // later inputs finish first so results arrive out of order
+        for index in 0..6u8 {
+            let in_flight = in_flight.clone();
+            let max_in_flight = max_in_flight.clone();
+            downloads.push(Box::pin(async move {
+                let current = in_flight.fetch_add(1, Ordering::SeqCst) + 1;
+                max_in_flight.fetch_max(current, Ordering::SeqCst);
+                tokio::time::sleep(Duration::from_millis(5 * (6 - index as u64))).await;
+                in_flight.fetch_sub(1, Ordering::SeqCst);
+                Ok(ResolvedInput {
+                    index,
+                    data: vec![index],
+                    input_type: ProgramInputType::Public,
+                })
+            }));
+        }
+        let mut res = vec![ProgramInput::Empty; 6];
+        resolve_concurrently(downloads, 2, &mut res).await.unwrap();
+
+        assert_eq!(max_in_flight.load(Ordering::SeqCst), 2);
+        for (index, input) in res.into_iter().enumerate() {
+            match input {
+                ProgramInput::Resolved(ri) => assert_eq!(ri.data, vec![index as u8]),
+                other => panic!("input {} was not resolved: {:?}", index, other),
+            }
+        }
+    }
+
+    async fn html_server(content_type: &str, body: &str) -> (Mock, Url, mockito::ServerGuard) {
+        let mut server = mockito::Server::new_async().await;
+        let url = Url::parse(&format!("{}/input", server.url())).unwrap();
+        let mock = server
+            .mock("GET", "/input")
+            .with_status(200)
+            .with_header("content-type", content_type)
+            .with_body(body)
+            .create_async()
+            .await;
+        (mock, url, server)
+    }
+
+    fn validated_opts() -> PublicDownloadOpts {
+        PublicDownloadOpts {
+            validator: Some(Arc::new(reject_html)),
+            ..PublicDownloadOpts::new(1, Duration::from_secs(30))
+        }
+    }
+
+    #[tokio::test]
+    async fn test_download_public_input_rejects_html_error_page() {
+        let body = "<html><body><h1>502 Bad Gateway</h1></body></html>";
+        let (mock, url, _server) = html_server("text/html; charset=utf-8", body).await;
+        let result = download_public_input(
+            Arc::new(Client::new()),
+            0,
+            url,
+            ProgramInputType::Public,
+            validated_opts(),
+        )
+        .await;
+        assert!(result.unwrap_err().to_string().contains("html document"));
+        mock.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn test_download_public_input_rejects_mislabelled_html() {
+        let body = "\n  <!DOCTYPE html><html><body>Not Found</body></html>";
+        let (mock, url, _server) = html_server("application/octet-stream", body).await;
+        let result = download_public_input(
+            Arc::new(Client::new()),
+            0,
+            url,
+            ProgramInputType::Public,
+            validated_opts(),
+        )
+        .await;
+        assert!(result.is_err());
+        mock.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn test_download_public_input_validation_is_opt_in() {
+        let body = "<html><body>Not Found</body></html>";
+        let (mock, url, _server) = html_server("text/html", body).await;
+        let result = download_public_input(
+            Arc::new(Client::new()),
+            0,
+            url,
+            ProgramInputType::Public,
+            PublicDownloadOpts::new(1, Duration::from_secs(30)),
+        )
+        .await
+        .unwrap();
+        assert_eq!(result.data, body.as_bytes());
+        mock.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn test_download_public_input_validator_accepts_binary() {
+        let input_data = vec![0u8, 159, 146, 150];
+        let (mock, url, _server) = get_server("/download", &input_data).await;
+        let result = download_public_input(
+            Arc::new(Client::new()),
+            0,
+            url,
+            ProgramInputType::Public,
+            validated_opts(),
+        )
+        .await
+        .unwrap();
+        assert_eq!(result.data, input_data);
+        mock.assert();
+    }
+
+    #[tokio::test]
+    async fn test_resolve_public_inputs_uses_cache() {
+        // mocks expect exactly one request unless told otherwise
+        let (mock, url, _server) = get_server("/shared", b"shared input").await;
+        let rpc_client = Arc::new(RpcClient::new_mock("succeeds".to_string()));
+        let resolver = DefaultInputResolver::new(Arc::new(Client::new()), rpc_client)
+            .with_input_cache(16, 1024 * 1024);
+        let inputs = || {
+            vec![InputT::new(
+                InputType::PublicProof,
+                Some(url.to_string().into_bytes()),
+            )]
+        };
+
+        for _ in 0..2 {
+            let resolved = resolver.resolve_public_inputs(inputs()).await.unwrap();
+            assert_eq!(
+                resolved,
+                vec![ProgramInput::Resolved(ResolvedInput {
+                    index: 0,
+                    data: b"shared input".to_vec(),
+                    input_type: ProgramInputType::PublicProof,
+                })]
+            );
+        }
+        let stats = resolver.input_cache_stats().unwrap();
+        assert_eq!(stats.hits, 1);
+        assert_eq!(stats.misses, 1);
+        assert_eq!(stats.entries, 1);
+        mock.assert_async().await;
+    }
+
+    #[test]
+    fn test_url_content_hash() {
+        let hash: [u8; 32] = Sha256::digest(b"input").into();
+        let url = Url::parse(&format!("https://a.com/input#sha256={}", hex::encode(hash))).unwrap();
+        assert_eq!(url_content_hash(&url), Some(hash));
+        for url in [
+            "https://a.com/input",
+            "https://a.com/input#md5=00",
+            "https://a.com/input#sha256=abcd",
+        ] {
+            assert_eq!(url_content_hash(&Url::parse(url).unwrap()), None);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_resolve_public_inputs_checks_cached_content_hash() {
+        let mut server = mockito::Server::new_async().await;
+        let mock = server
+            .mock("GET", "/pinned")
+            .with_status(200)
+            .with_body(b"pinned input")
+            .expect(3)
+            .create_async()
+            .await;
+        let rpc_client = Arc::new(RpcClient::new_mock("succeeds".to_string()));
+        let resolver = DefaultInputResolver::new(Arc::new(Client::new()), rpc_client)
+            .with_input_cache(16, 1024 * 1024);
+        let pinned = |hash: &[u8]| {
+            let url = format!("{}/pinned#sha256={}", server.url(), hex::encode(hash));
+            vec![InputT::new(InputType::PublicUrl, Some(url.into_bytes()))]
+        };
+
+        // the cached content does not match this hash, so it is downloaded every time
+        let wrong = pinned(&[0u8; 32]);
+        for _ in 0..2 {
+            resolver.resolve_public_inputs(wrong.clone()).await.unwrap();
+        }
+        let right = pinned(&Sha256::digest(b"pinned input"));
+        resolver.resolve_public_inputs(right.clone()).await.unwrap();
+        resolver.resolve_public_inputs(right).await.unwrap();
+        let stats = resolver.input_cache_stats().unwrap();
+        assert_eq!(stats.hits, 1);
+        assert_eq!(stats.misses, 3);
+        mock.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn test_resolve_encrypted_inputs() {
+        let encrypted = hex::decode(ENCRYPTED_FIXTURE).unwrap();
+        let (mock, url, _server) = get_server("/private", &encrypted).await;
+        let rpc_client = Arc::new(RpcClient::new_mock("succeeds".to_string()));
+        let resolver = DefaultInputResolver::new(Arc::new(Client::new()), rpc_client);
+        let inputs = vec![
+            InputT::public(b"public".to_vec()),
+            InputT::private(url.to_string().into_bytes()),
+        ];
+
+        let mut resolved = resolver.resolve_public_inputs(inputs).await.unwrap();
+        resolver
+            .resolve_encrypted_inputs(&mut resolved, &fixture_key())
+            .await
+            .unwrap();
+        assert_eq!(
+            resolved[1],
+            ProgramInput::Resolved(ResolvedInput {
+                index: 1,
+                data: b"private input".to_vec(),
+                input_type: ProgramInputType::Private,
+            })
+        );
+        mock.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn test_resolve_encrypted_inputs_wrong_key() {
+        let encrypted = hex::decode(ENCRYPTED_FIXTURE).unwrap();
+        let (_mock, url, _server) = get_server("/private", &encrypted).await;
+        let rpc_client = Arc::new(RpcClient::new_mock("succeeds".to_string()));
+        let resolver = DefaultInputResolver::new(Arc::new(Client::new()), rpc_client);
+        let inputs = vec![InputT::private(url.to_string().into_bytes())];
+
+        let mut resolved = resolver.resolve_public_inputs(inputs).await.unwrap();
+        let key = InputKey::new([8; 32]);
+        assert!(resolver
+            .resolve_encrypted_inputs(&mut resolved, &key)
+            .await
+            .is_err());
+    }
 }
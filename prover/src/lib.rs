@@ -1,6 +1,10 @@
+pub mod encryption;
 pub mod image;
+pub mod image_cache;
+pub mod input_cache;
 pub mod input_resolver;
 pub mod prover;
+pub mod receipt_file;
 pub mod util;
 
 pub use bonsol_schema::{
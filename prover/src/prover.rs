@@ -1,24 +1,126 @@
+use std::collections::HashSet;
+use std::fmt;
 use std::rc::Rc;
+use std::str::FromStr;
 
-use anyhow::Result;
+use anyhow::{anyhow, Result};
 use bonsol_schema::ProgramInputType;
 use risc0_binfmt::MemoryImage;
-use risc0_zkvm::{get_prover_server, ExecutorEnv, ExecutorImpl, ProverOpts, ProverServer, Receipt};
+use risc0_zkvm::sha::{Digest, Digestible};
+use risc0_zkvm::{
+    get_prover_server, ExecutorEnv, ExecutorEnvBuilder, ExecutorImpl, ProverOpts, ProverServer,
+    Session,
+};
+use tracing::info;
 
+use crate::image::Image;
 use crate::input_resolver::ProgramInput;
+use crate::receipt_file::deserialize_receipt;
+
+/// Total session cycles allowed unless configured otherwise, so a guest that never halts cannot
+/// hold a prover forever
+pub const DEFAULT_MAX_CYCLES: u64 = 1 << 28;
+
+/// What the risc0 executor fails with once a session reaches its `session_limit`
+const SESSION_LIMIT_EXCEEDED: &str = "Session limit exceeded";
+
+/// Returned by `run_session` when the guest runs for longer than its cycle limit
+#[derive(Debug)]
+pub struct CyclesExceeded;
+
+impl fmt::Display for CyclesExceeded {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Execution exceeded its cycle limit")
+    }
+}
+
+impl std::error::Error for CyclesExceeded {}
+
+/// Cycle and segment counts of an executed session, for pricing and capacity planning
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SessionStats {
+    pub total_cycles: u64,
+    pub user_cycles: u64,
+    pub segments: usize,
+}
+
+impl From<&Session> for SessionStats {
+    fn from(session: &Session) -> Self {
+        SessionStats {
+            total_cycles: session.total_cycles,
+            user_cycles: session.user_cycles,
+            segments: session.segments.len(),
+        }
+    }
+}
+
+impl fmt::Display for SessionStats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "User cycles: {}\nTotal cycles: {}\nSegments: {}",
+            self.user_cycles, self.total_cycles, self.segments
+        )
+    }
+}
+
+/// Work a proof of an execution takes, proving time grows with the number of segments
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EstimatedWork {
+    pub image_id: String,
+    pub stats: SessionStats,
+}
+
+/// Executes the guest without proving it to measure the work a proof would take, so a node can
+/// weigh it against the tip before claiming. Fails like `run_session` when the guest does.
+pub fn estimate_execution(
+    image: &Image,
+    sorted_inputs: Vec<ProgramInput>,
+    max_cycles: Option<u64>,
+) -> Result<EstimatedWork> {
+    let mut exec = new_risc0_exec_env(image.get_memory_image()?, sorted_inputs, max_cycles)?;
+    let session = run_session(&mut exec)?;
+    Ok(EstimatedWork {
+        image_id: image.id.clone(),
+        stats: SessionStats::from(&session),
+    })
+}
 
 /// Creates a new risc0 executor environment from the provided inputs, it hadles setting up the execution env in the same way across types of provers.
+/// The session is limited to `max_cycles` user cycles, or `DEFAULT_MAX_CYCLES` when not set.
 pub fn new_risc0_exec_env(
     image: MemoryImage,
     sorted_inputs: Vec<ProgramInput>,
+    max_cycles: Option<u64>,
 ) -> Result<ExecutorImpl<'static>> {
+    let env = build_exec_env(sorted_inputs, max_cycles)?;
+    ExecutorImpl::new(env, image)
+}
+
+/// Runs the executor, returning `CyclesExceeded` when the guest reaches the cycle limit its
+/// environment was created with
+pub fn run_session(exec: &mut ExecutorImpl<'_>) -> Result<Session> {
+    exec.run().map_err(|e| {
+        if e.to_string() == SESSION_LIMIT_EXCEEDED {
+            CyclesExceeded.into()
+        } else {
+            e
+        }
+    })
+}
+
+fn build_exec_env(
+    sorted_inputs: Vec<ProgramInput>,
+    max_cycles: Option<u64>,
+) -> Result<ExecutorEnv<'static>> {
     let mut env_builder = ExecutorEnv::builder();
+    env_builder.session_limit(Some(max_cycles.unwrap_or(DEFAULT_MAX_CYCLES)));
+    let mut assumptions = HashSet::new();
     for input in sorted_inputs.into_iter() {
         match input {
             ProgramInput::Resolved(ri) => {
                 if ri.input_type == ProgramInputType::PublicProof {
-                    let reciept: Receipt = bincode::deserialize(&ri.data)?;
-                    env_builder.add_assumption(reciept);
+                    add_assumption(&mut env_builder, &mut assumptions, ri.index, &ri.data)?;
                 } else {
                     env_builder.write_slice(&ri.data);
                 }
@@ -28,13 +130,220 @@ pub fn new_risc0_exec_env(
             }
         }
     }
-    let env = env_builder.build()?;
-    ExecutorImpl::new(env, image)
+    env_builder.build()
+}
+
+/// Adds a `PublicProof` input as an assumption the guest can compose over. Passing the same
+/// receipt twice is rejected, it is never needed and most likely a mistake in the inputs.
+fn add_assumption(
+    env_builder: &mut ExecutorEnvBuilder<'static>,
+    assumptions: &mut HashSet<Digest>,
+    index: u8,
+    data: &[u8],
+) -> Result<()> {
+    let receipt = deserialize_receipt(data)
+        .map_err(|e| anyhow!("Invalid assumption receipt in input {}: {}", index, e))?;
+    let claim = receipt
+        .claim()
+        .map_err(|e| anyhow!("Invalid assumption receipt in input {}: {}", index, e))?;
+    if !assumptions.insert(claim.digest()) {
+        return Err(anyhow!("Duplicate assumption receipt in input {}", index));
+    }
+    match claim.as_value() {
+        Ok(claim) => info!(index, image_id = %claim.pre.digest(), "Adding assumption"),
+        Err(_) => info!(index, "Adding assumption with a pruned claim"),
+    }
+    env_builder.add_assumption(receipt);
+    Ok(())
+}
+
+/// The kind of receipt a prover produces, succinct and groth16 receipts are smaller and cheaper to
+/// verify but take longer to prove. Groth16 proving needs docker on x86.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ProverKind {
+    #[default]
+    Default,
+    Succinct,
+    Groth16,
+}
+
+impl ProverKind {
+    pub fn opts(self) -> ProverOpts {
+        match self {
+            ProverKind::Default => ProverOpts::default(),
+            ProverKind::Succinct => ProverOpts::succinct(),
+            ProverKind::Groth16 => ProverOpts::groth16(),
+        }
+    }
+}
+
+impl FromStr for ProverKind {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "default" => Ok(ProverKind::Default),
+            "succinct" => Ok(ProverKind::Succinct),
+            "groth16" => Ok(ProverKind::Groth16),
+            _ => Err(anyhow!(
+                "Invalid prover kind, expected default, succinct or groth16"
+            )),
+        }
+    }
 }
 
 /// Gets the default r0 prover for this application
 /// Since the cli and the node both produce proofs there is a need for a central prover configuration.
 pub fn get_risc0_prover() -> Result<Rc<dyn ProverServer>> {
-    let opts = ProverOpts::default();
-    get_prover_server(&opts)
+    get_risc0_prover_with(ProverKind::Default)
+}
+
+pub fn get_risc0_prover_with(kind: ProverKind) -> Result<Rc<dyn ProverServer>> {
+    get_prover_server(&kind.opts())
+}
+
+#[cfg(test)]
+mod tests {
+    use bytes::Bytes;
+    use risc0_zkvm::{FakeReceipt, InnerReceipt, Receipt, ReceiptClaim, ReceiptKind};
+
+    use super::*;
+    use crate::input_resolver::ResolvedInput;
+    use crate::receipt_file::serialize_receipt;
+
+    const SIMPLE_IMAGE: &str = concat!(
+        env!("CARGO_MANIFEST_DIR"),
+        "/../elf/68f4b0c5f9ce034aa60ceb264a18d6c410a3af68fafd931bcfd9ebe7c1e42960"
+    );
+
+    fn simple_image() -> Image {
+        let elf = std::fs::read(SIMPLE_IMAGE).unwrap();
+        Image::from_bytes(Bytes::from(elf)).unwrap()
+    }
+
+    fn simple_inputs() -> Vec<ProgramInput> {
+        [ProgramInputType::Public, ProgramInputType::Private]
+            .into_iter()
+            .enumerate()
+            .map(|(index, input_type)| {
+                ProgramInput::Resolved(ResolvedInput {
+                    index: index as u8,
+                    data: b"test".to_vec(),
+                    input_type,
+                })
+            })
+            .collect()
+    }
+
+    fn simple_exec_env(max_cycles: Option<u64>) -> ExecutorImpl<'static> {
+        let memory_image = simple_image().get_memory_image().unwrap();
+        new_risc0_exec_env(memory_image, simple_inputs(), max_cycles).unwrap()
+    }
+
+    fn assumption(index: u8, image_id: [u32; 8]) -> ProgramInput {
+        let journal = vec![1, 2, 3];
+        let claim = ReceiptClaim::ok(Digest::from(image_id), journal.clone());
+        let receipt = Receipt::new(InnerReceipt::Fake(FakeReceipt::new(claim)), journal);
+        proof_input(index, serialize_receipt(&receipt).unwrap())
+    }
+
+    fn proof_input(index: u8, data: Vec<u8>) -> ProgramInput {
+        ProgramInput::Resolved(ResolvedInput {
+            index,
+            data,
+            input_type: ProgramInputType::PublicProof,
+        })
+    }
+
+    #[test]
+    fn test_multiple_assumptions() {
+        let inputs = vec![assumption(0, [1; 8]), assumption(1, [2; 8])];
+        assert!(build_exec_env(inputs, None).is_ok());
+    }
+
+    #[test]
+    fn test_corrupt_assumption_names_input() {
+        let inputs = vec![
+            assumption(0, [1; 8]),
+            assumption(1, [2; 8]),
+            proof_input(2, vec![1, 2, 3]),
+        ];
+        let err = build_exec_env(inputs, None).err().unwrap();
+        assert!(err
+            .to_string()
+            .starts_with("Invalid assumption receipt in input 2"));
+    }
+
+    #[test]
+    fn test_duplicate_assumption_rejected() {
+        let inputs = vec![assumption(0, [1; 8]), assumption(1, [1; 8])];
+        let err = build_exec_env(inputs, None).err().unwrap();
+        assert_eq!(err.to_string(), "Duplicate assumption receipt in input 1");
+    }
+
+    #[test]
+    fn test_prover_kind_opts() {
+        assert!(matches!(
+            ProverKind::Default.opts().receipt_kind,
+            ReceiptKind::Composite
+        ));
+        assert!(matches!(
+            ProverKind::Succinct.opts().receipt_kind,
+            ReceiptKind::Succinct
+        ));
+        assert!(matches!(
+            ProverKind::Groth16.opts().receipt_kind,
+            ReceiptKind::Groth16
+        ));
+        assert_eq!(
+            "succinct".parse::<ProverKind>().unwrap(),
+            ProverKind::Succinct
+        );
+        assert!("fast".parse::<ProverKind>().is_err());
+    }
+
+    #[test]
+    fn test_session_within_cycle_limit() {
+        assert!(run_session(&mut simple_exec_env(None)).is_ok());
+    }
+
+    #[test]
+    fn test_session_exceeds_cycle_limit() {
+        let err = run_session(&mut simple_exec_env(Some(1))).err().unwrap();
+        assert!(err.downcast_ref::<CyclesExceeded>().is_some());
+    }
+
+    #[test]
+    fn test_cycle_limit_counts_user_cycles() {
+        let user_cycles = run_session(&mut simple_exec_env(None)).unwrap().user_cycles;
+        assert!(run_session(&mut simple_exec_env(Some(user_cycles))).is_ok());
+        let err = run_session(&mut simple_exec_env(Some(user_cycles - 1)))
+            .err()
+            .unwrap();
+        assert!(err.downcast_ref::<CyclesExceeded>().is_some());
+    }
+
+    #[test]
+    fn test_session_stats() {
+        let session = run_session(&mut simple_exec_env(None)).unwrap();
+        let stats = SessionStats::from(&session);
+        assert!(stats.user_cycles > 0);
+        assert!(stats.total_cycles >= stats.user_cycles);
+        assert!(stats.segments > 0);
+    }
+
+    #[test]
+    fn test_estimate_execution() {
+        let image = simple_image();
+        let work = estimate_execution(&image, simple_inputs(), None).unwrap();
+        assert_eq!(
+            work.image_id,
+            "68f4b0c5f9ce034aa60ceb264a18d6c410a3af68fafd931bcfd9ebe7c1e42960"
+        );
+        assert!(work.stats.user_cycles > 0);
+        assert!(work.stats.total_cycles >= work.stats.user_cycles);
+        assert!(work.stats.segments > 0);
+        let err = estimate_execution(&image, simple_inputs(), Some(1)).unwrap_err();
+        assert!(err.downcast_ref::<CyclesExceeded>().is_some());
+    }
 }
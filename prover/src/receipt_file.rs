@@ -0,0 +1,166 @@
+use anyhow::{anyhow, Result};
+use risc0_zkvm::Receipt;
+use sha2::{Digest, Sha256};
+
+/// Written at the start of every receipt file so other files are told apart from receipts
+const MAGIC: &[u8; 8] = b"BONSOLRC";
+
+/// Version of the header layout, bumped whenever the header changes
+pub const RECEIPT_FORMAT_VERSION: u8 = 2;
+
+/// Serializes a receipt for a proof file or a `PublicProof` input. Bincode has no notion of
+/// versions, so the receipt is prefixed with a header naming the file format and the risc0
+/// version that wrote it, followed by the sha256 of the serialized receipt so a truncated or
+/// corrupted file is caught before decoding.
+pub fn serialize_receipt(receipt: &Receipt) -> Result<Vec<u8>> {
+    serialize_with_version(receipt, risc0_zkvm::VERSION)
+}
+
+fn serialize_with_version(receipt: &Receipt, risc0_version: &str) -> Result<Vec<u8>> {
+    let version = u8::try_from(risc0_version.len())
+        .map_err(|_| anyhow!("Risc0 version '{}' is too long", risc0_version))?;
+    let mut data = MAGIC.to_vec();
+    data.push(RECEIPT_FORMAT_VERSION);
+    data.push(version);
+    data.extend_from_slice(risc0_version.as_bytes());
+    let receipt = bincode::serialize(receipt)?;
+    data.extend_from_slice(&Sha256::digest(&receipt));
+    data.extend_from_slice(&receipt);
+    Ok(data)
+}
+
+/// Reads a receipt written by `serialize_receipt`. Files without a header, from another format
+/// version, from a risc0 release with a different major or minor version or whose receipt does not
+/// match its checksum are rejected with an error saying so, instead of failing inside bincode.
+pub fn deserialize_receipt(data: &[u8]) -> Result<Receipt> {
+    let data = data
+        .strip_prefix(MAGIC)
+        .ok_or_else(|| anyhow!("Not a bonsol receipt, it may predate versioned receipts"))?;
+    let (&format_version, data) = data
+        .split_first()
+        .ok_or_else(|| anyhow!("Truncated receipt header"))?;
+    if format_version != RECEIPT_FORMAT_VERSION {
+        return Err(anyhow!(
+            "Receipt format version {} is not supported, expected {}",
+            format_version,
+            RECEIPT_FORMAT_VERSION
+        ));
+    }
+    let (&len, data) = data
+        .split_first()
+        .ok_or_else(|| anyhow!("Truncated receipt header"))?;
+    if data.len() < len as usize {
+        return Err(anyhow!("Truncated receipt header"));
+    }
+    let (risc0_version, data) = data.split_at(len as usize);
+    let risc0_version = std::str::from_utf8(risc0_version)
+        .map_err(|_| anyhow!("Invalid risc0 version in receipt header"))?;
+    if release(risc0_version) != release(risc0_zkvm::VERSION) {
+        return Err(anyhow!(
+            "Receipt was written by risc0 {} but this build uses {}, prove it again",
+            risc0_version,
+            risc0_zkvm::VERSION
+        ));
+    }
+    if data.len() < 32 {
+        return Err(anyhow!("Truncated receipt header"));
+    }
+    let (checksum, receipt) = data.split_at(32);
+    if Sha256::digest(receipt).as_slice() != checksum {
+        return Err(anyhow!(
+            "Receipt does not match its checksum, the file is truncated or corrupted"
+        ));
+    }
+    bincode::deserialize(receipt).map_err(|e| anyhow!("Invalid receipt: {}", e))
+}
+
+/// The major and minor version, receipts keep their layout across patch releases
+fn release(version: &str) -> (&str, &str) {
+    let mut parts = version.split('.');
+    (parts.next().unwrap_or(""), parts.next().unwrap_or(""))
+}
+
+#[cfg(test)]
+mod tests {
+    use risc0_zkvm::sha::Digest as Risc0Digest;
+    use risc0_zkvm::{FakeReceipt, InnerReceipt, ReceiptClaim};
+
+    use super::*;
+
+    fn receipt() -> Receipt {
+        let journal = vec![1, 2, 3];
+        let claim = ReceiptClaim::ok(Risc0Digest::from([1; 8]), journal.clone());
+        Receipt::new(InnerReceipt::Fake(FakeReceipt::new(claim)), journal)
+    }
+
+    #[test]
+    fn test_receipt_round_trip() {
+        let data = serialize_receipt(&receipt()).unwrap();
+        assert!(data.starts_with(MAGIC));
+        let read = deserialize_receipt(&data).unwrap();
+        assert_eq!(read.journal.bytes, vec![1, 2, 3]);
+    }
+
+    #[test]
+    fn test_untagged_receipt_rejected() {
+        let data = bincode::serialize(&receipt()).unwrap();
+        let err = deserialize_receipt(&data).unwrap_err();
+        assert!(err.to_string().starts_with("Not a bonsol receipt"));
+    }
+
+    #[test]
+    fn test_other_format_version_rejected() {
+        let mut data = serialize_receipt(&receipt()).unwrap();
+        data[MAGIC.len()] = RECEIPT_FORMAT_VERSION + 1;
+        let err = deserialize_receipt(&data).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            format!(
+                "Receipt format version {} is not supported, expected {}",
+                RECEIPT_FORMAT_VERSION + 1,
+                RECEIPT_FORMAT_VERSION
+            )
+        );
+    }
+
+    #[test]
+    fn test_other_risc0_release_rejected() {
+        let data = serialize_with_version(&receipt(), "0.1.0").unwrap();
+        let err = deserialize_receipt(&data).unwrap_err();
+        assert!(err
+            .to_string()
+            .starts_with("Receipt was written by risc0 0.1.0"));
+
+        // a patch release keeps the receipt layout
+        let (major, minor) = release(risc0_zkvm::VERSION);
+        let patch = format!("{}.{}.999", major, minor);
+        let data = serialize_with_version(&receipt(), &patch).unwrap();
+        assert!(deserialize_receipt(&data).is_ok());
+    }
+
+    #[test]
+    fn test_tampered_receipt_fails_checksum() {
+        let mut data = serialize_receipt(&receipt()).unwrap();
+        let last = data.len() - 1;
+        data[last] ^= 1;
+        let err = deserialize_receipt(&data).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Receipt does not match its checksum, the file is truncated or corrupted"
+        );
+
+        // a truncated write is caught the same way rather than by bincode
+        let data = serialize_receipt(&receipt()).unwrap();
+        let err = deserialize_receipt(&data[..data.len() - 1]).unwrap_err();
+        assert!(err
+            .to_string()
+            .starts_with("Receipt does not match its checksum"));
+    }
+
+    #[test]
+    fn test_truncated_header() {
+        let data = serialize_receipt(&receipt()).unwrap();
+        assert!(deserialize_receipt(&data[..MAGIC.len() + 3]).is_err());
+        assert!(deserialize_receipt(&data[..MAGIC.len()]).is_err());
+    }
+}
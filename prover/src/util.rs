@@ -5,6 +5,15 @@ use futures_util::{Stream, StreamExt};
 pub async fn get_body_max_size(
     stream: impl Stream<Item = reqwest::Result<Bytes>> + 'static,
     max_size: usize,
+) -> Result<Bytes> {
+    get_body_max_size_with_progress(stream, max_size, |_| {}).await
+}
+
+/// Same as `get_body_max_size` but calls `progress` with the total number of bytes received after every chunk
+pub async fn get_body_max_size_with_progress(
+    stream: impl Stream<Item = reqwest::Result<Bytes>> + 'static,
+    max_size: usize,
+    mut progress: impl FnMut(usize),
 ) -> Result<Bytes> {
     let mut max = 0;
     let mut b = BytesMut::new();
@@ -18,6 +27,7 @@ pub async fn get_body_max_size(
             return Err(anyhow::anyhow!("Max size exceeded"));
         }
         b.extend_from_slice(&chunk);
+        progress(max);
     }
     Ok(b.into())
 }
@@ -17,14 +17,16 @@ use self::flatbuffers::{EndianScalar, Follow};
 #[deprecated(since = "2.0.0", note = "Use associated constants instead. This will no longer be generated in 2021.")]
 pub const ENUM_MIN_CHANNEL_INSTRUCTION_IX_TYPE: u8 = 0;
 #[deprecated(since = "2.0.0", note = "Use associated constants instead. This will no longer be generated in 2021.")]
-pub const ENUM_MAX_CHANNEL_INSTRUCTION_IX_TYPE: u8 = 3;
+pub const ENUM_MAX_CHANNEL_INSTRUCTION_IX_TYPE: u8 = 6;
 #[deprecated(since = "2.0.0", note = "Use associated constants instead. This will no longer be generated in 2021.")]
 #[allow(non_camel_case_types)]
-pub const ENUM_VALUES_CHANNEL_INSTRUCTION_IX_TYPE: [ChannelInstructionIxType; 4] = [
+pub const ENUM_VALUES_CHANNEL_INSTRUCTION_IX_TYPE: [ChannelInstructionIxType; 6] = [
   ChannelInstructionIxType::ExecuteV1,
   ChannelInstructionIxType::StatusV1,
   ChannelInstructionIxType::DeployV1,
   ChannelInstructionIxType::ClaimV1,
+  ChannelInstructionIxType::ReclaimV1,
+  ChannelInstructionIxType::UpdateDeployV1,
 ];
 
 #[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
@@ -36,14 +38,18 @@ impl ChannelInstructionIxType {
   pub const StatusV1: Self = Self(1);
   pub const DeployV1: Self = Self(2);
   pub const ClaimV1: Self = Self(3);
+  pub const ReclaimV1: Self = Self(5);
+  pub const UpdateDeployV1: Self = Self(6);
 
   pub const ENUM_MIN: u8 = 0;
-  pub const ENUM_MAX: u8 = 3;
+  pub const ENUM_MAX: u8 = 6;
   pub const ENUM_VALUES: &'static [Self] = &[
     Self::ExecuteV1,
     Self::StatusV1,
     Self::DeployV1,
     Self::ClaimV1,
+    Self::ReclaimV1,
+    Self::UpdateDeployV1,
   ];
   /// Returns the variant's name or "" if unknown.
   pub fn variant_name(self) -> Option<&'static str> {
@@ -52,6 +58,8 @@ impl ChannelInstructionIxType {
       Self::StatusV1 => Some("StatusV1"),
       Self::DeployV1 => Some("DeployV1"),
       Self::ClaimV1 => Some("ClaimV1"),
+      Self::ReclaimV1 => Some("ReclaimV1"),
+      Self::UpdateDeployV1 => Some("UpdateDeployV1"),
       _ => None,
     }
   }
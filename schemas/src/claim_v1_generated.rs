@@ -9,6 +9,135 @@ use core::cmp::Ordering;
 extern crate flatbuffers;
 use self::flatbuffers::{EndianScalar, Follow};
 
+// struct ProverShare, aligned to 8
+#[repr(transparent)]
+#[derive(Clone, Copy, PartialEq)]
+pub struct ProverShare(pub [u8; 40]);
+impl Default for ProverShare { 
+  fn default() -> Self { 
+    Self([0; 40])
+  }
+}
+impl core::fmt::Debug for ProverShare {
+  fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+    f.debug_struct("ProverShare")
+      .field("weight", &self.weight())
+      .field("prover", &self.prover())
+      .finish()
+  }
+}
+
+impl flatbuffers::SimpleToVerifyInSlice for ProverShare {}
+impl<'a> flatbuffers::Follow<'a> for ProverShare {
+  type Inner = &'a ProverShare;
+  #[inline]
+  unsafe fn follow(buf: &'a [u8], loc: usize) -> Self::Inner {
+    <&'a ProverShare>::follow(buf, loc)
+  }
+}
+impl<'a> flatbuffers::Follow<'a> for &'a ProverShare {
+  type Inner = &'a ProverShare;
+  #[inline]
+  unsafe fn follow(buf: &'a [u8], loc: usize) -> Self::Inner {
+    flatbuffers::follow_cast_ref::<ProverShare>(buf, loc)
+  }
+}
+impl<'b> flatbuffers::Push for ProverShare {
+    type Output = ProverShare;
+    #[inline]
+    unsafe fn push(&self, dst: &mut [u8], _written_len: usize) {
+        let src = ::core::slice::from_raw_parts(self as *const ProverShare as *const u8, Self::size());
+        dst.copy_from_slice(src);
+    }
+}
+
+impl<'a> flatbuffers::Verifiable for ProverShare {
+  #[inline]
+  fn run_verifier(
+    v: &mut flatbuffers::Verifier, pos: usize
+  ) -> Result<(), flatbuffers::InvalidFlatbuffer> {
+    use self::flatbuffers::Verifiable;
+    v.in_buffer::<Self>(pos)
+  }
+}
+
+impl<'a> ProverShare {
+  #[allow(clippy::too_many_arguments)]
+  pub fn new(
+    weight: u64,
+    prover: &[u8; 32],
+  ) -> Self {
+    let mut s = Self([0; 40]);
+    s.set_weight(weight);
+    s.set_prover(prover);
+    s
+  }
+
+  pub fn weight(&self) -> u64 {
+    let mut mem = core::mem::MaybeUninit::<<u64 as EndianScalar>::Scalar>::uninit();
+    // Safety:
+    // Created from a valid Table for this object
+    // Which contains a valid value in this slot
+    EndianScalar::from_little_endian(unsafe {
+      core::ptr::copy_nonoverlapping(
+        self.0[0..].as_ptr(),
+        mem.as_mut_ptr() as *mut u8,
+        core::mem::size_of::<<u64 as EndianScalar>::Scalar>(),
+      );
+      mem.assume_init()
+    })
+  }
+
+  pub fn set_weight(&mut self, x: u64) {
+    let x_le = x.to_little_endian();
+    // Safety:
+    // Created from a valid Table for this object
+    // Which contains a valid value in this slot
+    unsafe {
+      core::ptr::copy_nonoverlapping(
+        &x_le as *const _ as *const u8,
+        self.0[0..].as_mut_ptr(),
+        core::mem::size_of::<<u64 as EndianScalar>::Scalar>(),
+      );
+    }
+  }
+
+  pub fn prover(&'a self) -> flatbuffers::Array<'a, u8, 32> {
+    // Safety:
+    // Created from a valid Table for this object
+    // Which contains a valid array in this slot
+    unsafe { flatbuffers::Array::follow(&self.0, 8) }
+  }
+
+  pub fn set_prover(&mut self, items: &[u8; 32]) {
+    // Safety:
+    // Created from a valid Table for this object
+    // Which contains a valid array in this slot
+    unsafe { flatbuffers::emplace_scalar_array(&mut self.0, 8, items) };
+  }
+
+  pub fn unpack(&self) -> ProverShareT {
+    ProverShareT {
+      weight: self.weight(),
+      prover: self.prover().into(),
+    }
+  }
+}
+
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct ProverShareT {
+  pub weight: u64,
+  pub prover: [u8; 32],
+}
+impl ProverShareT {
+  pub fn pack(&self) -> ProverShare {
+    ProverShare::new(
+      self.weight,
+      &self.prover,
+    )
+  }
+}
+
 pub enum ClaimV1Offset {}
 #[derive(Copy, Clone, PartialEq)]
 
@@ -27,6 +156,7 @@ impl<'a> flatbuffers::Follow<'a> for ClaimV1<'a> {
 impl<'a> ClaimV1<'a> {
   pub const VT_EXECUTION_ID: flatbuffers::VOffsetT = 4;
   pub const VT_BLOCK_COMMITMENT: flatbuffers::VOffsetT = 6;
+  pub const VT_PROVER_SHARES: flatbuffers::VOffsetT = 8;
 
   #[inline]
   pub unsafe fn init_from_table(table: flatbuffers::Table<'a>) -> Self {
@@ -39,6 +169,7 @@ impl<'a> ClaimV1<'a> {
   ) -> flatbuffers::WIPOffset<ClaimV1<'bldr>> {
     let mut builder = ClaimV1Builder::new(_fbb);
     builder.add_block_commitment(args.block_commitment);
+    if let Some(x) = args.prover_shares { builder.add_prover_shares(x); }
     if let Some(x) = args.execution_id { builder.add_execution_id(x); }
     builder.finish()
   }
@@ -48,9 +179,13 @@ impl<'a> ClaimV1<'a> {
       x.to_string()
     });
     let block_commitment = self.block_commitment();
+    let prover_shares = self.prover_shares().map(|x| {
+      x.iter().map(|t| t.unpack()).collect()
+    });
     ClaimV1T {
       execution_id,
       block_commitment,
+      prover_shares,
     }
   }
 
@@ -68,6 +203,13 @@ impl<'a> ClaimV1<'a> {
     // which contains a valid value in this slot
     unsafe { self._tab.get::<u64>(ClaimV1::VT_BLOCK_COMMITMENT, Some(0)).unwrap()}
   }
+  #[inline]
+  pub fn prover_shares(&self) -> Option<flatbuffers::Vector<'a, ProverShare>> {
+    // Safety:
+    // Created from valid Table for this object
+    // which contains a valid value in this slot
+    unsafe { self._tab.get::<flatbuffers::ForwardsUOffset<flatbuffers::Vector<'a, ProverShare>>>(ClaimV1::VT_PROVER_SHARES, None)}
+  }
 }
 
 impl flatbuffers::Verifiable for ClaimV1<'_> {
@@ -79,6 +221,7 @@ impl flatbuffers::Verifiable for ClaimV1<'_> {
     v.visit_table(pos)?
      .visit_field::<flatbuffers::ForwardsUOffset<&str>>("execution_id", Self::VT_EXECUTION_ID, false)?
      .visit_field::<u64>("block_commitment", Self::VT_BLOCK_COMMITMENT, false)?
+     .visit_field::<flatbuffers::ForwardsUOffset<flatbuffers::Vector<'_, ProverShare>>>("prover_shares", Self::VT_PROVER_SHARES, false)?
      .finish();
     Ok(())
   }
@@ -86,6 +229,7 @@ impl flatbuffers::Verifiable for ClaimV1<'_> {
 pub struct ClaimV1Args<'a> {
     pub execution_id: Option<flatbuffers::WIPOffset<&'a str>>,
     pub block_commitment: u64,
+    pub prover_shares: Option<flatbuffers::WIPOffset<flatbuffers::Vector<'a, ProverShare>>>,
 }
 impl<'a> Default for ClaimV1Args<'a> {
   #[inline]
@@ -93,6 +237,7 @@ impl<'a> Default for ClaimV1Args<'a> {
     ClaimV1Args {
       execution_id: None,
       block_commitment: 0,
+      prover_shares: None,
     }
   }
 }
@@ -111,6 +256,10 @@ impl<'a: 'b, 'b, A: flatbuffers::Allocator + 'a> ClaimV1Builder<'a, 'b, A> {
     self.fbb_.push_slot::<u64>(ClaimV1::VT_BLOCK_COMMITMENT, block_commitment, 0);
   }
   #[inline]
+  pub fn add_prover_shares(&mut self, prover_shares: flatbuffers::WIPOffset<flatbuffers::Vector<'b , ProverShare>>) {
+    self.fbb_.push_slot_always::<flatbuffers::WIPOffset<_>>(ClaimV1::VT_PROVER_SHARES, prover_shares);
+  }
+  #[inline]
   pub fn new(_fbb: &'b mut flatbuffers::FlatBufferBuilder<'a, A>) -> ClaimV1Builder<'a, 'b, A> {
     let start = _fbb.start_table();
     ClaimV1Builder {
@@ -130,6 +279,7 @@ impl core::fmt::Debug for ClaimV1<'_> {
     let mut ds = f.debug_struct("ClaimV1");
       ds.field("execution_id", &self.execution_id());
       ds.field("block_commitment", &self.block_commitment());
+      ds.field("prover_shares", &self.prover_shares());
       ds.finish()
   }
 }
@@ -138,12 +288,14 @@ impl core::fmt::Debug for ClaimV1<'_> {
 pub struct ClaimV1T {
   pub execution_id: Option<String>,
   pub block_commitment: u64,
+  pub prover_shares: Option<Vec<ProverShareT>>,
 }
 impl Default for ClaimV1T {
   fn default() -> Self {
     Self {
       execution_id: None,
       block_commitment: 0,
+      prover_shares: None,
     }
   }
 }
@@ -156,9 +308,13 @@ impl ClaimV1T {
       _fbb.create_string(x)
     });
     let block_commitment = self.block_commitment;
+    let prover_shares = self.prover_shares.as_ref().map(|x|{
+      let w: Vec<_> = x.iter().map(|t| t.pack()).collect();_fbb.create_vector(&w)
+    });
     ClaimV1::create(_fbb, &ClaimV1Args{
       execution_id,
       block_commitment,
+      prover_shares,
     })
   }
 }
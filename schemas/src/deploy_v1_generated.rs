@@ -32,6 +32,7 @@ impl<'a> DeployV1<'a> {
   pub const VT_URL: flatbuffers::VOffsetT = 10;
   pub const VT_SIZE_: flatbuffers::VOffsetT = 12;
   pub const VT_INPUTS: flatbuffers::VOffsetT = 14;
+  pub const VT_SHA256: flatbuffers::VOffsetT = 16;
 
   #[inline]
   pub unsafe fn init_from_table(table: flatbuffers::Table<'a>) -> Self {
@@ -44,6 +45,7 @@ impl<'a> DeployV1<'a> {
   ) -> flatbuffers::WIPOffset<DeployV1<'bldr>> {
     let mut builder = DeployV1Builder::new(_fbb);
     builder.add_size_(args.size_);
+    if let Some(x) = args.sha256 { builder.add_sha256(x); }
     if let Some(x) = args.inputs { builder.add_inputs(x); }
     if let Some(x) = args.url { builder.add_url(x); }
     if let Some(x) = args.program_name { builder.add_program_name(x); }
@@ -69,6 +71,9 @@ impl<'a> DeployV1<'a> {
     let inputs = self.inputs().map(|x| {
       x.into_iter().collect()
     });
+    let sha256 = self.sha256().map(|x| {
+      x.into_iter().collect()
+    });
     DeployV1T {
       owner,
       image_id,
@@ -76,6 +81,7 @@ impl<'a> DeployV1<'a> {
       url,
       size_,
       inputs,
+      sha256,
     }
   }
 
@@ -121,6 +127,13 @@ impl<'a> DeployV1<'a> {
     // which contains a valid value in this slot
     unsafe { self._tab.get::<flatbuffers::ForwardsUOffset<flatbuffers::Vector<'a, ProgramInputType>>>(DeployV1::VT_INPUTS, None)}
   }
+  #[inline]
+  pub fn sha256(&self) -> Option<flatbuffers::Vector<'a, u8>> {
+    // Safety:
+    // Created from valid Table for this object
+    // which contains a valid value in this slot
+    unsafe { self._tab.get::<flatbuffers::ForwardsUOffset<flatbuffers::Vector<'a, u8>>>(DeployV1::VT_SHA256, None)}
+  }
 }
 
 impl flatbuffers::Verifiable for DeployV1<'_> {
@@ -136,6 +149,7 @@ impl flatbuffers::Verifiable for DeployV1<'_> {
      .visit_field::<flatbuffers::ForwardsUOffset<&str>>("url", Self::VT_URL, false)?
      .visit_field::<u64>("size_", Self::VT_SIZE_, false)?
      .visit_field::<flatbuffers::ForwardsUOffset<flatbuffers::Vector<'_, ProgramInputType>>>("inputs", Self::VT_INPUTS, false)?
+     .visit_field::<flatbuffers::ForwardsUOffset<flatbuffers::Vector<'_, u8>>>("sha256", Self::VT_SHA256, false)?
      .finish();
     Ok(())
   }
@@ -147,6 +161,7 @@ pub struct DeployV1Args<'a> {
     pub url: Option<flatbuffers::WIPOffset<&'a str>>,
     pub size_: u64,
     pub inputs: Option<flatbuffers::WIPOffset<flatbuffers::Vector<'a, ProgramInputType>>>,
+    pub sha256: Option<flatbuffers::WIPOffset<flatbuffers::Vector<'a, u8>>>,
 }
 impl<'a> Default for DeployV1Args<'a> {
   #[inline]
@@ -158,6 +173,7 @@ impl<'a> Default for DeployV1Args<'a> {
       url: None,
       size_: 0,
       inputs: None,
+      sha256: None,
     }
   }
 }
@@ -192,6 +208,10 @@ impl<'a: 'b, 'b, A: flatbuffers::Allocator + 'a> DeployV1Builder<'a, 'b, A> {
     self.fbb_.push_slot_always::<flatbuffers::WIPOffset<_>>(DeployV1::VT_INPUTS, inputs);
   }
   #[inline]
+  pub fn add_sha256(&mut self, sha256: flatbuffers::WIPOffset<flatbuffers::Vector<'b , u8>>) {
+    self.fbb_.push_slot_always::<flatbuffers::WIPOffset<_>>(DeployV1::VT_SHA256, sha256);
+  }
+  #[inline]
   pub fn new(_fbb: &'b mut flatbuffers::FlatBufferBuilder<'a, A>) -> DeployV1Builder<'a, 'b, A> {
     let start = _fbb.start_table();
     DeployV1Builder {
@@ -215,6 +235,7 @@ impl core::fmt::Debug for DeployV1<'_> {
       ds.field("url", &self.url());
       ds.field("size_", &self.size_());
       ds.field("inputs", &self.inputs());
+      ds.field("sha256", &self.sha256());
       ds.finish()
   }
 }
@@ -227,6 +248,7 @@ pub struct DeployV1T {
   pub url: Option<String>,
   pub size_: u64,
   pub inputs: Option<Vec<ProgramInputType>>,
+  pub sha256: Option<Vec<u8>>,
 }
 impl Default for DeployV1T {
   fn default() -> Self {
@@ -237,6 +259,7 @@ impl Default for DeployV1T {
       url: None,
       size_: 0,
       inputs: None,
+      sha256: None,
     }
   }
 }
@@ -261,6 +284,9 @@ impl DeployV1T {
     let inputs = self.inputs.as_ref().map(|x|{
       _fbb.create_vector(x)
     });
+    let sha256 = self.sha256.as_ref().map(|x|{
+      _fbb.create_vector(x)
+    });
     DeployV1::create(_fbb, &DeployV1Args{
       owner,
       image_id,
@@ -268,6 +294,7 @@ impl DeployV1T {
       url,
       size_,
       inputs,
+      sha256,
     })
   }
 }
@@ -256,6 +256,7 @@ impl<'a> ExecutionRequestV1<'a> {
   pub const VT_MAX_BLOCK_HEIGHT: flatbuffers::VOffsetT = 22;
   pub const VT_CALLBACK_EXTRA_ACCOUNTS: flatbuffers::VOffsetT = 24;
   pub const VT_PROVER_VERSION: flatbuffers::VOffsetT = 26;
+  pub const VT_REFUND_DESTINATION: flatbuffers::VOffsetT = 28;
 
   #[inline]
   pub unsafe fn init_from_table(table: flatbuffers::Table<'a>) -> Self {
@@ -269,6 +270,7 @@ impl<'a> ExecutionRequestV1<'a> {
     let mut builder = ExecutionRequestV1Builder::new(_fbb);
     builder.add_max_block_height(args.max_block_height);
     builder.add_tip(args.tip);
+    if let Some(x) = args.refund_destination { builder.add_refund_destination(x); }
     if let Some(x) = args.callback_extra_accounts { builder.add_callback_extra_accounts(x); }
     if let Some(x) = args.input_digest { builder.add_input_digest(x); }
     if let Some(x) = args.input { builder.add_input(x); }
@@ -309,6 +311,9 @@ impl<'a> ExecutionRequestV1<'a> {
       x.iter().map(|t| t.unpack()).collect()
     });
     let prover_version = self.prover_version();
+    let refund_destination = self.refund_destination().map(|x| {
+      x.into_iter().collect()
+    });
     ExecutionRequestV1T {
       tip,
       execution_id,
@@ -322,6 +327,7 @@ impl<'a> ExecutionRequestV1<'a> {
       max_block_height,
       callback_extra_accounts,
       prover_version,
+      refund_destination,
     }
   }
 
@@ -409,6 +415,13 @@ impl<'a> ExecutionRequestV1<'a> {
     // which contains a valid value in this slot
     unsafe { self._tab.get::<ProverVersion>(ExecutionRequestV1::VT_PROVER_VERSION, Some(ProverVersion::DEFAULT)).unwrap()}
   }
+  #[inline]
+  pub fn refund_destination(&self) -> Option<flatbuffers::Vector<'a, u8>> {
+    // Safety:
+    // Created from valid Table for this object
+    // which contains a valid value in this slot
+    unsafe { self._tab.get::<flatbuffers::ForwardsUOffset<flatbuffers::Vector<'a, u8>>>(ExecutionRequestV1::VT_REFUND_DESTINATION, None)}
+  }
 }
 
 impl flatbuffers::Verifiable for ExecutionRequestV1<'_> {
@@ -430,6 +443,7 @@ impl flatbuffers::Verifiable for ExecutionRequestV1<'_> {
      .visit_field::<u64>("max_block_height", Self::VT_MAX_BLOCK_HEIGHT, false)?
      .visit_field::<flatbuffers::ForwardsUOffset<flatbuffers::Vector<'_, Account>>>("callback_extra_accounts", Self::VT_CALLBACK_EXTRA_ACCOUNTS, false)?
      .visit_field::<ProverVersion>("prover_version", Self::VT_PROVER_VERSION, false)?
+     .visit_field::<flatbuffers::ForwardsUOffset<flatbuffers::Vector<'_, u8>>>("refund_destination", Self::VT_REFUND_DESTINATION, false)?
      .finish();
     Ok(())
   }
@@ -447,6 +461,7 @@ pub struct ExecutionRequestV1Args<'a> {
     pub max_block_height: u64,
     pub callback_extra_accounts: Option<flatbuffers::WIPOffset<flatbuffers::Vector<'a, Account>>>,
     pub prover_version: ProverVersion,
+    pub refund_destination: Option<flatbuffers::WIPOffset<flatbuffers::Vector<'a, u8>>>,
 }
 impl<'a> Default for ExecutionRequestV1Args<'a> {
   #[inline]
@@ -464,6 +479,7 @@ impl<'a> Default for ExecutionRequestV1Args<'a> {
       max_block_height: 0,
       callback_extra_accounts: None,
       prover_version: ProverVersion::DEFAULT,
+      refund_destination: None,
     }
   }
 }
@@ -522,6 +538,10 @@ impl<'a: 'b, 'b, A: flatbuffers::Allocator + 'a> ExecutionRequestV1Builder<'a, '
     self.fbb_.push_slot::<ProverVersion>(ExecutionRequestV1::VT_PROVER_VERSION, prover_version, ProverVersion::DEFAULT);
   }
   #[inline]
+  pub fn add_refund_destination(&mut self, refund_destination: flatbuffers::WIPOffset<flatbuffers::Vector<'b , u8>>) {
+    self.fbb_.push_slot_always::<flatbuffers::WIPOffset<_>>(ExecutionRequestV1::VT_REFUND_DESTINATION, refund_destination);
+  }
+  #[inline]
   pub fn new(_fbb: &'b mut flatbuffers::FlatBufferBuilder<'a, A>) -> ExecutionRequestV1Builder<'a, 'b, A> {
     let start = _fbb.start_table();
     ExecutionRequestV1Builder {
@@ -551,6 +571,7 @@ impl core::fmt::Debug for ExecutionRequestV1<'_> {
       ds.field("max_block_height", &self.max_block_height());
       ds.field("callback_extra_accounts", &self.callback_extra_accounts());
       ds.field("prover_version", &self.prover_version());
+      ds.field("refund_destination", &self.refund_destination());
       ds.finish()
   }
 }
@@ -569,6 +590,7 @@ pub struct ExecutionRequestV1T {
   pub max_block_height: u64,
   pub callback_extra_accounts: Option<Vec<AccountT>>,
   pub prover_version: ProverVersion,
+  pub refund_destination: Option<Vec<u8>>,
 }
 impl Default for ExecutionRequestV1T {
   fn default() -> Self {
@@ -585,6 +607,7 @@ impl Default for ExecutionRequestV1T {
       max_block_height: 0,
       callback_extra_accounts: None,
       prover_version: ProverVersion::DEFAULT,
+      refund_destination: None,
     }
   }
 }
@@ -619,6 +642,9 @@ impl ExecutionRequestV1T {
       let w: Vec<_> = x.iter().map(|t| t.pack()).collect();_fbb.create_vector(&w)
     });
     let prover_version = self.prover_version;
+    let refund_destination = self.refund_destination.as_ref().map(|x|{
+      _fbb.create_vector(x)
+    });
     ExecutionRequestV1::create(_fbb, &ExecutionRequestV1Args{
       tip,
       execution_id,
@@ -632,6 +658,7 @@ impl ExecutionRequestV1T {
       max_block_height,
       callback_extra_accounts,
       prover_version,
+      refund_destination,
     })
   }
 }
@@ -131,6 +131,7 @@ impl<'a> StatusV1<'a> {
   pub const VT_ASSUMPTION_DIGEST: flatbuffers::VOffsetT = 16;
   pub const VT_EXIT_CODE_SYSTEM: flatbuffers::VOffsetT = 18;
   pub const VT_EXIT_CODE_USER: flatbuffers::VOffsetT = 20;
+  pub const VT_DEV_MODE: flatbuffers::VOffsetT = 22;
 
   #[inline]
   pub unsafe fn init_from_table(table: flatbuffers::Table<'a>) -> Self {
@@ -150,6 +151,7 @@ impl<'a> StatusV1<'a> {
     if let Some(x) = args.execution_digest { builder.add_execution_digest(x); }
     if let Some(x) = args.proof { builder.add_proof(x); }
     if let Some(x) = args.execution_id { builder.add_execution_id(x); }
+    builder.add_dev_mode(args.dev_mode);
     builder.add_status(args.status);
     builder.finish()
   }
@@ -176,6 +178,7 @@ impl<'a> StatusV1<'a> {
     });
     let exit_code_system = self.exit_code_system();
     let exit_code_user = self.exit_code_user();
+    let dev_mode = self.dev_mode();
     StatusV1T {
       execution_id,
       status,
@@ -186,6 +189,7 @@ impl<'a> StatusV1<'a> {
       assumption_digest,
       exit_code_system,
       exit_code_user,
+      dev_mode,
     }
   }
 
@@ -252,6 +256,13 @@ impl<'a> StatusV1<'a> {
     // which contains a valid value in this slot
     unsafe { self._tab.get::<u32>(StatusV1::VT_EXIT_CODE_USER, Some(0)).unwrap()}
   }
+  #[inline]
+  pub fn dev_mode(&self) -> bool {
+    // Safety:
+    // Created from valid Table for this object
+    // which contains a valid value in this slot
+    unsafe { self._tab.get::<bool>(StatusV1::VT_DEV_MODE, Some(false)).unwrap()}
+  }
 }
 
 impl flatbuffers::Verifiable for StatusV1<'_> {
@@ -270,6 +281,7 @@ impl flatbuffers::Verifiable for StatusV1<'_> {
      .visit_field::<flatbuffers::ForwardsUOffset<flatbuffers::Vector<'_, u8>>>("assumption_digest", Self::VT_ASSUMPTION_DIGEST, false)?
      .visit_field::<u32>("exit_code_system", Self::VT_EXIT_CODE_SYSTEM, false)?
      .visit_field::<u32>("exit_code_user", Self::VT_EXIT_CODE_USER, false)?
+     .visit_field::<bool>("dev_mode", Self::VT_DEV_MODE, false)?
      .finish();
     Ok(())
   }
@@ -284,6 +296,7 @@ pub struct StatusV1Args<'a> {
     pub assumption_digest: Option<flatbuffers::WIPOffset<flatbuffers::Vector<'a, u8>>>,
     pub exit_code_system: u32,
     pub exit_code_user: u32,
+    pub dev_mode: bool,
 }
 impl<'a> Default for StatusV1Args<'a> {
   #[inline]
@@ -298,6 +311,7 @@ impl<'a> Default for StatusV1Args<'a> {
       assumption_digest: None,
       exit_code_system: 0,
       exit_code_user: 0,
+      dev_mode: false,
     }
   }
 }
@@ -344,6 +358,10 @@ impl<'a: 'b, 'b, A: flatbuffers::Allocator + 'a> StatusV1Builder<'a, 'b, A> {
     self.fbb_.push_slot::<u32>(StatusV1::VT_EXIT_CODE_USER, exit_code_user, 0);
   }
   #[inline]
+  pub fn add_dev_mode(&mut self, dev_mode: bool) {
+    self.fbb_.push_slot::<bool>(StatusV1::VT_DEV_MODE, dev_mode, false);
+  }
+  #[inline]
   pub fn new(_fbb: &'b mut flatbuffers::FlatBufferBuilder<'a, A>) -> StatusV1Builder<'a, 'b, A> {
     let start = _fbb.start_table();
     StatusV1Builder {
@@ -370,6 +388,7 @@ impl core::fmt::Debug for StatusV1<'_> {
       ds.field("assumption_digest", &self.assumption_digest());
       ds.field("exit_code_system", &self.exit_code_system());
       ds.field("exit_code_user", &self.exit_code_user());
+      ds.field("dev_mode", &self.dev_mode());
       ds.finish()
   }
 }
@@ -385,6 +404,7 @@ pub struct StatusV1T {
   pub assumption_digest: Option<Vec<u8>>,
   pub exit_code_system: u32,
   pub exit_code_user: u32,
+  pub dev_mode: bool,
 }
 impl Default for StatusV1T {
   fn default() -> Self {
@@ -398,6 +418,7 @@ impl Default for StatusV1T {
       assumption_digest: None,
       exit_code_system: 0,
       exit_code_user: 0,
+      dev_mode: false,
     }
   }
 }
@@ -427,6 +448,7 @@ impl StatusV1T {
     });
     let exit_code_system = self.exit_code_system;
     let exit_code_user = self.exit_code_user;
+    let dev_mode = self.dev_mode;
     StatusV1::create(_fbb, &StatusV1Args{
       execution_id,
       status,
@@ -437,6 +459,7 @@ impl StatusV1T {
       assumption_digest,
       exit_code_system,
       exit_code_user,
+      dev_mode,
     })
   }
 }
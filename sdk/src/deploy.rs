@@ -0,0 +1,281 @@
+use reqwest::header::{CONTENT_ENCODING, CONTENT_LENGTH, RANGE};
+use reqwest::Url;
+use solana_sdk::instruction::Instruction;
+use solana_sdk::pubkey::Pubkey;
+
+use crate::{BonsolClient, BonsolClientError, ProgramInputType, Result, GZIP_MAGIC, ZSTD_MAGIC};
+
+/// Builds deployment instructions, validating the program url before anything is sent.
+#[derive(Debug, Clone)]
+pub struct DeployBuilder {
+    image_id: String,
+    image_size: u64,
+    program_name: String,
+    url: String,
+    inputs: Vec<ProgramInputType>,
+    compute_unit_limit: Option<u32>,
+    sha256: Option<[u8; 32]>,
+    check_image_size: bool,
+}
+
+impl DeployBuilder {
+    pub fn new(image_id: &str, image_size: u64, program_name: &str, url: &str) -> Self {
+        DeployBuilder {
+            image_id: image_id.to_string(),
+            image_size,
+            program_name: program_name.to_string(),
+            url: url.to_string(),
+            inputs: vec![],
+            compute_unit_limit: None,
+            sha256: None,
+            check_image_size: false,
+        }
+    }
+
+    pub fn inputs(mut self, inputs: Vec<ProgramInputType>) -> Self {
+        self.inputs = inputs;
+        self
+    }
+
+    pub const fn compute_unit_limit(mut self, compute_unit_limit: u32) -> Self {
+        self.compute_unit_limit = Some(compute_unit_limit);
+        self
+    }
+
+    /// Stores the sha256 of the program elf so `download_program` can reject a corrupted download
+    /// before computing the image id
+    pub const fn sha256(mut self, sha256: [u8; 32]) -> Self {
+        self.sha256 = Some(sha256);
+        self
+    }
+
+    /// Sends a HEAD request to the url during `build`, with the client's http client, and fails if
+    /// the Content-Length does not match `image_size`. Servers that omit the header are not checked,
+    /// and neither are compressed programs, whose Content-Length is the compressed size.
+    pub const fn check_image_size(mut self, check: bool) -> Self {
+        self.check_image_size = check;
+        self
+    }
+
+    pub async fn build(self, client: &BonsolClient, signer: &Pubkey) -> Result<Vec<Instruction>> {
+        let url = validate_url(&self.url)?;
+        if self.check_image_size {
+            check_content_length(&client.http_client, url, self.image_size).await?;
+        }
+        client
+            .deploy_v1(
+                signer,
+                &self.image_id,
+                self.image_size,
+                &self.program_name,
+                &self.url,
+                self.inputs,
+                self.sha256,
+                self.compute_unit_limit,
+            )
+            .await
+    }
+}
+
+pub(crate) fn validate_url(url: &str) -> Result<Url> {
+    let parsed = Url::parse(url)
+        .map_err(|e| BonsolClientError::InvalidDeploymentUrl(format!("{}: {}", url, e)))?;
+    match parsed.scheme() {
+        "http" | "https" => Ok(parsed),
+        scheme => Err(BonsolClientError::InvalidDeploymentUrl(format!(
+            "{}: unsupported scheme {}",
+            url, scheme
+        ))),
+    }
+}
+
+async fn check_content_length(
+    http_client: &reqwest::Client,
+    url: Url,
+    image_size: u64,
+) -> Result<()> {
+    let resp = http_client
+        .head(url.clone())
+        .send()
+        .await?
+        .error_for_status()?;
+    if resp.headers().contains_key(CONTENT_ENCODING) {
+        return Ok(());
+    }
+    // reqwest reports a zero content length for HEAD responses, so read the header directly
+    let content_length = resp
+        .headers()
+        .get(CONTENT_LENGTH)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.parse::<u64>().ok());
+    match content_length {
+        Some(actual) if actual != image_size => {
+            if is_compressed_program(http_client, url).await? {
+                return Ok(());
+            }
+            Err(BonsolClientError::ImageSizeMismatch {
+                expected: image_size,
+                actual,
+            })
+        }
+        _ => Ok(()),
+    }
+}
+
+/// Whether the program at `url` is a gzip or zstd blob, only its first bytes are requested
+async fn is_compressed_program(http_client: &reqwest::Client, url: Url) -> Result<bool> {
+    let mut resp = http_client
+        .get(url)
+        .header(RANGE, format!("bytes=0-{}", ZSTD_MAGIC.len() - 1))
+        .send()
+        .await?
+        .error_for_status()?;
+    let mut head = vec![];
+    while head.len() < ZSTD_MAGIC.len() {
+        match resp.chunk().await? {
+            Some(chunk) => head.extend_from_slice(&chunk),
+            None => break,
+        }
+    }
+    Ok(head.starts_with(GZIP_MAGIC) || head.starts_with(ZSTD_MAGIC))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::{mock_client, TEST_IMAGE_ID};
+
+    #[test]
+    fn test_validate_url() {
+        assert!(validate_url("https://example.com/program").is_ok());
+        assert!(validate_url("http://localhost:8080/program").is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_build_rejects_invalid_urls() {
+        let client = mock_client();
+        for url in [
+            "ftp://example.com/program",
+            "file:///tmp/program",
+            "s3://bucket/program",
+            "example.com/program",
+            "",
+        ] {
+            let err = DeployBuilder::new(TEST_IMAGE_ID, 100, "simple", url)
+                .build(&client, &Pubkey::new_unique())
+                .await
+                .unwrap_err();
+            assert!(
+                matches!(err, BonsolClientError::InvalidDeploymentUrl(_)),
+                "{} was accepted",
+                url
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn test_build_checks_image_size() {
+        let mut server = mockito::Server::new_async().await;
+        let url = format!("{}/program", server.url());
+        let mock = server
+            .mock("HEAD", "/program")
+            .with_status(200)
+            .with_header("content-length", "64")
+            .expect(2)
+            .create_async()
+            .await;
+        let program = server
+            .mock("GET", "/program")
+            .with_status(200)
+            .with_body([0x7f; 64])
+            .create_async()
+            .await;
+        let client = mock_client();
+
+        let err = DeployBuilder::new(TEST_IMAGE_ID, 100, "simple", &url)
+            .check_image_size(true)
+            .build(&client, &Pubkey::new_unique())
+            .await
+            .unwrap_err();
+        assert!(matches!(
+            err,
+            BonsolClientError::ImageSizeMismatch {
+                expected: 100,
+                actual: 64
+            }
+        ));
+
+        let ixs = DeployBuilder::new(TEST_IMAGE_ID, 64, "simple", &url)
+            .check_image_size(true)
+            .build(&client, &Pubkey::new_unique())
+            .await
+            .unwrap();
+        assert_eq!(ixs.len(), 3);
+        mock.assert_async().await;
+        program.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn test_build_skips_size_check_for_compressed_programs() {
+        let mut server = mockito::Server::new_async().await;
+        let url = format!("{}/program", server.url());
+        for magic in [GZIP_MAGIC, ZSTD_MAGIC] {
+            server
+                .mock("HEAD", "/program")
+                .with_status(200)
+                .with_header("content-length", "20")
+                .create_async()
+                .await;
+            let program = server
+                .mock("GET", "/program")
+                .match_header("range", "bytes=0-3")
+                .with_status(206)
+                .with_body(magic)
+                .create_async()
+                .await;
+            let ixs = DeployBuilder::new(TEST_IMAGE_ID, 100, "simple", &url)
+                .check_image_size(true)
+                .build(&mock_client(), &Pubkey::new_unique())
+                .await
+                .unwrap();
+            assert_eq!(ixs.len(), 3);
+            program.assert_async().await;
+            server.reset();
+        }
+    }
+
+    #[tokio::test]
+    async fn test_size_check_uses_client_http_client() {
+        let mut server = mockito::Server::new_async().await;
+        let url = format!("{}/program", server.url());
+        let mock = server
+            .mock("HEAD", "/program")
+            .match_header("user-agent", "bonsol-test")
+            .with_status(200)
+            .with_header("content-length", "64")
+            .create_async()
+            .await;
+        let http_client = reqwest::Client::builder()
+            .user_agent("bonsol-test")
+            .build()
+            .unwrap();
+        let client = mock_client().with_http_client(http_client);
+
+        DeployBuilder::new(TEST_IMAGE_ID, 64, "simple", &url)
+            .check_image_size(true)
+            .build(&client, &Pubkey::new_unique())
+            .await
+            .unwrap();
+        mock.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn test_build_skips_size_check_by_default() {
+        let ixs = DeployBuilder::new(TEST_IMAGE_ID, 100, "simple", "https://example.invalid/p")
+            .compute_unit_limit(50_000)
+            .build(&mock_client(), &Pubkey::new_unique())
+            .await
+            .unwrap();
+        assert_eq!(ixs.len(), 3);
+    }
+}
@@ -0,0 +1,63 @@
+use bonsol_interface::prover_version::ProverVersion;
+use sha2::{Digest, Sha256};
+
+use crate::{BonsolClientError, Result};
+
+/// The output digest the bonsol program rebuilds from a status instruction before checking the
+/// proof, so auditors can reproduce it off chain from a completed execution. The journal is the
+/// input digest followed by the committed outputs, hashed under risc0's `risc0.Output` tag.
+pub fn compute_output_digest(
+    version: &ProverVersion,
+    input_digest: &[u8],
+    committed_outputs: &[u8],
+    assumption_digest: &[u8],
+) -> Result<[u8; 32]> {
+    // every supported version tags outputs the same way, only the verifying constants differ
+    if let ProverVersion::UnsupportedVersion = version {
+        return Err(BonsolClientError::UnsupportedProverVersion(
+            version.to_string(),
+        ));
+    }
+    let journal = Sha256::new()
+        .chain_update(input_digest)
+        .chain_update(committed_outputs)
+        .finalize();
+    let digest = Sha256::new()
+        .chain_update(Sha256::digest(b"risc0.Output"))
+        .chain_update(journal)
+        .chain_update(assumption_digest)
+        .chain_update(2u16.to_le_bytes())
+        .finalize();
+    Ok(digest.into())
+}
+
+#[cfg(test)]
+mod tests {
+    use bonsol_interface::prover_version::{VERSION_V1_0_1, VERSION_V1_2_1};
+
+    use super::*;
+
+    // the output digests the bonsol program's proof_handling tests compute for the same inputs
+    const HELLO_OUTPUT_DIGEST: &str =
+        "a942112e53a1f98c0be31401d43851f98e86a9069358ac06885f9b8f98f6c3d5";
+    const EMPTY_OUTPUT_DIGEST: &str =
+        "b684ac4f589e9cd4f53e022331e433f9f70c4f5062f78033a5fda86d0a7fa7f8";
+
+    #[test]
+    fn test_output_digest_matches_program() {
+        for version in [VERSION_V1_0_1, VERSION_V1_2_1] {
+            let digest = compute_output_digest(&version, &[2; 32], b"hello", &[0; 32]).unwrap();
+            assert_eq!(hex::encode(digest), HELLO_OUTPUT_DIGEST, "{}", version);
+            let digest = compute_output_digest(&version, &[7; 32], &[], &[9; 32]).unwrap();
+            assert_eq!(hex::encode(digest), EMPTY_OUTPUT_DIGEST, "{}", version);
+        }
+    }
+
+    #[test]
+    fn test_output_digest_unsupported_version() {
+        assert!(matches!(
+            compute_output_digest(&ProverVersion::UnsupportedVersion, &[0; 32], &[], &[0; 32]),
+            Err(BonsolClientError::UnsupportedProverVersion(_))
+        ));
+    }
+}
@@ -0,0 +1,97 @@
+pub use bonsol_interface::error::ChannelError;
+use bonsol_interface::error::ClientError as InstructionError;
+use solana_rpc_client_api::client_error::Error as RpcError;
+use solana_sdk::instruction::InstructionError as TransactionInstructionError;
+use solana_sdk::transaction::TransactionError;
+use thiserror::Error;
+
+/// Errors returned by `BonsolClient`. Converts into `anyhow::Error` for callers that do not need
+/// to match on specific failures.
+#[derive(Error, Debug)]
+pub enum BonsolClientError {
+    #[error("Deployment for image {0} not found")]
+    DeploymentNotFound(String),
+    #[error("Execution request {0} not found")]
+    ExecutionNotFound(String),
+    #[error("Claim for execution {0} not found")]
+    ClaimNotFound(String),
+    #[error("Invalid account data: {0}")]
+    InvalidAccountData(String),
+    #[error("Invalid exit code {0}")]
+    InvalidExitCode(u8),
+    #[error("Timeout")]
+    Timeout,
+    #[error("Expired")]
+    Expired,
+    #[error("Execution request {0} has already completed")]
+    ExecutionCompleted(String),
+    #[error("Execution expires after slot {max_block_height}, current slot is {current_slot}")]
+    ExecutionNotExpired {
+        max_block_height: u64,
+        current_slot: u64,
+    },
+    #[error("Execution request {0} has an active claim")]
+    ActiveClaim(String),
+    #[error("Prover version {0} is not supported")]
+    UnsupportedProverVersion(String),
+    #[error("Tip of {tip} lamports is below the minimum of {min_tip}")]
+    TipBelowMinimum { tip: u64, min_tip: u64 },
+    #[error("Rpc error: {0}")]
+    Rpc(Box<RpcError>),
+    #[error("Account subscription failed: {0}")]
+    Subscription(String),
+    #[error("Http error: {0}")]
+    Http(#[from] reqwest::Error),
+    #[error("Failed to download program: {0}")]
+    Download(String),
+    #[error("Invalid program: {0}")]
+    InvalidProgram(String),
+    #[error("Downloaded program has image id {actual} but {expected} was requested")]
+    MismatchedImageId { expected: String, actual: String },
+    #[error("Downloaded program has sha256 {actual} but the deployment stores {expected}")]
+    MismatchedSha256 { expected: String, actual: String },
+    #[error("Invalid deployment url {0}, only http and https are supported")]
+    InvalidDeploymentUrl(String),
+    #[error("Program is {actual} bytes at the deployment url but image_size is {expected}")]
+    ImageSizeMismatch { expected: u64, actual: u64 },
+    #[error("Signer is not the deployer of image {0}")]
+    NotDeploymentOwner(String),
+    #[error("Execution request {0} has no stored input digest")]
+    MissingInputDigest(String),
+    #[error("Failed to resolve inputs: {0}")]
+    InputResolution(String),
+    #[error("Invalid keypair: {0}")]
+    InvalidKeypair(String),
+    #[error("Invalid pda spec: {0}")]
+    InvalidPdaSpec(String),
+    #[error("Failed to build instruction: {0}")]
+    Instruction(#[from] InstructionError),
+    #[error("Failed to build transaction: {0}")]
+    InvalidTransaction(String),
+    #[error("Transaction failed: {0}")]
+    TransactionFailed(TransactionError),
+    #[error("Bonsol program failed: {channel_error}")]
+    ProgramFailed {
+        channel_error: ChannelError,
+        transaction_error: TransactionError,
+    },
+    #[error("Failed to confirm transaction")]
+    TransactionNotConfirmed,
+}
+
+impl From<RpcError> for BonsolClientError {
+    fn from(e: RpcError) -> Self {
+        BonsolClientError::Rpc(Box::new(e))
+    }
+}
+
+/// The `ChannelError` behind a custom instruction error. Other programs use the same code space,
+/// so only decode errors of instructions sent to the bonsol program.
+pub fn decode_channel_error(err: &TransactionError) -> Option<ChannelError> {
+    match err {
+        TransactionError::InstructionError(_, TransactionInstructionError::Custom(code)) => {
+            ChannelError::from_code(*code)
+        }
+        _ => None,
+    }
+}
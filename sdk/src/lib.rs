@@ -1,22 +1,38 @@
+use std::collections::HashMap;
+use std::io::Read;
+use std::mem::discriminant;
+use std::sync::Arc;
 use std::time::Duration;
 
-use anyhow::Result;
-
 use bytes::Bytes;
-use futures_util::TryFutureExt;
+use flate2::read::GzDecoder;
+use futures_util::future;
+use futures_util::stream::{self, Stream, StreamExt};
 use num_traits::FromPrimitive;
+use sha2::{Digest, Sha256};
+use solana_account_decoder::UiAccountEncoding;
+use solana_pubsub_client::nonblocking::pubsub_client::PubsubClient;
 
 use solana_rpc_client::nonblocking::rpc_client::RpcClient;
-use solana_rpc_client_api::config::RpcSendTransactionConfig;
+use solana_rpc_client_api::config::{RpcAccountInfoConfig, RpcSendTransactionConfig};
+use solana_rpc_client_api::request::MAX_GET_SIGNATURE_STATUSES_QUERY_ITEMS;
+use solana_rpc_client_api::response::RpcSimulateTransactionResult;
 use solana_sdk::account::Account;
 use solana_sdk::commitment_config::CommitmentConfig;
 use solana_sdk::compute_budget::ComputeBudgetInstruction;
+use solana_sdk::hash::Hash;
 use solana_sdk::instruction::Instruction;
 use solana_sdk::message::{v0, VersionedMessage};
 use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::Signature;
 use solana_sdk::signer::Signer;
-use solana_sdk::transaction::VersionedTransaction;
+use solana_sdk::signers::Signers;
+use solana_sdk::transaction::{TransactionError, VersionedTransaction};
+use solana_transaction_status::{
+    TransactionConfirmationStatus, TransactionStatus as RpcTransactionStatus,
+};
 
+use tokio::sync::{mpsc, oneshot};
 use tokio::time::Instant;
 
 use bonsol_interface::bonsol_schema::{root_as_deploy_v1, root_as_execution_request_v1};
@@ -25,15 +41,228 @@ pub use bonsol_interface::bonsol_schema::{
     StatusTypes,
 };
 use bonsol_interface::claim_state::ClaimStateHolder;
-use bonsol_interface::prover_version::ProverVersion;
+use bonsol_interface::prover_version::{ProverVersion, VERSION_V1_0_1, VERSION_V1_2_1};
+use bonsol_prover::image::Image;
+use bonsol_prover::input_resolver::{DefaultInputResolver, InputResolver, ProgramInput};
+use bonsol_prover::util::get_body_max_size_with_progress;
 pub use bonsol_interface::util::*;
 pub use bonsol_interface::{instructions, ID};
 use instructions::{CallbackConfig, ExecutionConfig, InputRef};
 
 pub use flatbuffers;
 
+pub mod deploy;
+pub mod digest;
+pub mod error;
+pub mod pda;
+pub mod retry;
+pub mod signer;
+pub use deploy::DeployBuilder;
+pub use digest::compute_output_digest;
+pub use error::{decode_channel_error, BonsolClientError, ChannelError};
+pub use pda::PdaSpec;
+
+pub type Result<T, E = BonsolClientError> = std::result::Result<T, E>;
+pub use retry::{JitterSource, RandomJitter, RetryPolicy};
+pub use signer::{keypair_from_base58, keypair_from_env};
+
+/// Compute unit limit used by `deploy_v1` and `execute_v1` when the caller does not provide one.
+pub const DEFAULT_COMPUTE_UNIT_LIMIT: u32 = 20_000;
+
+/// How often `wait_for_proof` checks the execution request.
+pub const DEFAULT_POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Largest program `download_program` will accept.
+pub const DEFAULT_MAX_PROGRAM_SIZE_MB: u32 = 10;
+
+/// Prover versions `execute_v1` can request, most preferred first.
+pub const SUPPORTED_PROVER_VERSIONS: &[ProverVersion] = &[VERSION_V1_2_1, VERSION_V1_0_1];
+
+#[derive(Debug, Clone)]
+pub struct DownloadProgramOptions {
+    pub max_size_mb: u32,
+    /// Checks that the downloaded elf hashes to the requested image id, only disable this if you
+    /// trust the deployment url
+    pub verify_image_id: bool,
+    /// Extra headers sent with the download, e.g. an Authorization header for a private gateway
+    pub headers: HashMap<String, String>,
+}
+
+impl Default for DownloadProgramOptions {
+    fn default() -> Self {
+        DownloadProgramOptions {
+            max_size_mb: DEFAULT_MAX_PROGRAM_SIZE_MB,
+            verify_image_id: true,
+            headers: HashMap::new(),
+        }
+    }
+}
+
+fn verify_program_sha256(expected: &[u8], program: &Bytes) -> Result<()> {
+    let actual = Sha256::digest(program);
+    if actual.as_slice() != expected {
+        return Err(BonsolClientError::MismatchedSha256 {
+            expected: hex::encode(expected),
+            actual: hex::encode(actual),
+        });
+    }
+    Ok(())
+}
+
+fn verify_program_image_id(image_id: &str, program: &Bytes) -> Result<()> {
+    let image = Image::from_bytes(program.clone())
+        .map_err(|e| BonsolClientError::InvalidProgram(e.to_string()))?;
+    if image.id != image_id {
+        return Err(BonsolClientError::MismatchedImageId {
+            expected: image_id.to_string(),
+            actual: image.id,
+        });
+    }
+    Ok(())
+}
+
+const GZIP_MAGIC: &[u8] = &[0x1f, 0x8b];
+const ZSTD_MAGIC: &[u8] = &[0x28, 0xb5, 0x2f, 0xfd];
+
+/// Decompresses a program deployed as a gzip or zstd blob, recognised by its magic bytes. Anything
+/// else is returned as is. The decompressed program must be `image_size` bytes and is never read
+/// past `max_size`.
+fn decompress_program(program: Bytes, image_size: u64, max_size: usize) -> Result<Bytes> {
+    let decoder: Box<dyn Read + '_> = if program.starts_with(GZIP_MAGIC) {
+        Box::new(GzDecoder::new(program.as_ref()))
+    } else if program.starts_with(ZSTD_MAGIC) {
+        let decoder = zstd::stream::read::Decoder::new(program.as_ref())
+            .map_err(|e| BonsolClientError::InvalidProgram(e.to_string()))?;
+        Box::new(decoder)
+    } else {
+        return Ok(program);
+    };
+    let mut decompressed = vec![];
+    decoder
+        .take(max_size as u64 + 1)
+        .read_to_end(&mut decompressed)
+        .map_err(|e| BonsolClientError::InvalidProgram(e.to_string()))?;
+    if decompressed.len() > max_size {
+        return Err(BonsolClientError::Download(format!(
+            "Max size exceeded when decompressing, limit is {} bytes",
+            max_size
+        )));
+    }
+    if decompressed.len() as u64 != image_size {
+        return Err(BonsolClientError::ImageSizeMismatch {
+            expected: image_size,
+            actual: decompressed.len() as u64,
+        });
+    }
+    Ok(decompressed.into())
+}
+
+async fn download_program_from_url(
+    http_client: &reqwest::Client,
+    url: &str,
+    max_size_mb: u32,
+    headers: &HashMap<String, String>,
+    progress: impl FnMut(usize),
+) -> Result<Bytes> {
+    let mut req = http_client.get(url);
+    for (name, value) in headers {
+        req = req.header(name, value);
+    }
+    let resp = req.send().await?.error_for_status()?;
+    get_body_max_size_with_progress(
+        resp.bytes_stream(),
+        max_size_mb as usize * 1024 * 1024,
+        progress,
+    )
+    .await
+    .map_err(|e| BonsolClientError::Download(e.to_string()))
+}
+
+/// Everything needed to build a single deployment in `BonsolClient::deploy_many`
+#[derive(Debug, Clone)]
+pub struct DeploySpec {
+    pub image_id: String,
+    pub image_size: u64,
+    pub program_name: String,
+    pub url: String,
+    pub inputs: Vec<ProgramInputType>,
+    pub compute_unit_limit: Option<u32>,
+    /// sha256 of the program elf, stored so downloads can be checked before the image id
+    pub sha256: Option<[u8; 32]>,
+}
+
+impl DeploySpec {
+    fn matches(&self, deployment: &DeployV1T) -> bool {
+        deployment.url.as_deref() == Some(self.url.as_str()) && self.matches_program(deployment)
+    }
+
+    /// Same size and name, the url is the only part of a deployment that can be updated
+    fn matches_program(&self, deployment: &DeployV1T) -> bool {
+        deployment.size_ == self.image_size
+            && deployment.program_name.as_deref() == Some(self.program_name.as_str())
+    }
+}
+
+/// What `BonsolClient::ensure_deployed` found for an image
+#[derive(Debug)]
+pub enum DeployOutcome {
+    /// The image is deployed with the same url, size and name, nothing needs to be sent
+    AlreadyDeployed,
+    /// The image is deployed by the signer with a different url, the instructions point it at the
+    /// new url, see `BonsolClient::update_deployment_url`
+    UpdateUrl(Vec<Instruction>),
+    /// The image is deployed with a different size or name, or with a different url by another
+    /// deployer. Deployments cannot be replaced, so the existing deployment is returned instead of
+    /// instructions that would fail on chain.
+    NeedsUpdate(DeployV1T),
+    /// The image is not deployed yet
+    Deploy(Vec<Instruction>),
+}
+
+/// Prefixes the instruction with the compute budget instructions
+fn with_compute_budget(
+    instruction: Instruction,
+    compute_unit_limit: Option<u32>,
+    compute_price_val: u64,
+) -> Vec<Instruction> {
+    let compute = ComputeBudgetInstruction::set_compute_unit_limit(
+        compute_unit_limit.unwrap_or(DEFAULT_COMPUTE_UNIT_LIMIT),
+    );
+    let compute_price = ComputeBudgetInstruction::set_compute_unit_price(compute_price_val);
+    vec![compute, compute_price, instruction]
+}
+
+/// Percentile of recent prioritization fees used by `get_fees` unless configured otherwise.
+pub const DEFAULT_FEE_PERCENTILE: f32 = 50.0;
+
+/// Prioritization fee used when the rpc node returns no recent samples.
+pub const FALLBACK_PRIORITIZATION_FEE: u64 = 5;
+
+/// Picks the `percentile` (0-100) fee out of the recent fee samples, the samples do not need to
+/// be sorted. Returns `FALLBACK_PRIORITIZATION_FEE` when there are no samples.
+pub fn fee_percentile(fees: &[u64], percentile: f32) -> u64 {
+    if fees.is_empty() {
+        return FALLBACK_PRIORITIZATION_FEE;
+    }
+    let mut sorted = fees.to_vec();
+    sorted.sort_unstable();
+    let percentile = if percentile.is_nan() {
+        DEFAULT_FEE_PERCENTILE
+    } else {
+        percentile.clamp(0.0, 100.0)
+    };
+    let index = ((percentile / 100.0) * (sorted.len() - 1) as f32).round() as usize;
+    sorted[index.min(sorted.len() - 1)]
+}
+
 pub struct BonsolClient {
     rpc_client: RpcClient,
+    fee_percentile: f32,
+    commitment: CommitmentConfig,
+    min_tip: Option<u64>,
+    ws_url: Option<String>,
+    http_client: reqwest::Client,
+    jitter: Arc<dyn JitterSource + Send + Sync>,
 }
 
 pub enum ExecutionAccountStatus {
@@ -41,35 +270,235 @@ pub enum ExecutionAccountStatus {
     Pending(ExecutionRequestV1T),
 }
 
+impl ExecutionAccountStatus {
+    /// An execution account holds the request until it completes, then just the exit code
+    pub fn from_account_data(data: &[u8]) -> Result<Self> {
+        if data.len() == 1 {
+            let ec =
+                ExitCode::from_u8(data[0]).ok_or(BonsolClientError::InvalidExitCode(data[0]))?;
+            return Ok(ExecutionAccountStatus::Completed(ec));
+        }
+        let er = root_as_execution_request_v1(data).map_err(|_| {
+            BonsolClientError::InvalidAccountData("Invalid execution request account".to_string())
+        })?;
+        Ok(ExecutionAccountStatus::Pending(er.unpack()))
+    }
+}
+
+/// Where a sent transaction stands, as reported by `BonsolClient::statuses`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TransactionStatus {
+    Processed,
+    Confirmed,
+    Finalized,
+    /// The transaction landed but its instructions failed
+    Failed(TransactionError),
+}
+
+impl From<RpcTransactionStatus> for TransactionStatus {
+    fn from(status: RpcTransactionStatus) -> Self {
+        if let Some(err) = status.err {
+            return TransactionStatus::Failed(err);
+        }
+        match status.confirmation_status() {
+            TransactionConfirmationStatus::Processed => TransactionStatus::Processed,
+            TransactionConfirmationStatus::Confirmed => TransactionStatus::Confirmed,
+            TransactionConfirmationStatus::Finalized => TransactionStatus::Finalized,
+        }
+    }
+}
+
+/// Sha256 over the data of every input in order, as committed to by `input_digest` in an
+/// execution request. Fails if any input is still unresolved.
+pub fn input_digest(inputs: &[ProgramInput]) -> Result<Vec<u8>> {
+    let mut hash = Sha256::new();
+    for input in inputs {
+        match input {
+            ProgramInput::Resolved(ri) => hash.update(&ri.data),
+            _ => {
+                let msg = format!("input {} is not resolved", input.index());
+                return Err(BonsolClientError::InputResolution(msg));
+            }
+        }
+    }
+    Ok(hash.finalize().to_vec())
+}
+
+/// The websocket url a solana rpc serves next to `rpc_url`, on the next port when one is given
+fn websocket_url(rpc_url: &str) -> Result<String> {
+    let mut url = reqwest::Url::parse(rpc_url)
+        .map_err(|e| BonsolClientError::Subscription(format!("{}: {}", rpc_url, e)))?;
+    let scheme = match url.scheme() {
+        "http" => "ws",
+        "https" => "wss",
+        scheme => {
+            return Err(BonsolClientError::Subscription(format!(
+                "no websocket for {} urls",
+                scheme
+            )))
+        }
+    };
+    let _ = url.set_scheme(scheme);
+    if let Some(port) = url.port() {
+        let _ = url.set_port(Some(port.saturating_add(1)));
+    }
+    Ok(url.to_string())
+}
+
+/// Ends `statuses` after the first `Completed` status or error
+fn until_terminal<S>(statuses: S) -> impl Stream<Item = Result<ExecutionAccountStatus>>
+where
+    S: Stream<Item = Result<ExecutionAccountStatus>>,
+{
+    statuses.scan(false, |done, status| {
+        if *done {
+            return future::ready(None);
+        }
+        *done = !matches!(status, Ok(ExecutionAccountStatus::Pending(_)));
+        future::ready(Some(status))
+    })
+}
+
+/// Unpacks each deployment account update, skipping data that is not a deployment
+fn deployment_changes<S>(updates: S) -> impl Stream<Item = DeployV1T>
+where
+    S: Stream<Item = Vec<u8>>,
+{
+    updates.filter_map(|data| future::ready(root_as_deploy_v1(&data).ok().map(|d| d.unpack())))
+}
+
+/// The error for a transaction that failed on chain, carrying the decoded `ChannelError` when the
+/// failing instruction was sent to the bonsol program
+fn transaction_failed(tx: &VersionedTransaction, error: TransactionError) -> BonsolClientError {
+    let failed_program = match &error {
+        TransactionError::InstructionError(index, _) => tx
+            .message
+            .instructions()
+            .get(*index as usize)
+            .map(|ix| ix.program_id(tx.message.static_account_keys())),
+        _ => None,
+    };
+    match decode_channel_error(&error) {
+        Some(channel_error) if failed_program == Some(&ID) => BonsolClientError::ProgramFailed {
+            channel_error,
+            transaction_error: error,
+        },
+        _ => BonsolClientError::TransactionFailed(error),
+    }
+}
+
+/// Compiles the instructions into a v0 transaction paid for by `fee_payer` and signed by
+/// `signers`, which must be exactly the signers the instructions require.
+fn sign_txn<T: Signers + ?Sized>(
+    fee_payer: &Pubkey,
+    signers: &T,
+    instructions: &[Instruction],
+    blockhash: Hash,
+) -> Result<VersionedTransaction> {
+    let message = v0::Message::try_compile(fee_payer, instructions, &[], blockhash)
+        .map_err(|e| BonsolClientError::InvalidTransaction(e.to_string()))?;
+    VersionedTransaction::try_new(VersionedMessage::V0(message), signers)
+        .map_err(|e| BonsolClientError::InvalidTransaction(e.to_string()))
+}
+
 impl BonsolClient {
     pub fn new(rpc_url: String) -> Self {
         BonsolClient {
             rpc_client: RpcClient::new(rpc_url),
+            fee_percentile: DEFAULT_FEE_PERCENTILE,
+            commitment: CommitmentConfig::confirmed(),
+            min_tip: None,
+            ws_url: None,
+            http_client: reqwest::Client::new(),
+            jitter: Arc::new(RandomJitter),
         }
     }
 
+    /// Sets the percentile (0-100) of recent prioritization fees used when pricing transactions
+    pub fn with_fee_percentile(mut self, fee_percentile: f32) -> Self {
+        self.fee_percentile = fee_percentile;
+        self
+    }
+
+    /// Sets the commitment used for account reads, slots, blockhashes and transaction
+    /// confirmation, defaults to `confirmed`
+    pub fn with_commitment(mut self, commitment: CommitmentConfig) -> Self {
+        self.commitment = commitment;
+        self
+    }
+
+    /// Makes `execute_v1` reject tips below `min_tip` lamports, which no prover is likely to claim
+    pub fn with_min_tip(mut self, min_tip: u64) -> Self {
+        self.min_tip = Some(min_tip);
+        self
+    }
+
+    /// Sets the websocket url used by subscriptions, derived from the rpc url by default
+    pub fn with_ws_url(mut self, ws_url: String) -> Self {
+        self.ws_url = Some(ws_url);
+        self
+    }
+
+    /// Sets the http client used to download programs and resolve url inputs, e.g. one
+    /// configured with a proxy or timeouts
+    pub fn with_http_client(mut self, http_client: reqwest::Client) -> Self {
+        self.http_client = http_client;
+        self
+    }
+
+    /// Sets where the random part of retry delays comes from, e.g. a seeded or fixed source to make
+    /// retries reproducible in tests
+    pub fn with_jitter_source(mut self, jitter: Arc<dyn JitterSource + Send + Sync>) -> Self {
+        self.jitter = jitter;
+        self
+    }
+
+    /// See `SUPPORTED_PROVER_VERSIONS`
+    pub fn supported_prover_versions() -> &'static [ProverVersion] {
+        SUPPORTED_PROVER_VERSIONS
+    }
+
+    /// Resolves `requested` to the supported version of the same release, or to the most
+    /// preferred supported version when nothing is requested
+    pub fn negotiate_prover_version(requested: Option<ProverVersion>) -> Result<ProverVersion> {
+        let requested = match requested {
+            Some(requested) => requested,
+            None => return Ok(SUPPORTED_PROVER_VERSIONS[0].clone()),
+        };
+        SUPPORTED_PROVER_VERSIONS
+            .iter()
+            .find(|v| discriminant(*v) == discriminant(&requested))
+            .cloned()
+            .ok_or_else(|| BonsolClientError::UnsupportedProverVersion(requested.to_string()))
+    }
+
     pub async fn get_current_slot(&self) -> Result<u64> {
-        self.rpc_client
-            .get_slot()
-            .map_err(|_| anyhow::anyhow!("Failed to get slot"))
-            .await
+        Ok(self.rpc_client.get_slot_with_commitment(self.commitment).await?)
     }
 
     pub fn with_rpc_client(rpc_client: RpcClient) -> Self {
-        BonsolClient { rpc_client }
+        BonsolClient {
+            rpc_client,
+            fee_percentile: DEFAULT_FEE_PERCENTILE,
+            commitment: CommitmentConfig::confirmed(),
+            min_tip: None,
+            ws_url: None,
+            http_client: reqwest::Client::new(),
+            jitter: Arc::new(RandomJitter),
+        }
     }
 
     pub async fn get_deployment_v1(&self, image_id: &str) -> Result<DeployV1T> {
         let (deployment_account, _) = deployment_address(image_id);
         let account = self
             .rpc_client
-            .get_account_with_commitment(&deployment_account, CommitmentConfig::confirmed())
-            .await
-            .map_err(|e| anyhow::anyhow!("Failed to get account: {:?}", e))?
+            .get_account_with_commitment(&deployment_account, self.commitment)
+            .await?
             .value
-            .ok_or(anyhow::anyhow!("Invalid deployment account"))?;
-        let deployment = root_as_deploy_v1(&account.data)
-            .map_err(|_| anyhow::anyhow!("Invalid deployment account"))?;
+            .ok_or_else(|| BonsolClientError::DeploymentNotFound(image_id.to_string()))?;
+        let deployment = root_as_deploy_v1(&account.data).map_err(|_| {
+            BonsolClientError::InvalidAccountData("Invalid deployment account".to_string())
+        })?;
         Ok(deployment.unpack())
     }
 
@@ -78,22 +507,73 @@ impl BonsolClient {
         requester_pubkey: &Pubkey,
         execution_id: &str,
     ) -> Result<ExecutionAccountStatus> {
+        self.try_get_execution_request_v1(requester_pubkey, execution_id)
+            .await?
+            .ok_or_else(|| BonsolClientError::ExecutionNotFound(execution_id.to_string()))
+    }
+
+    /// Same as `get_execution_request_v1` but returns `Ok(None)` when the account does not exist
+    /// yet, so pollers can tell a request that is still being created from a malformed one.
+    pub async fn try_get_execution_request_v1(
+        &self,
+        requester_pubkey: &Pubkey,
+        execution_id: &str,
+    ) -> Result<Option<ExecutionAccountStatus>> {
         let (er, _) = execution_address(requester_pubkey, execution_id.as_bytes());
-        let account = self
+        let account = match self
             .rpc_client
-            .get_account_with_commitment(&er, CommitmentConfig::confirmed())
-            .await
-            .map_err(|e| anyhow::anyhow!("Failed to get account: {:?}", e))?
+            .get_account_with_commitment(&er, self.commitment)
+            .await?
             .value
-            .ok_or(anyhow::anyhow!("Invalid execution request account"))?;
-        if account.data.len() == 1 {
-            let ec =
-                ExitCode::from_u8(account.data[0]).ok_or(anyhow::anyhow!("Invalid exit code"))?;
-            return Ok(ExecutionAccountStatus::Completed(ec));
+        {
+            Some(account) => account,
+            None => return Ok(None),
+        };
+        ExecutionAccountStatus::from_account_data(&account.data).map(Some)
+    }
+
+    /// Resolves and hashes `inputs` the way the requester does when `verify_input_hash` is set and
+    /// compares the digest with the one stored for the execution, so a prover's claimed inputs can
+    /// be audited. Private inputs cannot be resolved here. The digest is only stored until the
+    /// execution completes, after that this fails with `MissingInputDigest`.
+    pub async fn verify_input_digest(
+        &self,
+        requester_pubkey: &Pubkey,
+        execution_id: &str,
+        inputs: Vec<InputT>,
+    ) -> Result<bool> {
+        let stored = self
+            .stored_input_digest(requester_pubkey, execution_id)
+            .await?;
+        let rpc_client = Arc::new(RpcClient::new_with_commitment(
+            self.rpc_client.url(),
+            self.commitment,
+        ));
+        let resolver = DefaultInputResolver::new(Arc::new(self.http_client.clone()), rpc_client);
+        let resolved = resolver
+            .resolve_public_inputs(inputs)
+            .await
+            .map_err(|e| BonsolClientError::InputResolution(e.to_string()))?;
+        Ok(input_digest(&resolved)? == stored)
+    }
+
+    async fn stored_input_digest(
+        &self,
+        requester_pubkey: &Pubkey,
+        execution_id: &str,
+    ) -> Result<Vec<u8>> {
+        let missing = || BonsolClientError::MissingInputDigest(execution_id.to_string());
+        match self
+            .get_execution_request_v1(requester_pubkey, execution_id)
+            .await?
+        {
+            ExecutionAccountStatus::Pending(er) => er
+                .input_digest
+                .filter(|d| !d.is_empty())
+                .ok_or_else(missing),
+            // the program only keeps the exit code of a completed execution
+            ExecutionAccountStatus::Completed(_) => Err(missing()),
         }
-        let er = root_as_execution_request_v1(&account.data)
-            .map_err(|_| anyhow::anyhow!("Invalid execution request account"))?;
-        Ok(ExecutionAccountStatus::Pending(er.unpack()))
     }
 
     pub async fn get_claim_state_v1<'a>(
@@ -105,34 +585,57 @@ impl BonsolClient {
         let (eca, _) = execution_claim_address(exad.as_ref());
         let account = self
             .rpc_client
-            .get_account_with_commitment(&eca, CommitmentConfig::confirmed())
-            .await
-            .map_err(|e| anyhow::anyhow!("Failed to get account: {:?}", e))?
+            .get_account_with_commitment(&eca, self.commitment)
+            .await?
             .value
-            .ok_or(anyhow::anyhow!("Invalid claim account"))?;
+            .ok_or_else(|| BonsolClientError::ClaimNotFound(execution_id.to_string()))?;
         Ok(ClaimStateHolder::new(account.data))
     }
 
     pub async fn download_program(&self, image_id: &str) -> Result<Bytes> {
-        let deployment = self.get_deployment_v1(image_id).await?;
-        let url = deployment
-            .url
-            .ok_or(anyhow::anyhow!("Invalid deployment"))?;
-        let resp = reqwest::get(url)
-            .await
-            .map_err(|e| anyhow::anyhow!("Failed to download program: {:?}", e))?;
-        resp.bytes()
+        self.download_program_with_opts(image_id, DownloadProgramOptions::default(), |_| {})
             .await
-            .map_err(|e| anyhow::anyhow!("Failed to download program: {:?}", e))
+    }
+
+    /// Streams the deployed program, failing once more than `opts.max_size_mb` has been received.
+    /// `progress` is called with the total number of bytes received after every chunk. Programs
+    /// deployed gzip or zstd compressed are decompressed, also up to `opts.max_size_mb`. When the
+    /// deployment stores a sha256 it is checked first, as it is much cheaper than the image id.
+    pub async fn download_program_with_opts(
+        &self,
+        image_id: &str,
+        opts: DownloadProgramOptions,
+        progress: impl FnMut(usize),
+    ) -> Result<Bytes> {
+        let deployment = self.get_deployment_v1(image_id).await?;
+        let url = deployment.url.ok_or_else(|| {
+            BonsolClientError::InvalidAccountData("Deployment has no url".to_string())
+        })?;
+        let bytes = download_program_from_url(
+            &self.http_client,
+            &url,
+            opts.max_size_mb,
+            &opts.headers,
+            progress,
+        )
+        .await?;
+        let max_size = opts.max_size_mb as usize * 1024 * 1024;
+        let bytes = decompress_program(bytes, deployment.size_, max_size)?;
+        if let Some(sha256) = &deployment.sha256 {
+            verify_program_sha256(sha256, &bytes)?;
+        }
+        if opts.verify_image_id {
+            verify_program_image_id(image_id, &bytes)?;
+        }
+        Ok(bytes)
     }
 
     pub async fn get_deployment(&self, image_id: &str) -> Result<Option<Account>> {
         let (deployment_account, _) = deployment_address(image_id);
         let account = self
             .rpc_client
-            .get_account_with_commitment(&deployment_account, CommitmentConfig::confirmed())
-            .await
-            .map_err(|e| anyhow::anyhow!("Failed to get account: {:?}", e))?;
+            .get_account_with_commitment(&deployment_account, self.commitment)
+            .await?;
         Ok(account.value)
     }
 
@@ -142,13 +645,14 @@ impl BonsolClient {
             .rpc_client
             .get_recent_prioritization_fees(&fee_accounts)
             .await?;
-        Ok(if compute_fees.is_empty() {
-            5
-        } else {
-            compute_fees[0].prioritization_fee
-        })
+        let fees: Vec<u64> = compute_fees
+            .iter()
+            .map(|f| f.prioritization_fee)
+            .collect();
+        Ok(fee_percentile(&fees, self.fee_percentile))
     }
 
+    #[allow(clippy::too_many_arguments)]
     pub async fn deploy_v1(
         &self,
         signer: &Pubkey,
@@ -157,13 +661,130 @@ impl BonsolClient {
         program_name: &str,
         url: &str,
         inputs: Vec<ProgramInputType>,
+        sha256: Option<[u8; 32]>,
+        compute_unit_limit: Option<u32>,
+    ) -> Result<Vec<Instruction>> {
+        let compute_price_val = self.get_fees(signer).await?;
+        let instruction = instructions::deploy_v1(
+            signer,
+            image_id,
+            image_size,
+            program_name,
+            url,
+            inputs,
+            sha256.as_ref().map(|s| s.as_slice()),
+        )?;
+        Ok(with_compute_budget(
+            instruction,
+            compute_unit_limit,
+            compute_price_val,
+        ))
+    }
+
+    /// Checks the deployment account before building deploy instructions, so deploying an image
+    /// that already exists does not cost a failed transaction
+    pub async fn ensure_deployed(
+        &self,
+        signer: &Pubkey,
+        deployment: DeploySpec,
+    ) -> Result<DeployOutcome> {
+        match self.get_deployment_v1(&deployment.image_id).await {
+            Ok(existing) if deployment.matches(&existing) => Ok(DeployOutcome::AlreadyDeployed),
+            Ok(existing)
+                if deployment.matches_program(&existing)
+                    && existing.owner.as_deref() == Some(signer.as_ref()) =>
+            {
+                let instructions = self
+                    .update_url_instructions(signer, existing, &deployment.url)
+                    .await?;
+                Ok(DeployOutcome::UpdateUrl(instructions))
+            }
+            Ok(existing) => Ok(DeployOutcome::NeedsUpdate(existing)),
+            Err(BonsolClientError::DeploymentNotFound(_)) => {
+                let instructions = self
+                    .deploy_v1(
+                        signer,
+                        &deployment.image_id,
+                        deployment.image_size,
+                        &deployment.program_name,
+                        &deployment.url,
+                        deployment.inputs,
+                        deployment.sha256,
+                        deployment.compute_unit_limit,
+                    )
+                    .await?;
+                Ok(DeployOutcome::Deploy(instructions))
+            }
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Builds the instructions that point an existing deployment at `new_url`, keeping its image
+    /// id, size, name and inputs. Only the original deployer can sign them.
+    pub async fn update_deployment_url(
+        &self,
+        signer: &Pubkey,
+        image_id: &str,
+        new_url: &str,
     ) -> Result<Vec<Instruction>> {
+        let deployment = self.get_deployment_v1(image_id).await?;
+        self.update_url_instructions(signer, deployment, new_url).await
+    }
+
+    async fn update_url_instructions(
+        &self,
+        signer: &Pubkey,
+        deployment: DeployV1T,
+        new_url: &str,
+    ) -> Result<Vec<Instruction>> {
+        deploy::validate_url(new_url)?;
+        let image_id = deployment.image_id.as_deref().unwrap_or_default();
+        if deployment.owner.as_deref() != Some(signer.as_ref()) {
+            return Err(BonsolClientError::NotDeploymentOwner(image_id.to_string()));
+        }
+        let compute_price_val = self.get_fees(signer).await?;
+        let instruction = instructions::update_deploy_v1(
+            signer,
+            image_id,
+            deployment.size_,
+            deployment.program_name.as_deref().unwrap_or_default(),
+            new_url,
+            deployment.inputs.unwrap_or_default(),
+            deployment.sha256.as_deref(),
+        )?;
+        Ok(with_compute_budget(instruction, None, compute_price_val))
+    }
+
+    /// Builds the deployment instructions for every spec, prioritization fees are fetched once and
+    /// shared across the whole batch. Each entry in the result is a separate transaction.
+    pub async fn deploy_many(
+        &self,
+        signer: &Pubkey,
+        deployments: Vec<DeploySpec>,
+    ) -> Result<Vec<Vec<Instruction>>> {
+        if deployments.is_empty() {
+            return Ok(vec![]);
+        }
         let compute_price_val = self.get_fees(signer).await?;
-        let instruction =
-            instructions::deploy_v1(signer, image_id, image_size, program_name, url, inputs)?;
-        let compute = ComputeBudgetInstruction::set_compute_unit_limit(20_000);
-        let compute_price = ComputeBudgetInstruction::set_compute_unit_price(compute_price_val);
-        Ok(vec![compute, compute_price, instruction])
+        deployments
+            .into_iter()
+            .map(|d| {
+                let instruction = instructions::deploy_v1(
+                    signer,
+                    &d.image_id,
+                    d.image_size,
+                    &d.program_name,
+                    &d.url,
+                    d.inputs,
+                    d.sha256.as_ref().map(|s| s.as_slice()),
+                )?;
+                Ok(with_compute_budget(
+                    instruction,
+                    d.compute_unit_limit,
+                    compute_price_val,
+                ))
+            })
+            .collect()
     }
 
     pub async fn execute_v1<'a>(
@@ -177,20 +798,64 @@ impl BonsolClient {
         config: ExecutionConfig<'a>,
         callback: Option<CallbackConfig>,
         prover_version: Option<ProverVersion>,
+        compute_unit_limit: Option<u32>,
     ) -> Result<Vec<Instruction>> {
-        let compute_price_val = self.get_fees(signer).await?;
+        self.execute_v1_with_payer(
+            signer,
+            signer,
+            image_id,
+            execution_id,
+            inputs,
+            tip,
+            expiration,
+            config,
+            callback,
+            prover_version,
+            compute_unit_limit,
+        )
+        .await
+    }
+
+    /// Same as `execute_v1` but the execution account's rent is paid by `payer`, for sponsored
+    /// setups where a relayer pays on behalf of the requester. The execution account is still
+    /// derived from the requester and both must sign, see `send_txn_with_payer`.
+    #[allow(clippy::too_many_arguments)]
+    pub async fn execute_v1_with_payer<'a>(
+        &self,
+        requester: &Pubkey,
+        payer: &Pubkey,
+        image_id: &str,
+        execution_id: &str,
+        inputs: Vec<InputRef<'a>>,
+        tip: u64,
+        expiration: u64,
+        config: ExecutionConfig<'a>,
+        callback: Option<CallbackConfig>,
+        prover_version: Option<ProverVersion>,
+        compute_unit_limit: Option<u32>,
+    ) -> Result<Vec<Instruction>> {
+        if let Some(min_tip) = self.min_tip {
+            if tip < min_tip {
+                return Err(BonsolClientError::TipBelowMinimum { tip, min_tip });
+            }
+        }
+        let compute_price_val = self.get_fees(payer).await?;
 
+        // no version leaves the choice to the program default
         let fbs_version_or_none = match prover_version {
             Some(version) => {
-                let fbs_version = version.try_into().expect("Unknown prover version");
+                let version = Self::negotiate_prover_version(Some(version))?;
+                let fbs_version = version.clone().try_into().map_err(|_| {
+                    BonsolClientError::UnsupportedProverVersion(version.to_string())
+                })?;
                 Some(fbs_version)
             }
             None => None,
         };
 
         let instruction = instructions::execute_v1(
-            signer,
-            signer,
+            requester,
+            payer,
             image_id,
             execution_id,
             inputs,
@@ -200,16 +865,100 @@ impl BonsolClient {
             callback,
             fbs_version_or_none,
         )?;
-        let compute = ComputeBudgetInstruction::set_compute_unit_limit(20_000);
-        let compute_price = ComputeBudgetInstruction::set_compute_unit_price(compute_price_val);
-        Ok(vec![compute, compute_price, instruction])
+        Ok(with_compute_budget(
+            instruction,
+            compute_unit_limit,
+            compute_price_val,
+        ))
+    }
+
+    /// Builds the instructions that refund the tip of an expired execution request to the
+    /// requester, or the request's refund destination, and close its account. Fails if the request has not expired yet or a prover
+    /// still holds a claim whose block commitment has not passed.
+    pub async fn reclaim_expired(
+        &self,
+        signer: &Pubkey,
+        execution_id: &str,
+    ) -> Result<Vec<Instruction>> {
+        let req = match self.get_execution_request_v1(signer, execution_id).await? {
+            ExecutionAccountStatus::Pending(req) => req,
+            ExecutionAccountStatus::Completed(_) => {
+                return Err(BonsolClientError::ExecutionCompleted(execution_id.to_string()));
+            }
+        };
+        let max_block_height = req.max_block_height;
+        let current_slot = self.get_current_slot().await?;
+        if current_slot <= max_block_height {
+            return Err(BonsolClientError::ExecutionNotExpired {
+                max_block_height,
+                current_slot,
+            });
+        }
+        match self.get_claim_state_v1(signer, execution_id).await {
+            Ok(holder) => {
+                let claim = holder.claim().map_err(|_| {
+                    BonsolClientError::InvalidAccountData("Invalid claim account".to_string())
+                })?;
+                if claim.block_commitment >= current_slot {
+                    return Err(BonsolClientError::ActiveClaim(execution_id.to_string()));
+                }
+            }
+            Err(BonsolClientError::ClaimNotFound(_)) => {}
+            Err(e) => return Err(e),
+        }
+        let compute_price_val = self.get_fees(signer).await?;
+        let refund_destination = req
+            .refund_destination
+            .map(|rd| Pubkey::try_from(rd.as_slice()))
+            .transpose()
+            .map_err(|_| {
+                BonsolClientError::InvalidAccountData("Invalid refund destination".to_string())
+            })?;
+        let instruction = instructions::reclaim_expired_v1(
+            signer,
+            execution_id,
+            refund_destination.as_ref(),
+        )?;
+        Ok(with_compute_budget(instruction, None, compute_price_val))
+    }
+
+    /// Compiles the instructions into a v0 transaction against the latest blockhash, this is the
+    /// same transaction shape that `send_txn` submits.
+    async fn compile_txn(
+        &self,
+        signer: &impl Signer,
+        instructions: &[Instruction],
+    ) -> Result<(VersionedTransaction, Hash)> {
+        let blockhash = self.latest_blockhash().await?;
+        let tx = sign_txn(&signer.pubkey(), &[signer], instructions, blockhash)?;
+        Ok((tx, blockhash))
+    }
+
+    async fn latest_blockhash(&self) -> Result<Hash> {
+        let (blockhash, _) = self
+            .rpc_client
+            .get_latest_blockhash_with_commitment(self.commitment)
+            .await?;
+        Ok(blockhash)
+    }
+
+    /// Simulates the transaction instead of sending it, use this to size compute budgets and
+    /// catch account errors before paying for the transaction.
+    pub async fn simulate_txn(
+        &self,
+        signer: impl Signer,
+        instructions: Vec<Instruction>,
+    ) -> Result<RpcSimulateTransactionResult> {
+        let (tx, _) = self.compile_txn(&signer, &instructions).await?;
+        let result = self.rpc_client.simulate_transaction(&tx).await?;
+        Ok(result.value)
     }
 
     pub async fn send_txn_standard(
         &self,
         signer: impl Signer,
         instructions: Vec<Instruction>,
-    ) -> Result<()> {
+    ) -> Result<Signature> {
         self.send_txn(signer, instructions, false, 1, 5).await
     }
 
@@ -220,13 +969,78 @@ impl BonsolClient {
         skip_preflight: bool,
         retry_timeout: u64,
         retry_count: usize,
-    ) -> Result<()> {
+    ) -> Result<Signature> {
+        self.send_txn_with_retry_policy(
+            signer,
+            instructions,
+            skip_preflight,
+            retry_timeout,
+            retry_count,
+            RetryPolicy::default(),
+        )
+        .await
+    }
+
+    /// Same as `send_txn` but waits between status checks, and before resending a transaction whose
+    /// blockhash expired, according to the provided `RetryPolicy` instead of a flat interval.
+    pub async fn send_txn_with_retry_policy(
+        &self,
+        signer: impl Signer,
+        instructions: Vec<Instruction>,
+        skip_preflight: bool,
+        retry_timeout: u64,
+        retry_count: usize,
+        retry_policy: RetryPolicy,
+    ) -> Result<Signature> {
+        let sign_tx = |blockhash| sign_txn(&signer.pubkey(), &[&signer], &instructions, blockhash);
+        self.send_signed_txn(
+            sign_tx,
+            skip_preflight,
+            retry_timeout,
+            retry_count,
+            retry_policy,
+        )
+        .await
+    }
+
+    /// Sends a transaction whose fees are paid by `fee_payer` rather than `signer`, for sponsored
+    /// setups where a relayer pays for the requester. Both keys sign when they differ. Every retry
+    /// signs against a fresh blockhash, so interactive signers such as hardware wallets prompt
+    /// again when a transaction expires before it lands.
+    pub async fn send_txn_with_payer(
+        &self,
+        signer: impl Signer,
+        fee_payer: impl Signer,
+        instructions: Vec<Instruction>,
+    ) -> Result<Signature> {
+        let sign_tx = |blockhash| {
+            let payer = fee_payer.pubkey();
+            if signer.pubkey() == payer {
+                sign_txn(&payer, &[&fee_payer], &instructions, blockhash)
+            } else {
+                let signers: [&dyn Signer; 2] = [&fee_payer, &signer];
+                sign_txn(&payer, &signers, &instructions, blockhash)
+            }
+        };
+        self.send_signed_txn(sign_tx, false, 1, 5, RetryPolicy::default())
+            .await
+    }
+
+    /// Submits the transaction built by `sign_tx` and waits for it to confirm, signing again
+    /// against a new blockhash whenever the previous one expires before confirmation.
+    async fn send_signed_txn(
+        &self,
+        sign_tx: impl Fn(Hash) -> Result<VersionedTransaction>,
+        skip_preflight: bool,
+        retry_timeout: u64,
+        retry_count: usize,
+        retry_policy: RetryPolicy,
+    ) -> Result<Signature> {
         let mut rt = retry_count;
+        let mut resends = 0;
         loop {
-            let blockhash = self.rpc_client.get_latest_blockhash().await?;
-            let message =
-                v0::Message::try_compile(&signer.pubkey(), &instructions, &[], blockhash)?;
-            let tx = VersionedTransaction::try_new(VersionedMessage::V0(message), &[&signer])?;
+            let blockhash = self.latest_blockhash().await?;
+            let tx = sign_tx(blockhash)?;
             let sig = self
                 .rpc_client
                 .send_transaction_with_config(
@@ -234,7 +1048,7 @@ impl BonsolClient {
                     RpcSendTransactionConfig {
                         skip_preflight,
                         max_retries: Some(0),
-                        preflight_commitment: Some(self.rpc_client.commitment().commitment),
+                        preflight_commitment: Some(self.commitment.commitment),
                         ..Default::default()
                     },
                 )
@@ -242,12 +1056,16 @@ impl BonsolClient {
 
             let now = Instant::now();
             let confirm_transaction_initial_timeout = Duration::from_secs(retry_timeout);
-            let (_, status) = loop {
-                let status = self.rpc_client.get_signature_status(&sig).await?;
+            let mut attempt = 0;
+            let (sig, status) = loop {
+                let status = self
+                    .rpc_client
+                    .get_signature_status_with_commitment(&sig, self.commitment)
+                    .await?;
                 if status.is_none() {
                     let blockhash_not_found = !self
                         .rpc_client
-                        .is_blockhash_valid(&blockhash, self.rpc_client.commitment())
+                        .is_blockhash_valid(&blockhash, self.commitment)
                         .await?;
                     if blockhash_not_found && now.elapsed() >= confirm_transaction_initial_timeout {
                         break (sig, status);
@@ -255,80 +1073,1792 @@ impl BonsolClient {
                 } else {
                     break (sig, status);
                 }
-                tokio::time::sleep(Duration::from_millis(500)).await;
+                tokio::time::sleep(retry_policy.delay(attempt, self.jitter.as_ref())).await;
+                attempt = attempt.saturating_add(1);
             };
 
             match status {
                 Some(Ok(())) => {
-                    return Ok(());
+                    return Ok(sig);
                 }
                 Some(Err(e)) => {
-                    return Err(anyhow::anyhow!("Transaction Falure Cannot Recover {:?}", e));
+                    return Err(transaction_failed(&tx, e));
                 }
                 None => {
                     rt -= 1;
                     if rt == 0 {
-                        return Err(anyhow::anyhow!("Timeout: Failed to confirm transaction"));
+                        return Err(BonsolClientError::TransactionNotConfirmed);
                     }
+                    tokio::time::sleep(retry_policy.delay(resends, self.jitter.as_ref())).await;
+                    resends = resends.saturating_add(1);
                 }
             }
         }
     }
 
+    /// Statuses of `sigs` in order, `None` for signatures the cluster has not seen. Asks for up to
+    /// `MAX_GET_SIGNATURE_STATUSES_QUERY_ITEMS` signatures per rpc call.
+    pub async fn statuses(&self, sigs: &[Signature]) -> Result<Vec<Option<TransactionStatus>>> {
+        let mut statuses = Vec::with_capacity(sigs.len());
+        for chunk in sigs.chunks(MAX_GET_SIGNATURE_STATUSES_QUERY_ITEMS) {
+            let response = self.rpc_client.get_signature_statuses(chunk).await?;
+            statuses.extend(response.value);
+        }
+        Ok(statuses
+            .into_iter()
+            .map(|s| s.map(TransactionStatus::from))
+            .collect())
+    }
+
+    /// Polls for the claim on an execution request, a `None` timeout waits indefinitely.
     pub async fn wait_for_claim(
         &self,
         requester: Pubkey,
         execution_id: &str,
         timeout: Option<u64>,
     ) -> Result<ClaimStateHolder> {
-        let mut interval = tokio::time::interval(tokio::time::Duration::from_secs(1));
+        let mut interval = tokio::time::interval(DEFAULT_POLL_INTERVAL);
         let now = Instant::now();
-        let mut end = false;
         loop {
             interval.tick().await;
-            if now.elapsed().as_secs() > timeout.unwrap_or(0) {
-                end = true;
-            }
+            let end = timeout.is_some_and(|t| now.elapsed() > Duration::from_secs(t));
             if let Ok(claim_state) = self.get_claim_state_v1(&requester, execution_id).await {
                 return Ok(claim_state);
             }
             if end {
-                return Err(anyhow::anyhow!("Timeout"));
+                return Err(BonsolClientError::Timeout);
             }
         }
     }
 
-    pub async fn wait_for_proof(
-        &self,
+    pub fn watch_execution<'a>(
+        &'a self,
         requester: Pubkey,
-        execution_id: &str,
-        timeout: Option<u64>,
-    ) -> Result<ExitCode> {
-        let current_block = self.get_current_slot().await?;
-        let expiry = current_block + 100;
-        let mut interval = tokio::time::interval(tokio::time::Duration::from_secs(1));
-        let now = Instant::now();
-        loop {
+        execution_id: &'a str,
+    ) -> impl Stream<Item = Result<ExecutionAccountStatus>> + 'a {
+        self.watch_execution_with_interval(requester, execution_id, DEFAULT_POLL_INTERVAL)
+    }
+
+    /// Yields the execution request's status every `poll_interval`. The stream ends after the
+    /// `Completed` status, or after an error, which is `BonsolClientError::Expired` once the
+    /// current slot passes the request's `max_block_height`.
+    pub fn watch_execution_with_interval<'a>(
+        &'a self,
+        requester: Pubkey,
+        execution_id: &'a str,
+        poll_interval: Duration,
+    ) -> impl Stream<Item = Result<ExecutionAccountStatus>> + 'a {
+        let interval = tokio::time::interval(poll_interval);
+        stream::unfold(Some((interval, None)), move |state| async move {
+            let (mut interval, mut expiry) = state?;
+            interval.tick().await;
+            let status = match self
+                .get_execution_request_v1(&requester, execution_id)
+                .await
+            {
+                Ok(ExecutionAccountStatus::Pending(req)) => {
+                    let max_block_height = *expiry.get_or_insert(req.max_block_height);
+                    match self.get_current_slot().await {
+                        Ok(slot) if slot > max_block_height => Err(BonsolClientError::Expired),
+                        Ok(_) => Ok(ExecutionAccountStatus::Pending(req)),
+                        Err(e) => Err(e),
+                    }
+                }
+                status => status,
+            };
+            let next = matches!(status, Ok(ExecutionAccountStatus::Pending(_)))
+                .then_some((interval, expiry));
+            Some((status, next))
+        })
+    }
+
+    /// Yields the execution request's current status and then a status each time its account
+    /// changes, ending like `watch_execution`. Falls back to `watch_execution` when the websocket
+    /// subscription cannot be opened.
+    pub fn subscribe_execution<'a>(
+        &'a self,
+        requester: Pubkey,
+        execution_id: &'a str,
+    ) -> impl Stream<Item = Result<ExecutionAccountStatus>> + 'a {
+        let (execution_account, _) = execution_address(&requester, execution_id.as_bytes());
+        let updates = stream::once(self.account_updates(execution_account));
+        updates.flat_map(move |updates| match updates {
+            Ok(updates) => {
+                // the subscription only reports changes made after it was opened
+                let current = async move {
+                    self.get_execution_request_v1(&requester, execution_id)
+                        .await
+                };
+                let changes = updates.map(|data| ExecutionAccountStatus::from_account_data(&data));
+                until_terminal(stream::once(current).chain(changes)).left_stream()
+            }
+            Err(_) => self.watch_execution(requester, execution_id).right_stream(),
+        })
+    }
+
+    /// Yields the deployment of `image_id` each time its account changes, so callers caching the
+    /// program url can pick up `update_deployment_url` without restarting.
+    pub async fn subscribe_deployment(
+        &self,
+        image_id: &str,
+    ) -> Result<impl Stream<Item = DeployV1T>> {
+        let (deployment_account, _) = deployment_address(image_id);
+        let updates = self.account_updates(deployment_account).await?;
+        Ok(deployment_changes(updates))
+    }
+
+    /// Forwards the data of `account` each time it changes. The subscription lives in a spawned
+    /// task, which unsubscribes and closes the websocket as soon as the returned stream is dropped.
+    async fn account_updates(&self, account: Pubkey) -> Result<impl Stream<Item = Vec<u8>>> {
+        let ws_url = match &self.ws_url {
+            Some(ws_url) => ws_url.clone(),
+            None => websocket_url(&self.rpc_client.url())?,
+        };
+        let pubsub = PubsubClient::new(&ws_url)
+            .await
+            .map_err(|e| BonsolClientError::Subscription(e.to_string()))?;
+        let config = RpcAccountInfoConfig {
+            encoding: Some(UiAccountEncoding::Base64),
+            commitment: Some(self.commitment),
+            ..Default::default()
+        };
+        let (updates_tx, mut updates_rx) = mpsc::unbounded_channel();
+        let (subscribed_tx, subscribed_rx) = oneshot::channel();
+        tokio::spawn(async move {
+            let (mut updates, unsubscribe) =
+                match pubsub.account_subscribe(&account, Some(config)).await {
+                    Ok(subscription) => {
+                        let _ = subscribed_tx.send(Ok(()));
+                        subscription
+                    }
+                    Err(e) => {
+                        let _ = subscribed_tx.send(Err(e.to_string()));
+                        return;
+                    }
+                };
+            loop {
+                tokio::select! {
+                    update = updates.next() => {
+                        let Some(update) = update else {
+                            break;
+                        };
+                        let Some(data) = update.value.data.decode() else {
+                            continue;
+                        };
+                        if updates_tx.send(data).is_err() {
+                            break;
+                        }
+                    }
+                    // the stream was dropped, waiting for the next change could take forever
+                    _ = updates_tx.closed() => break,
+                }
+            }
+            drop(updates);
+            unsubscribe().await;
+            let _ = pubsub.shutdown().await;
+        });
+        subscribed_rx
+            .await
+            .map_err(|e| BonsolClientError::Subscription(e.to_string()))?
+            .map_err(BonsolClientError::Subscription)?;
+        Ok(stream::poll_fn(move |cx| updates_rx.poll_recv(cx)))
+    }
+
+    pub async fn wait_for_proof(
+        &self,
+        requester: Pubkey,
+        execution_id: &str,
+        timeout: Option<u64>,
+    ) -> Result<ExitCode> {
+        self.wait_for_proof_with_interval(requester, execution_id, timeout, DEFAULT_POLL_INTERVAL)
+            .await
+    }
+
+    /// Polls the execution request every `poll_interval` until it completes. The request is
+    /// considered expired once the current slot passes its `max_block_height`, a `None` timeout
+    /// waits until then.
+    pub async fn wait_for_proof_with_interval(
+        &self,
+        requester: Pubkey,
+        execution_id: &str,
+        timeout: Option<u64>,
+        poll_interval: Duration,
+    ) -> Result<ExitCode> {
+        let mut interval = tokio::time::interval(poll_interval);
+        let now = Instant::now();
+        let mut expiry = None;
+        loop {
             interval.tick().await;
-            if now.elapsed().as_secs() > timeout.unwrap_or(0) {
-                return Err(anyhow::anyhow!("Timeout"));
+            if let Some(timeout) = timeout {
+                if now.elapsed() > Duration::from_secs(timeout) {
+                    return Err(BonsolClientError::Timeout);
+                }
             }
             let status = self
                 .get_execution_request_v1(&requester, execution_id)
-                .await;
+                .await?;
             match status {
-                Ok(ExecutionAccountStatus::Pending(req)) => {
-                    if req.max_block_height < expiry {
-                        return Err(anyhow::anyhow!("Expired"));
+                ExecutionAccountStatus::Pending(req) => {
+                    let expiry = *expiry.get_or_insert(req.max_block_height);
+                    if self.get_current_slot().await? > expiry {
+                        return Err(BonsolClientError::Expired);
                     }
                 }
-                Ok(ExecutionAccountStatus::Completed(s)) => {
+                ExecutionAccountStatus::Completed(s) => {
                     return Ok(s);
                 }
-                Err(e) => {
-                    return Err(e);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+    use std::io::Write;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::{Arc, Mutex};
+
+    use base64::Engine;
+    use bonsol_interface::bonsol_schema::{parse_ix_data, ChannelInstructionIxType};
+    use flate2::write::GzEncoder;
+    use flate2::Compression;
+    use futures_util::{SinkExt, StreamExt};
+    use serde_json::json;
+    use solana_rpc_client::rpc_client::RpcClientConfig;
+    use solana_rpc_client::rpc_sender::{RpcSender, RpcTransportStats};
+    use solana_rpc_client_api::request::RpcRequest;
+    use solana_sdk::instruction::AccountMeta;
+    use tokio_tungstenite::tungstenite::Message;
+
+    use super::*;
+
+    /// Counts prioritization fee lookups, every other request returns null
+    struct FeeCountingSender {
+        fee_calls: Arc<AtomicUsize>,
+    }
+
+    #[async_trait::async_trait]
+    impl RpcSender for FeeCountingSender {
+        async fn send(
+            &self,
+            request: RpcRequest,
+            _params: serde_json::Value,
+        ) -> solana_rpc_client_api::client_error::Result<serde_json::Value> {
+            match request {
+                RpcRequest::GetRecentPrioritizationFees => {
+                    self.fee_calls.fetch_add(1, Ordering::SeqCst);
+                    Ok(json!([{ "slot": 1, "prioritizationFee": 7 }]))
                 }
+                _ => Ok(serde_json::Value::Null),
             }
         }
+
+        fn get_transport_stats(&self) -> RpcTransportStats {
+            RpcTransportStats::default()
+        }
+
+        fn url(&self) -> String {
+            "fee-counting".to_string()
+        }
+    }
+
+    /// Serves `accounts[n]` for the nth account lookup, repeating the last entry once exhausted
+    /// Also records every request so tests can check the params that were sent
+    struct AccountSender {
+        account_calls: Arc<AtomicUsize>,
+        accounts: Vec<Option<Vec<u8>>>,
+        slot: u64,
+        requests: Arc<Mutex<Vec<(RpcRequest, serde_json::Value)>>>,
+    }
+
+    #[async_trait::async_trait]
+    impl RpcSender for AccountSender {
+        async fn send(
+            &self,
+            request: RpcRequest,
+            params: serde_json::Value,
+        ) -> solana_rpc_client_api::client_error::Result<serde_json::Value> {
+            self.requests.lock().unwrap().push((request, params));
+            match request {
+                RpcRequest::GetAccountInfo => {
+                    let n = self.account_calls.fetch_add(1, Ordering::SeqCst);
+                    let account = self
+                        .accounts
+                        .get(n)
+                        .or(self.accounts.last())
+                        .cloned()
+                        .flatten();
+                    let value = account.map(|data| {
+                        let encoded = base64::engine::general_purpose::STANDARD.encode(&data);
+                        json!({
+                            "data": [encoded, "base64"],
+                            "executable": false,
+                            "lamports": 1_000_000,
+                            "owner": ID.to_string(),
+                            "rentEpoch": 0,
+                            "space": data.len()
+                        })
+                    });
+                    Ok(json!({ "context": { "slot": self.slot }, "value": value }))
+                }
+                RpcRequest::GetSlot => Ok(json!(self.slot)),
+                RpcRequest::GetLatestBlockhash => Ok(json!({
+                    "context": { "slot": self.slot },
+                    "value": {
+                        "blockhash": Hash::default().to_string(),
+                        "lastValidBlockHeight": self.slot + 150
+                    }
+                })),
+                RpcRequest::GetVersion => Ok(json!({ "solana-core": "2.0.0" })),
+                RpcRequest::GetRecentPrioritizationFees => Ok(json!([])),
+                _ => Ok(serde_json::Value::Null),
+            }
+        }
+
+        fn get_transport_stats(&self) -> RpcTransportStats {
+            RpcTransportStats::default()
+        }
+
+        fn url(&self) -> String {
+            "accounts".to_string()
+        }
+    }
+
+    fn account_client(
+        accounts: Vec<Option<Vec<u8>>>,
+        slot: u64,
+    ) -> (BonsolClient, Arc<AtomicUsize>) {
+        let account_calls = Arc::new(AtomicUsize::new(0));
+        let sender = AccountSender {
+            account_calls: account_calls.clone(),
+            accounts,
+            slot,
+            requests: Arc::new(Mutex::new(vec![])),
+        };
+        let client = BonsolClient::with_rpc_client(RpcClient::new_sender(
+            sender,
+            RpcClientConfig::default(),
+        ));
+        (client, account_calls)
+    }
+
+    fn pending_execution_request(max_block_height: u64) -> Vec<u8> {
+        let mut er = ExecutionRequestV1T::default();
+        er.execution_id = Some("test".to_string());
+        er.image_id = Some(TEST_IMAGE_ID.to_string());
+        er.max_block_height = max_block_height;
+        let mut fbb = flatbuffers::FlatBufferBuilder::new();
+        let offset = er.pack(&mut fbb);
+        fbb.finish(offset, None);
+        fbb.finished_data().to_vec()
+    }
+
+    pub(crate) const TEST_IMAGE_ID: &str =
+        "68f4b0c5f9ce034aa60ceb264a18d6c410a3af68fafd931bcfd9ebe7c1e42960";
+
+    pub(crate) fn mock_client() -> BonsolClient {
+        let mut mocks = HashMap::new();
+        mocks.insert(
+            RpcRequest::GetRecentPrioritizationFees,
+            json!([{ "slot": 1, "prioritizationFee": 7 }]),
+        );
+        BonsolClient::with_rpc_client(RpcClient::new_mock_with_mocks(
+            "succeeds".to_string(),
+            mocks,
+        ))
+    }
+
+    async fn program_server(body: Vec<u8>) -> (mockito::Mock, String, mockito::ServerGuard) {
+        let mut server = mockito::Server::new_async().await;
+        let url = format!("{}/program", server.url());
+        let mock = server
+            .mock("GET", "/program")
+            .with_status(200)
+            .with_header("content-type", "application/octet-stream")
+            .with_body(body)
+            .create_async()
+            .await;
+        (mock, url, server)
+    }
+
+    #[tokio::test]
+    async fn test_download_program_within_limit() {
+        let body = vec![1u8; 1024 * 10];
+        let (mock, url, _server) = program_server(body.clone()).await;
+        let http_client = reqwest::Client::new();
+        let mut progress = 0;
+        let bytes = download_program_from_url(&http_client, &url, 1, &HashMap::new(), |received| {
+            progress = received
+        })
+        .await
+        .unwrap();
+        assert_eq!(bytes.as_ref(), body.as_slice());
+        assert_eq!(progress, body.len());
+        mock.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn test_download_program_oversized() {
+        let body = vec![1u8; 1024 * 1024 * 2];
+        let (mock, url, _server) = program_server(body).await;
+        let http_client = reqwest::Client::new();
+        let result =
+            download_program_from_url(&http_client, &url, 1, &HashMap::new(), |_| {}).await;
+        assert!(result.is_err());
+        assert!(result
+            .unwrap_err()
+            .to_string()
+            .contains("Max size exceeded"));
+        mock.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn test_download_program_with_headers() {
+        let mut server = mockito::Server::new_async().await;
+        let url = format!("{}/program", server.url());
+        let mock = server
+            .mock("GET", "/program")
+            .match_header("authorization", "Bearer gateway-token")
+            .with_status(200)
+            .with_body(b"program")
+            .create_async()
+            .await;
+        let mut headers = HashMap::new();
+        headers.insert(
+            "Authorization".to_string(),
+            "Bearer gateway-token".to_string(),
+        );
+        let bytes = download_program_from_url(&reqwest::Client::new(), &url, 1, &headers, |_| {})
+            .await
+            .unwrap();
+        assert_eq!(bytes.as_ref(), b"program");
+        mock.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn test_download_program_with_http_client() {
+        let mut server = mockito::Server::new_async().await;
+        let url = format!("{}/program", server.url());
+        let mock = server
+            .mock("GET", "/program")
+            .match_header("user-agent", "custom-agent")
+            .with_status(200)
+            .with_body(b"program")
+            .create_async()
+            .await;
+        let (client, _) = account_client(vec![Some(deployment(&url, 7))], 10);
+        let http_client = reqwest::Client::builder()
+            .user_agent("custom-agent")
+            .build()
+            .unwrap();
+        let client = client.with_http_client(http_client);
+        let opts = DownloadProgramOptions {
+            verify_image_id: false,
+            ..Default::default()
+        };
+        let bytes = client
+            .download_program_with_opts(TEST_IMAGE_ID, opts, |_| {})
+            .await
+            .unwrap();
+        assert_eq!(bytes.as_ref(), b"program");
+        mock.assert_async().await;
+    }
+
+    const SIMPLE_ELF: &[u8] = include_bytes!(concat!(
+        env!("CARGO_MANIFEST_DIR"),
+        "/../elf/68f4b0c5f9ce034aa60ceb264a18d6c410a3af68fafd931bcfd9ebe7c1e42960"
+    ));
+
+    fn gzip(data: &[u8]) -> Vec<u8> {
+        let mut encoder = GzEncoder::new(vec![], Compression::default());
+        encoder.write_all(data).unwrap();
+        encoder.finish().unwrap()
+    }
+
+    #[test]
+    fn test_decompress_program() {
+        let size = SIMPLE_ELF.len() as u64;
+        let max_size = 1024 * 1024;
+        let gzipped = Bytes::from(gzip(SIMPLE_ELF));
+        let program = decompress_program(gzipped, size, max_size).unwrap();
+        assert_eq!(program.as_ref(), SIMPLE_ELF);
+        let zstd = Bytes::from(zstd::encode_all(SIMPLE_ELF, 0).unwrap());
+        let program = decompress_program(zstd, size, max_size).unwrap();
+        assert_eq!(program.as_ref(), SIMPLE_ELF);
+        let uncompressed = Bytes::from_static(SIMPLE_ELF);
+        let program = decompress_program(uncompressed, size, max_size).unwrap();
+        assert_eq!(program.as_ref(), SIMPLE_ELF);
+    }
+
+    #[test]
+    fn test_decompress_program_checks_size() {
+        let gzipped = Bytes::from(gzip(&[0u8; 2048]));
+        assert!(matches!(
+            decompress_program(gzipped.clone(), 1024, 4096),
+            Err(BonsolClientError::ImageSizeMismatch {
+                expected: 1024,
+                actual: 2048
+            })
+        ));
+        assert!(matches!(
+            decompress_program(gzipped, 2048, 1024),
+            Err(BonsolClientError::Download(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_download_gzipped_program() {
+        let (mock, url, _server) = program_server(gzip(SIMPLE_ELF)).await;
+        let (client, _) = account_client(vec![Some(deployment(&url, SIMPLE_ELF.len() as u64))], 10);
+        let program = client.download_program(TEST_IMAGE_ID).await.unwrap();
+        assert_eq!(program.as_ref(), SIMPLE_ELF);
+        mock.assert_async().await;
+    }
+
+    async fn download_with_sha256(sha256: Vec<u8>) -> Result<Bytes> {
+        let (_mock, url, _server) = program_server(SIMPLE_ELF.to_vec()).await;
+        let size = SIMPLE_ELF.len() as u64;
+        let deployment = deployment_with_sha256(&Pubkey::default(), &url, size, Some(sha256));
+        let (client, _) = account_client(vec![Some(deployment)], 10);
+        client.download_program(TEST_IMAGE_ID).await
+    }
+
+    #[tokio::test]
+    async fn test_download_program_matching_sha256() {
+        let program = download_with_sha256(Sha256::digest(SIMPLE_ELF).to_vec())
+            .await
+            .unwrap();
+        assert_eq!(program.as_ref(), SIMPLE_ELF);
+    }
+
+    #[tokio::test]
+    async fn test_download_program_mismatched_sha256() {
+        let err = download_with_sha256(vec![0; 32]).await.unwrap_err();
+        match err {
+            BonsolClientError::MismatchedSha256 { expected, actual } => {
+                assert_eq!(expected, "00".repeat(32));
+                assert_eq!(actual, hex::encode(Sha256::digest(SIMPLE_ELF)));
+            }
+            _ => panic!("expected MismatchedSha256, got {:?}", err),
+        }
+    }
+
+    #[test]
+    fn test_verify_program_image_id_mismatch() {
+        let other_image_id = "20b9db715f989e3f57842787badafae101ce0b16202491bac1a3aebf573da0ba";
+        let err = verify_program_image_id(other_image_id, &Bytes::from_static(SIMPLE_ELF))
+            .unwrap_err();
+        match err {
+            BonsolClientError::MismatchedImageId { expected, .. } => {
+                assert_eq!(expected, other_image_id);
+            }
+            _ => panic!("expected MismatchedImageId, got {:?}", err),
+        }
+    }
+
+    #[test]
+    fn test_verify_program_image_id_not_an_elf() {
+        let result = verify_program_image_id(TEST_IMAGE_ID, &Bytes::from_static(b"not an elf"));
+        assert!(matches!(result, Err(BonsolClientError::InvalidProgram(_))));
+    }
+
+    #[tokio::test]
+    async fn test_missing_accounts_are_not_found() {
+        let (client, _) = account_client(vec![None], 10);
+        let requester = Pubkey::new_unique();
+        assert!(matches!(
+            client.get_deployment_v1(TEST_IMAGE_ID).await,
+            Err(BonsolClientError::DeploymentNotFound(id)) if id == TEST_IMAGE_ID
+        ));
+        assert!(matches!(
+            client.get_execution_request_v1(&requester, "test").await,
+            Err(BonsolClientError::ExecutionNotFound(id)) if id == "test"
+        ));
+        assert!(matches!(
+            client.get_claim_state_v1(&requester, "test").await,
+            Err(BonsolClientError::ClaimNotFound(id)) if id == "test"
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_malformed_accounts_are_invalid_account_data() {
+        let (client, _) = account_client(vec![Some(vec![1, 2, 3, 4])], 10);
+        assert!(matches!(
+            client.get_deployment_v1(TEST_IMAGE_ID).await,
+            Err(BonsolClientError::InvalidAccountData(_))
+        ));
+        assert!(matches!(
+            client
+                .get_execution_request_v1(&Pubkey::new_unique(), "test")
+                .await,
+            Err(BonsolClientError::InvalidAccountData(_))
+        ));
+
+        let (client, _) = account_client(vec![Some(vec![42])], 10);
+        assert!(matches!(
+            client
+                .get_execution_request_v1(&Pubkey::new_unique(), "test")
+                .await,
+            Err(BonsolClientError::InvalidExitCode(42))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_try_get_execution_request_v1() {
+        let requester = Pubkey::new_unique();
+        let (client, _) = account_client(vec![None], 10);
+        assert!(matches!(
+            client.try_get_execution_request_v1(&requester, "test").await,
+            Ok(None)
+        ));
+
+        let (client, _) = account_client(vec![Some(vec![0xff; 48])], 10);
+        assert!(matches!(
+            client.try_get_execution_request_v1(&requester, "test").await,
+            Err(BonsolClientError::InvalidAccountData(_))
+        ));
+
+        let (client, _) = account_client(vec![Some(vec![ExitCode::VerifyError as u8])], 10);
+        assert!(matches!(
+            client.try_get_execution_request_v1(&requester, "test").await,
+            Ok(Some(ExecutionAccountStatus::Completed(ExitCode::VerifyError)))
+        ));
+
+        let (client, _) = account_client(vec![Some(pending_execution_request(100))], 10);
+        match client.try_get_execution_request_v1(&requester, "test").await {
+            Ok(Some(ExecutionAccountStatus::Pending(er))) => {
+                assert_eq!(er.max_block_height, 100);
+                assert_eq!(er.image_id.as_deref(), Some(TEST_IMAGE_ID));
+            }
+            _ => panic!("expected a pending execution request"),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_commitment_is_forwarded() {
+        let requests = Arc::new(Mutex::new(vec![]));
+        let sender = AccountSender {
+            account_calls: Arc::new(AtomicUsize::new(0)),
+            accounts: vec![Some(pending_execution_request(100))],
+            slot: 10,
+            requests: requests.clone(),
+        };
+        let client = BonsolClient::with_rpc_client(RpcClient::new_sender(
+            sender,
+            RpcClientConfig::default(),
+        ))
+        .with_commitment(CommitmentConfig::finalized());
+        let requester = Pubkey::new_unique();
+
+        client.get_deployment(TEST_IMAGE_ID).await.unwrap();
+        client
+            .get_execution_request_v1(&requester, "test")
+            .await
+            .unwrap();
+        client.get_current_slot().await.unwrap();
+        let signer = solana_sdk::signature::Keypair::new();
+        client.compile_txn(&signer, &[]).await.unwrap();
+
+        let requests = requests.lock().unwrap();
+        let mut checked = 0;
+        for (request, params) in requests.iter() {
+            let config = match request {
+                RpcRequest::GetAccountInfo => &params[1],
+                RpcRequest::GetSlot | RpcRequest::GetLatestBlockhash => &params[0],
+                _ => continue,
+            };
+            assert_eq!(config["commitment"], "finalized", "{:?}", request);
+            checked += 1;
+        }
+        assert_eq!(checked, 4);
+    }
+
+    #[tokio::test]
+    async fn test_rpc_failures_are_rpc_errors() {
+        let client = BonsolClient::with_rpc_client(RpcClient::new_mock("fails".to_string()));
+        assert!(matches!(
+            client.get_current_slot().await,
+            Err(BonsolClientError::Rpc(_))
+        ));
+        assert!(matches!(
+            client.get_deployment_v1(TEST_IMAGE_ID).await,
+            Err(BonsolClientError::Rpc(_))
+        ));
+    }
+
+    #[test]
+    fn test_errors_convert_to_anyhow() {
+        let err: anyhow::Error = BonsolClientError::Timeout.into();
+        assert!(matches!(
+            err.downcast_ref::<BonsolClientError>(),
+            Some(BonsolClientError::Timeout)
+        ));
+    }
+
+    #[test]
+    fn test_fee_percentile() {
+        let fees = vec![90, 10, 50, 30, 70, 20, 80, 40, 60, 100, 0];
+        assert_eq!(fee_percentile(&fees, 0.0), 0);
+        assert_eq!(fee_percentile(&fees, 50.0), 50);
+        assert_eq!(fee_percentile(&fees, 75.0), 80);
+        assert_eq!(fee_percentile(&fees, 90.0), 90);
+        assert_eq!(fee_percentile(&fees, 100.0), 100);
+    }
+
+    #[test]
+    fn test_fee_percentile_out_of_range() {
+        let fees = vec![3, 1, 2];
+        assert_eq!(fee_percentile(&fees, -10.0), 1);
+        assert_eq!(fee_percentile(&fees, 250.0), 3);
+        assert_eq!(fee_percentile(&fees, f32::NAN), 2);
+    }
+
+    #[test]
+    fn test_fee_percentile_empty() {
+        assert_eq!(fee_percentile(&[], 75.0), FALLBACK_PRIORITIZATION_FEE);
+        assert_eq!(fee_percentile(&[42], 75.0), 42);
+    }
+
+    #[tokio::test]
+    async fn test_get_fees_uses_configured_percentile() {
+        let mut mocks = HashMap::new();
+        mocks.insert(
+            RpcRequest::GetRecentPrioritizationFees,
+            json!([
+                { "slot": 1, "prioritizationFee": 100 },
+                { "slot": 2, "prioritizationFee": 0 },
+                { "slot": 3, "prioritizationFee": 50 },
+                { "slot": 4, "prioritizationFee": 25 },
+                { "slot": 5, "prioritizationFee": 75 }
+            ]),
+        );
+        let client = BonsolClient::with_rpc_client(RpcClient::new_mock_with_mocks(
+            "succeeds".to_string(),
+            mocks,
+        ))
+        .with_fee_percentile(75.0);
+        let fee = client.get_fees(&Pubkey::new_unique()).await.unwrap();
+        assert_eq!(fee, 75);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_wait_for_claim_without_timeout_keeps_polling() {
+        let mut accounts = vec![None; 5];
+        accounts.push(Some(vec![0u8; 40]));
+        let (client, account_calls) = account_client(accounts, 10);
+        client
+            .wait_for_claim(Pubkey::new_unique(), "test", None)
+            .await
+            .unwrap();
+        assert_eq!(account_calls.load(Ordering::SeqCst), 6);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_wait_for_claim_timeout() {
+        let (client, account_calls) = account_client(vec![None], 10);
+        let err = client
+            .wait_for_claim(Pubkey::new_unique(), "test", Some(2))
+            .await
+            .err()
+            .expect("expected timeout");
+        assert!(matches!(err, BonsolClientError::Timeout));
+        // polls at 0, 1, 2 and 3 seconds, giving up once more than 2 seconds have passed
+        assert_eq!(account_calls.load(Ordering::SeqCst), 4);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_wait_for_proof_without_timeout_keeps_polling() {
+        let mut accounts = vec![Some(pending_execution_request(100)); 5];
+        accounts.push(Some(vec![ExitCode::Success as u8]));
+        let (client, account_calls) = account_client(accounts, 10);
+        let exit_code = client
+            .wait_for_proof(Pubkey::new_unique(), "test", None)
+            .await
+            .unwrap();
+        assert!(exit_code == ExitCode::Success);
+        assert_eq!(account_calls.load(Ordering::SeqCst), 6);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_wait_for_proof_timeout() {
+        let (client, _) = account_client(vec![Some(pending_execution_request(100))], 10);
+        let err = client
+            .wait_for_proof_with_interval(
+                Pubkey::new_unique(),
+                "test",
+                Some(2),
+                Duration::from_millis(500),
+            )
+            .await
+            .err()
+            .expect("expected timeout");
+        assert!(matches!(err, BonsolClientError::Timeout));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_wait_for_proof_expired() {
+        let (client, account_calls) = account_client(vec![Some(pending_execution_request(10))], 20);
+        let err = client
+            .wait_for_proof(Pubkey::new_unique(), "test", None)
+            .await
+            .err()
+            .expect("expected expiry");
+        assert!(matches!(err, BonsolClientError::Expired));
+        assert_eq!(account_calls.load(Ordering::SeqCst), 1);
+    }
+
+    fn claim_state(block_commitment: u64) -> Vec<u8> {
+        let mut data = Pubkey::new_unique().to_bytes().to_vec();
+        data.extend_from_slice(&1u64.to_le_bytes());
+        data.extend_from_slice(&block_commitment.to_le_bytes());
+        data
+    }
+
+    #[tokio::test]
+    async fn test_reclaim_expired_unclaimed() {
+        let requester = Pubkey::new_unique();
+        let (client, _) = account_client(vec![Some(pending_execution_request(10)), None], 20);
+        let ixs = client.reclaim_expired(&requester, "test").await.unwrap();
+        let ix = ixs.last().unwrap();
+        let (execution_account, _) = execution_address(&requester, b"test");
+        assert_eq!(ix.program_id, ID);
+        assert_eq!(ix.accounts[0].pubkey, requester);
+        assert!(ix.accounts[0].is_signer);
+        assert_eq!(ix.accounts[1].pubkey, execution_account);
+        assert_eq!(ix.accounts.len(), 3);
+    }
+
+    #[tokio::test]
+    async fn test_reclaim_expired_refunds_destination() {
+        let requester = Pubkey::new_unique();
+        let refund_destination = Pubkey::new_unique();
+        let mut er = ExecutionRequestV1T::default();
+        er.execution_id = Some("test".to_string());
+        er.max_block_height = 10;
+        er.refund_destination = Some(refund_destination.to_bytes().to_vec());
+        let mut fbb = flatbuffers::FlatBufferBuilder::new();
+        let offset = er.pack(&mut fbb);
+        fbb.finish(offset, None);
+        let (client, _) = account_client(vec![Some(fbb.finished_data().to_vec()), None], 20);
+        let ixs = client.reclaim_expired(&requester, "test").await.unwrap();
+        let ix = ixs.last().unwrap();
+        assert_eq!(ix.accounts.len(), 4);
+        assert_eq!(ix.accounts[3].pubkey, refund_destination);
+        assert!(ix.accounts[3].is_writable);
+    }
+
+    #[tokio::test]
+    async fn test_reclaim_expired_lapsed_claim() {
+        let accounts = vec![Some(pending_execution_request(10)), Some(claim_state(15))];
+        let (client, _) = account_client(accounts, 20);
+        assert!(client
+            .reclaim_expired(&Pubkey::new_unique(), "test")
+            .await
+            .is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_reclaim_still_valid_rejected() {
+        let (client, account_calls) =
+            account_client(vec![Some(pending_execution_request(100))], 20);
+        let err = client
+            .reclaim_expired(&Pubkey::new_unique(), "test")
+            .await
+            .expect_err("expected rejection");
+        assert!(matches!(
+            err,
+            BonsolClientError::ExecutionNotExpired {
+                max_block_height: 100,
+                current_slot: 20
+            }
+        ));
+        assert_eq!(account_calls.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn test_reclaim_active_claim_rejected() {
+        let accounts = vec![Some(pending_execution_request(10)), Some(claim_state(30))];
+        let (client, _) = account_client(accounts, 20);
+        let err = client
+            .reclaim_expired(&Pubkey::new_unique(), "test")
+            .await
+            .expect_err("expected rejection");
+        assert!(matches!(err, BonsolClientError::ActiveClaim(id) if id == "test"));
+    }
+
+    #[tokio::test]
+    async fn test_reclaim_completed_rejected() {
+        let (client, _) = account_client(vec![Some(vec![ExitCode::Success as u8])], 20);
+        let err = client
+            .reclaim_expired(&Pubkey::new_unique(), "test")
+            .await
+            .expect_err("expected rejection");
+        assert!(matches!(err, BonsolClientError::ExecutionCompleted(id) if id == "test"));
+    }
+
+    #[tokio::test]
+    async fn test_simulate_txn() {
+        let signer = solana_sdk::signature::Keypair::new();
+        let instructions = vec![ComputeBudgetInstruction::set_compute_unit_limit(
+            DEFAULT_COMPUTE_UNIT_LIMIT,
+        )];
+        let blockhash = Hash::new_unique();
+        let expected_tx = sign_txn(&signer.pubkey(), &[&signer], &instructions, blockhash).unwrap();
+        let encoded_tx = base64::engine::general_purpose::STANDARD
+            .encode(bincode::serialize(&expected_tx).unwrap());
+
+        let mut server = mockito::Server::new_async().await;
+        let blockhash_mock = server
+            .mock("POST", "/")
+            .match_body(mockito::Matcher::PartialJson(
+                json!({ "method": "getLatestBlockhash" }),
+            ))
+            .with_body(
+                json!({
+                    "jsonrpc": "2.0",
+                    "id": 1,
+                    "result": {
+                        "context": { "slot": 1 },
+                        "value": {
+                            "blockhash": blockhash.to_string(),
+                            "lastValidBlockHeight": 100
+                        }
+                    }
+                })
+                .to_string(),
+            )
+            .create_async()
+            .await;
+        // the simulation has to be of the signed transaction send_txn would send, so neither
+        // signature verification nor blockhash replacement may be asked for
+        let simulate_mock = server
+            .mock("POST", "/")
+            .match_body(mockito::Matcher::PartialJson(json!({
+                "method": "simulateTransaction",
+                "params": [
+                    encoded_tx,
+                    {
+                        "encoding": "base64",
+                        "sigVerify": false,
+                        "replaceRecentBlockhash": false
+                    }
+                ]
+            })))
+            .with_body(
+                json!({
+                    "jsonrpc": "2.0",
+                    "id": 1,
+                    "result": {
+                        "context": { "slot": 1 },
+                        "value": {
+                            "err": null,
+                            "logs": [
+                                "Program BoNsHRcyLLNdtnoDf8hiCNZpyehMC4FDMxs6NTxFi3ew success"
+                            ],
+                            "accounts": null,
+                            "unitsConsumed": 1234,
+                            "returnData": null
+                        }
+                    }
+                })
+                .to_string(),
+            )
+            .create_async()
+            .await;
+
+        let client = BonsolClient::new(server.url());
+        let result = client.simulate_txn(&signer, instructions).await.unwrap();
+        assert!(result.err.is_none());
+        assert_eq!(result.units_consumed, Some(1234));
+        assert_eq!(result.logs.map(|l| l.len()), Some(1));
+        blockhash_mock.assert_async().await;
+        simulate_mock.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn test_send_txn_returns_signature() {
+        let signer = solana_sdk::signature::Keypair::new();
+        let instructions = vec![ComputeBudgetInstruction::set_compute_unit_limit(
+            DEFAULT_COMPUTE_UNIT_LIMIT,
+        )];
+        // the mock rpc always hands out the same blockhash so the signature is deterministic
+        let (expected_tx, _) = BonsolClient::with_rpc_client(RpcClient::new_mock(
+            "succeeds".to_string(),
+        ))
+        .compile_txn(&signer, &instructions)
+        .await
+        .unwrap();
+        let expected = expected_tx.signatures[0];
+        let mut mocks = HashMap::new();
+        mocks.insert(RpcRequest::SendTransaction, json!(expected.to_string()));
+        let client = BonsolClient::with_rpc_client(RpcClient::new_mock_with_mocks(
+            "succeeds".to_string(),
+            mocks,
+        ));
+        let sig = client
+            .send_txn_standard(&signer, instructions)
+            .await
+            .unwrap();
+        assert_eq!(sig, expected);
+    }
+
+    /// `send_transaction` rejects a returned signature that differs from the transaction's own
+    fn sending_client(signature: Signature) -> BonsolClient {
+        let mut mocks = HashMap::new();
+        mocks.insert(RpcRequest::SendTransaction, json!(signature.to_string()));
+        BonsolClient::with_rpc_client(RpcClient::new_mock_with_mocks(
+            "succeeds".to_string(),
+            mocks,
+        ))
+    }
+
+    fn requester_transfer(requester: &Pubkey) -> Vec<Instruction> {
+        vec![solana_sdk::system_instruction::transfer(
+            requester,
+            &Pubkey::new_unique(),
+            1,
+        )]
+    }
+
+    #[test]
+    fn test_sign_txn_with_separate_payer() {
+        let requester = solana_sdk::signature::Keypair::new();
+        let payer = solana_sdk::signature::Keypair::new();
+        let instructions = requester_transfer(&requester.pubkey());
+        let signers: [&dyn Signer; 2] = [&payer, &requester];
+        let blockhash = Hash::new_unique();
+        let tx = sign_txn(&payer.pubkey(), &signers, &instructions, blockhash).unwrap();
+
+        let keys = tx.message.static_account_keys();
+        assert_eq!(keys[0], payer.pubkey());
+        assert_eq!(keys[1], requester.pubkey());
+        assert_eq!(tx.signatures.len(), 2);
+        assert!(tx.signatures.iter().all(|s| *s != Signature::default()));
+        assert_eq!(tx.verify_with_results(), vec![true, true]);
+    }
+
+    fn custom_error(index: u8, code: u32) -> TransactionError {
+        TransactionError::InstructionError(
+            index,
+            solana_sdk::instruction::InstructionError::Custom(code),
+        )
+    }
+
+    #[test]
+    fn test_decode_channel_error() {
+        let err = custom_error(0, ChannelError::ExecutionExpired as u32);
+        assert_eq!(
+            decode_channel_error(&err),
+            Some(ChannelError::ExecutionExpired)
+        );
+        let err = custom_error(2, ChannelError::InvalidRequesterAccount as u32);
+        assert_eq!(
+            decode_channel_error(&err),
+            Some(ChannelError::InvalidRequesterAccount)
+        );
+        assert_eq!(decode_channel_error(&custom_error(0, 10_000)), None);
+        assert_eq!(decode_channel_error(&TransactionError::AccountInUse), None);
+    }
+
+    #[test]
+    fn test_transaction_failed_in_bonsol_program() {
+        let signer = solana_sdk::signature::Keypair::new();
+        let instructions = vec![
+            Instruction::new_with_bytes(ID, &[0], vec![]),
+            Instruction::new_with_bytes(Pubkey::new_unique(), &[0], vec![]),
+        ];
+        let blockhash = Hash::new_unique();
+        let tx = sign_txn(&signer.pubkey(), &[&signer], &instructions, blockhash).unwrap();
+        let code = ChannelError::InvalidProof as u32;
+        assert!(matches!(
+            transaction_failed(&tx, custom_error(0, code)),
+            BonsolClientError::ProgramFailed {
+                channel_error: ChannelError::InvalidProof,
+                ..
+            }
+        ));
+        // the same code from another program is not a bonsol error
+        assert!(matches!(
+            transaction_failed(&tx, custom_error(1, code)),
+            BonsolClientError::TransactionFailed(_)
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_send_txn_with_payer() {
+        let requester = solana_sdk::signature::Keypair::new();
+        let payer = solana_sdk::signature::Keypair::new();
+        let instructions = requester_transfer(&requester.pubkey());
+        let blockhash = BonsolClient::with_rpc_client(RpcClient::new_mock("succeeds".to_string()))
+            .latest_blockhash()
+            .await
+            .unwrap();
+        let signers: [&dyn Signer; 2] = [&payer, &requester];
+        let expected = sign_txn(&payer.pubkey(), &signers, &instructions, blockhash)
+            .unwrap()
+            .signatures[0];
+        let sig = sending_client(expected)
+            .send_txn_with_payer(&requester, &payer, instructions.clone())
+            .await
+            .unwrap();
+        assert_eq!(sig, expected);
+
+        // a requester paying for itself signs once
+        let expected = sign_txn(&requester.pubkey(), &[&requester], &instructions, blockhash)
+            .unwrap()
+            .signatures[0];
+        let sig = sending_client(expected)
+            .send_txn_with_payer(&requester, &requester, instructions)
+            .await
+            .unwrap();
+        assert_eq!(sig, expected);
+    }
+
+    #[tokio::test]
+    async fn test_deploy_many_fetches_fees_once() {
+        let fee_calls = Arc::new(AtomicUsize::new(0));
+        let client = BonsolClient::with_rpc_client(RpcClient::new_sender(
+            FeeCountingSender {
+                fee_calls: fee_calls.clone(),
+            },
+            RpcClientConfig::default(),
+        ));
+        let signer = Pubkey::new_unique();
+        let specs: Vec<DeploySpec> = (0..10)
+            .map(|i| DeploySpec {
+                image_id: format!("{:064x}", i),
+                image_size: 1024,
+                program_name: format!("program-{}", i),
+                url: format!("https://example.com/program-{}", i),
+                inputs: vec![ProgramInputType::Public],
+                compute_unit_limit: None,
+                sha256: None,
+            })
+            .collect();
+        let deployments = client.deploy_many(&signer, specs).await.unwrap();
+        assert_eq!(fee_calls.load(Ordering::SeqCst), 1);
+        assert_eq!(deployments.len(), 10);
+        for ixs in deployments {
+            assert_eq!(ixs[1], ComputeBudgetInstruction::set_compute_unit_price(7));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_deploy_v1_compute_unit_limit() {
+        let client = mock_client();
+        let signer = Pubkey::new_unique();
+        let ixs = client
+            .deploy_v1(
+                &signer,
+                TEST_IMAGE_ID,
+                1024,
+                "simple",
+                "https://example.com/simple",
+                vec![ProgramInputType::Public],
+                None,
+                Some(1_400_000),
+            )
+            .await
+            .unwrap();
+        assert_eq!(
+            ixs[0],
+            ComputeBudgetInstruction::set_compute_unit_limit(1_400_000)
+        );
+        assert_eq!(ixs[1], ComputeBudgetInstruction::set_compute_unit_price(7));
+    }
+
+    #[tokio::test]
+    async fn test_execute_v1_default_compute_unit_limit() {
+        let client = mock_client();
+        let signer = Pubkey::new_unique();
+        let ixs = client
+            .execute_v1(
+                &signer,
+                TEST_IMAGE_ID,
+                "test-execution",
+                vec![InputRef::public(b"input")],
+                1000,
+                100,
+                ExecutionConfig {
+                    verify_input_hash: false,
+                    input_hash: None,
+                    forward_output: false,
+                    refund_destination: None,
+                },
+                None,
+                None,
+                None,
+            )
+            .await
+            .unwrap();
+        assert_eq!(
+            ixs[0],
+            ComputeBudgetInstruction::set_compute_unit_limit(DEFAULT_COMPUTE_UNIT_LIMIT)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_execute_v1_compute_unit_limit() {
+        let client = mock_client();
+        let signer = Pubkey::new_unique();
+        let ixs = client
+            .execute_v1(
+                &signer,
+                TEST_IMAGE_ID,
+                "test-execution",
+                vec![InputRef::public(b"input")],
+                1000,
+                100,
+                ExecutionConfig {
+                    verify_input_hash: false,
+                    input_hash: None,
+                    forward_output: false,
+                    refund_destination: None,
+                },
+                None,
+                None,
+                Some(250_000),
+            )
+            .await
+            .unwrap();
+        assert_eq!(
+            ixs[0],
+            ComputeBudgetInstruction::set_compute_unit_limit(250_000)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_execute_v1_with_payer() {
+        let client = mock_client();
+        let requester = Pubkey::new_unique();
+        let payer = Pubkey::new_unique();
+        let ixs = client
+            .execute_v1_with_payer(
+                &requester,
+                &payer,
+                TEST_IMAGE_ID,
+                "test-execution",
+                vec![InputRef::public(b"input")],
+                1000,
+                100,
+                ExecutionConfig {
+                    verify_input_hash: false,
+                    input_hash: None,
+                    forward_output: false,
+                    refund_destination: None,
+                },
+                None,
+                None,
+                None,
+            )
+            .await
+            .unwrap();
+        let accounts = &ixs[2].accounts;
+        assert_eq!(accounts[0], AccountMeta::new(requester, true));
+        assert_eq!(accounts[1], AccountMeta::new(payer, true));
+        let (execution_account, _) = execution_address(&requester, b"test-execution");
+        assert_eq!(accounts[2].pubkey, execution_account);
+        assert_ne!(
+            accounts[2].pubkey,
+            execution_address(&payer, b"test-execution").0
+        );
+    }
+
+    #[test]
+    fn test_negotiate_supported_prover_version() {
+        assert_eq!(
+            BonsolClient::negotiate_prover_version(Some(VERSION_V1_0_1)).unwrap(),
+            VERSION_V1_0_1
+        );
+        // the supported digest replaces whatever the caller passed for the same release
+        let requested = ProverVersion::V1_2_1 {
+            verifier_digest: "other",
+        };
+        assert_eq!(
+            BonsolClient::negotiate_prover_version(Some(requested)).unwrap(),
+            VERSION_V1_2_1
+        );
+    }
+
+    #[test]
+    fn test_negotiate_unsupported_prover_version() {
+        let err = BonsolClient::negotiate_prover_version(Some(ProverVersion::UnsupportedVersion))
+            .unwrap_err();
+        assert!(matches!(err, BonsolClientError::UnsupportedProverVersion(_)));
+    }
+
+    #[test]
+    fn test_negotiate_prover_version_defaults_to_most_preferred() {
+        assert_eq!(
+            BonsolClient::negotiate_prover_version(None).unwrap(),
+            BonsolClient::supported_prover_versions()[0]
+        );
+        assert_eq!(BonsolClient::supported_prover_versions()[0], ProverVersion::default());
+    }
+
+    #[tokio::test]
+    async fn test_execute_v1_unsupported_prover_version_is_an_error() {
+        let client = mock_client();
+        let signer = Pubkey::new_unique();
+        let err = client
+            .execute_v1(
+                &signer,
+                TEST_IMAGE_ID,
+                "test-execution",
+                vec![InputRef::public(b"input")],
+                1000,
+                100,
+                ExecutionConfig {
+                    verify_input_hash: false,
+                    input_hash: None,
+                    forward_output: false,
+                    refund_destination: None,
+                },
+                None,
+                Some(ProverVersion::UnsupportedVersion),
+                None,
+            )
+            .await
+            .unwrap_err();
+        assert!(matches!(err, BonsolClientError::UnsupportedProverVersion(_)));
+    }
+
+    async fn execute_with_tip(client: &BonsolClient, tip: u64) -> Result<Vec<Instruction>> {
+        client
+            .execute_v1(
+                &Pubkey::new_unique(),
+                TEST_IMAGE_ID,
+                "test-execution",
+                vec![InputRef::public(b"input")],
+                tip,
+                100,
+                ExecutionConfig {
+                    verify_input_hash: false,
+                    input_hash: None,
+                    forward_output: false,
+                    refund_destination: None,
+                },
+                None,
+                None,
+                None,
+            )
+            .await
+    }
+
+    #[tokio::test]
+    async fn test_execute_v1_tip_below_minimum() {
+        let client = mock_client().with_min_tip(1000);
+        let err = execute_with_tip(&client, 999).await.unwrap_err();
+        assert!(matches!(
+            err,
+            BonsolClientError::TipBelowMinimum {
+                tip: 999,
+                min_tip: 1000
+            }
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_execute_v1_tip_at_minimum() {
+        let client = mock_client().with_min_tip(1000);
+        assert!(execute_with_tip(&client, 1000).await.is_ok());
+        // without a minimum any tip is accepted
+        assert!(execute_with_tip(&mock_client(), 0).await.is_ok());
+    }
+
+    fn deploy_spec(url: &str) -> DeploySpec {
+        DeploySpec {
+            image_id: TEST_IMAGE_ID.to_string(),
+            image_size: 1024,
+            program_name: "test".to_string(),
+            url: url.to_string(),
+            inputs: vec![ProgramInputType::Public],
+            compute_unit_limit: None,
+            sha256: None,
+        }
+    }
+
+    fn deployment(url: &str, size: u64) -> Vec<u8> {
+        deployment_owned_by(&Pubkey::default(), url, size)
+    }
+
+    fn deployment_owned_by(owner: &Pubkey, url: &str, size: u64) -> Vec<u8> {
+        deployment_with_sha256(owner, url, size, None)
+    }
+
+    fn deployment_with_sha256(
+        owner: &Pubkey,
+        url: &str,
+        size: u64,
+        sha256: Option<Vec<u8>>,
+    ) -> Vec<u8> {
+        let mut deployment = DeployV1T::default();
+        deployment.owner = Some(owner.to_bytes().to_vec());
+        deployment.image_id = Some(TEST_IMAGE_ID.to_string());
+        deployment.program_name = Some("test".to_string());
+        deployment.url = Some(url.to_string());
+        deployment.size_ = size;
+        deployment.sha256 = sha256;
+        let mut fbb = flatbuffers::FlatBufferBuilder::new();
+        let offset = deployment.pack(&mut fbb);
+        fbb.finish(offset, None);
+        fbb.finished_data().to_vec()
+    }
+
+    #[tokio::test]
+    async fn test_ensure_deployed_already_deployed() {
+        let (client, _) = account_client(vec![Some(deployment("https://a/p", 1024))], 10);
+        let outcome = client
+            .ensure_deployed(&Pubkey::new_unique(), deploy_spec("https://a/p"))
+            .await
+            .unwrap();
+        assert!(matches!(outcome, DeployOutcome::AlreadyDeployed));
+    }
+
+    #[tokio::test]
+    async fn test_ensure_deployed_updates_url() {
+        let deployer = Pubkey::new_unique();
+        let (client, _) = account_client(
+            vec![Some(deployment_owned_by(&deployer, "https://a/p", 1024))],
+            10,
+        );
+        let outcome = client
+            .ensure_deployed(&deployer, deploy_spec("https://b/p"))
+            .await
+            .unwrap();
+        match outcome {
+            DeployOutcome::UpdateUrl(ixs) => {
+                assert_eq!(ixs.len(), 3);
+                let ix = parse_ix_data(&ixs[2].data).unwrap();
+                assert_eq!(ix.ix_type(), ChannelInstructionIxType::UpdateDeployV1);
+                let update = ix.deploy_v1_nested_flatbuffer().unwrap();
+                assert_eq!(update.url(), Some("https://b/p"));
+                assert_eq!(update.size_(), 1024);
+            }
+            _ => panic!("expected update instructions"),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_ensure_deployed_needs_update() {
+        // only the deployer can change the url
+        let (client, _) = account_client(vec![Some(deployment("https://a/p", 1024))], 10);
+        let outcome = client
+            .ensure_deployed(&Pubkey::new_unique(), deploy_spec("https://b/p"))
+            .await
+            .unwrap();
+        match outcome {
+            DeployOutcome::NeedsUpdate(existing) => {
+                assert_eq!(existing.url.as_deref(), Some("https://a/p"));
+            }
+            _ => panic!("expected the existing deployment"),
+        }
+
+        let (client, _) = account_client(vec![Some(deployment("https://a/p", 2048))], 10);
+        let outcome = client
+            .ensure_deployed(&Pubkey::new_unique(), deploy_spec("https://a/p"))
+            .await
+            .unwrap();
+        assert!(matches!(outcome, DeployOutcome::NeedsUpdate(_)));
+    }
+
+    #[tokio::test]
+    async fn test_ensure_deployed_fresh() {
+        let (client, _) = account_client(vec![None], 10);
+        let signer = Pubkey::new_unique();
+        let outcome = client
+            .ensure_deployed(&signer, deploy_spec("https://a/p"))
+            .await
+            .unwrap();
+        match outcome {
+            DeployOutcome::Deploy(ixs) => {
+                assert_eq!(ixs.len(), 3);
+                assert_eq!(ixs[2].program_id, ID);
+            }
+            _ => panic!("expected deploy instructions"),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_update_deployment_url() {
+        let deployer = Pubkey::new_unique();
+        let (client, _) = account_client(
+            vec![Some(deployment_owned_by(&deployer, "https://a/p", 1024))],
+            10,
+        );
+        let ixs = client
+            .update_deployment_url(&deployer, TEST_IMAGE_ID, "https://b/p")
+            .await
+            .unwrap();
+        assert_eq!(ixs.len(), 3);
+        let ix = parse_ix_data(&ixs[2].data).unwrap();
+        assert_eq!(ix.ix_type(), ChannelInstructionIxType::UpdateDeployV1);
+        let update = ix.deploy_v1_nested_flatbuffer().unwrap();
+        assert_eq!(update.url(), Some("https://b/p"));
+        assert_eq!(update.image_id(), Some(TEST_IMAGE_ID));
+        assert_eq!(update.size_(), 1024);
+    }
+
+    #[tokio::test]
+    async fn test_update_deployment_url_requires_deployer() {
+        let (client, _) = account_client(
+            vec![Some(deployment_owned_by(
+                &Pubkey::new_unique(),
+                "https://a/p",
+                1024,
+            ))],
+            10,
+        );
+        let err = client
+            .update_deployment_url(&Pubkey::new_unique(), TEST_IMAGE_ID, "https://b/p")
+            .await
+            .unwrap_err();
+        assert!(matches!(err, BonsolClientError::NotDeploymentOwner(_)));
+    }
+
+    #[tokio::test]
+    async fn test_watch_execution_until_completed() {
+        let (client, _) = account_client(
+            vec![
+                Some(pending_execution_request(100)),
+                Some(vec![ExitCode::Success as u8]),
+            ],
+            10,
+        );
+        let statuses = client
+            .watch_execution_with_interval(Pubkey::new_unique(), "test", Duration::from_millis(1))
+            .collect::<Vec<_>>()
+            .await;
+        assert_eq!(statuses.len(), 2);
+        assert!(matches!(statuses[0], Ok(ExecutionAccountStatus::Pending(_))));
+        assert!(matches!(
+            statuses[1],
+            Ok(ExecutionAccountStatus::Completed(ExitCode::Success))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_watch_execution_expired() {
+        let (client, _) = account_client(vec![Some(pending_execution_request(5))], 10);
+        let statuses = client
+            .watch_execution_with_interval(Pubkey::new_unique(), "test", Duration::from_millis(1))
+            .collect::<Vec<_>>()
+            .await;
+        assert_eq!(statuses.len(), 1);
+        assert!(matches!(statuses[0], Err(BonsolClientError::Expired)));
+    }
+
+    #[test]
+    fn test_websocket_url() {
+        assert_eq!(
+            websocket_url("http://localhost:8899").unwrap(),
+            "ws://localhost:8900/"
+        );
+        assert_eq!(
+            websocket_url("https://api.devnet.solana.com").unwrap(),
+            "wss://api.devnet.solana.com/"
+        );
+        assert!(matches!(
+            websocket_url("succeeds"),
+            Err(BonsolClientError::Subscription(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_account_changes_until_completed() {
+        let changes = stream::iter(vec![
+            pending_execution_request(100),
+            vec![ExitCode::Success as u8],
+            pending_execution_request(100),
+        ]);
+        let statuses = changes.map(|data| ExecutionAccountStatus::from_account_data(&data));
+        let statuses = until_terminal(statuses).collect::<Vec<_>>().await;
+        assert_eq!(statuses.len(), 2);
+        assert!(matches!(
+            statuses[0],
+            Ok(ExecutionAccountStatus::Pending(_))
+        ));
+        assert!(matches!(
+            statuses[1],
+            Ok(ExecutionAccountStatus::Completed(ExitCode::Success))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_deployment_changes() {
+        let updates = stream::iter(vec![
+            deployment("https://a/p", 1024),
+            // a closed account has no data
+            vec![],
+            deployment("https://b/p", 1024),
+        ]);
+        let deployments = deployment_changes(updates).collect::<Vec<_>>().await;
+        let urls: Vec<_> = deployments.iter().map(|d| d.url.as_deref()).collect();
+        assert_eq!(urls, vec![Some("https://a/p"), Some("https://b/p")]);
+    }
+
+    #[tokio::test]
+    async fn test_subscribe_execution_falls_back_to_polling() {
+        // the mock rpc url has no websocket counterpart
+        let (client, account_calls) = account_client(vec![Some(vec![ExitCode::Success as u8])], 10);
+        let statuses = client
+            .subscribe_execution(Pubkey::new_unique(), "test")
+            .collect::<Vec<_>>()
+            .await;
+        assert_eq!(statuses.len(), 1);
+        assert!(matches!(
+            statuses[0],
+            Ok(ExecutionAccountStatus::Completed(ExitCode::Success))
+        ));
+        assert_eq!(account_calls.load(Ordering::SeqCst), 1);
+    }
+
+    /// A websocket that accepts account subscriptions and reports when one is unsubscribed, it
+    /// never sends an account change
+    async fn mock_pubsub() -> (String, oneshot::Receiver<()>) {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("ws://{}", listener.local_addr().unwrap());
+        let (unsubscribed_tx, unsubscribed_rx) = oneshot::channel();
+        tokio::spawn(async move {
+            let (socket, _) = listener.accept().await.unwrap();
+            let mut ws = tokio_tungstenite::accept_async(socket).await.unwrap();
+            let mut unsubscribed_tx = Some(unsubscribed_tx);
+            while let Some(Ok(Message::Text(text))) = ws.next().await {
+                let request: serde_json::Value = serde_json::from_str(&text).unwrap();
+                let result = match request["method"].as_str() {
+                    Some("accountSubscribe") => json!(1),
+                    Some("accountUnsubscribe") => {
+                        if let Some(unsubscribed_tx) = unsubscribed_tx.take() {
+                            let _ = unsubscribed_tx.send(());
+                        }
+                        json!(true)
+                    }
+                    _ => continue,
+                };
+                let response = json!({"jsonrpc": "2.0", "result": result, "id": request["id"]});
+                ws.send(Message::Text(response.to_string())).await.unwrap();
+            }
+        });
+        (url, unsubscribed_rx)
+    }
+
+    #[tokio::test]
+    async fn test_dropped_subscription_unsubscribes() {
+        let (ws_url, unsubscribed) = mock_pubsub().await;
+        let client = BonsolClient::new("http://localhost:8899".to_string()).with_ws_url(ws_url);
+        let deployments = client.subscribe_deployment(TEST_IMAGE_ID).await.unwrap();
+        // without an account change, only the dropped stream can end the subscription
+        drop(deployments);
+        tokio::time::timeout(Duration::from_secs(5), unsubscribed)
+            .await
+            .expect("subscription was not dropped")
+            .unwrap();
+    }
+
+    fn execution_request_with_digest(input_digest: &[u8]) -> Vec<u8> {
+        let mut er = ExecutionRequestV1T::default();
+        er.execution_id = Some("test".to_string());
+        er.image_id = Some(TEST_IMAGE_ID.to_string());
+        er.max_block_height = 100;
+        er.input_digest = Some(input_digest.to_vec());
+        let mut fbb = flatbuffers::FlatBufferBuilder::new();
+        let offset = er.pack(&mut fbb);
+        fbb.finish(offset, None);
+        fbb.finished_data().to_vec()
+    }
+
+    fn public_inputs(data: &[&[u8]]) -> Vec<InputT> {
+        data.iter().map(|d| InputT::public(d.to_vec())).collect()
+    }
+
+    #[tokio::test]
+    async fn test_verify_input_digest() {
+        let digest = Sha256::digest(b"ab");
+        let (client, _) = account_client(vec![Some(execution_request_with_digest(&digest))], 10);
+        let requester = Pubkey::new_unique();
+        let matching = public_inputs(&[b"a", b"b"]);
+        assert!(client
+            .verify_input_digest(&requester, "test", matching)
+            .await
+            .unwrap());
+        let altered = public_inputs(&[b"a", b"c"]);
+        assert!(!client
+            .verify_input_digest(&requester, "test", altered)
+            .await
+            .unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_verify_input_digest_of_completed_execution() {
+        let (client, _) = account_client(vec![Some(vec![ExitCode::Success as u8])], 10);
+        let inputs = public_inputs(&[b"a", b"b"]);
+        assert!(matches!(
+            client
+                .verify_input_digest(&Pubkey::new_unique(), "test", inputs)
+                .await,
+            Err(BonsolClientError::MissingInputDigest(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_verify_input_digest_without_stored_digest() {
+        let (client, _) = account_client(vec![Some(pending_execution_request(100))], 10);
+        let inputs = public_inputs(&[b"a"]);
+        assert!(matches!(
+            client
+                .verify_input_digest(&Pubkey::new_unique(), "test", inputs)
+                .await,
+            Err(BonsolClientError::MissingInputDigest(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_statuses() {
+        let mut mocks = HashMap::new();
+        mocks.insert(
+            RpcRequest::GetSignatureStatuses,
+            json!({
+                "context": { "slot": 10 },
+                "value": [
+                    {
+                        "slot": 8,
+                        "confirmations": null,
+                        "status": { "Ok": null },
+                        "err": null,
+                        "confirmationStatus": "finalized"
+                    },
+                    null,
+                    {
+                        "slot": 9,
+                        "confirmations": 1,
+                        "status": { "Ok": null },
+                        "err": null,
+                        "confirmationStatus": "confirmed"
+                    },
+                    {
+                        "slot": 9,
+                        "confirmations": 1,
+                        "status": { "Err": "AccountInUse" },
+                        "err": "AccountInUse",
+                        "confirmationStatus": "confirmed"
+                    }
+                ]
+            }),
+        );
+        let client = BonsolClient::with_rpc_client(RpcClient::new_mock_with_mocks(
+            "succeeds".to_string(),
+            mocks,
+        ));
+        let sigs: Vec<Signature> = (0..4).map(|_| Signature::new_unique()).collect();
+        assert_eq!(
+            client.statuses(&sigs).await.unwrap(),
+            vec![
+                Some(TransactionStatus::Finalized),
+                None,
+                Some(TransactionStatus::Confirmed),
+                Some(TransactionStatus::Failed(TransactionError::AccountInUse)),
+            ]
+        );
     }
 }
@@ -0,0 +1,75 @@
+use solana_sdk::instruction::AccountMeta;
+use solana_sdk::pubkey::Pubkey;
+
+use crate::{BonsolClientError, Result};
+
+/// A callback extra account at a program derived address, resolved when the execution request is
+/// built so callers can describe the account instead of deriving it themselves.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PdaSpec {
+    pub program_id: Pubkey,
+    pub seeds: Vec<Vec<u8>>,
+    pub is_writable: bool,
+}
+
+impl PdaSpec {
+    pub fn new(program_id: Pubkey, seeds: Vec<Vec<u8>>, is_writable: bool) -> Self {
+        PdaSpec {
+            program_id,
+            seeds,
+            is_writable,
+        }
+    }
+
+    /// Derives the account with `find_program_address`, returning its bump alongside. Seeds that
+    /// cannot form an address, too many or too long, are an error rather than a panic.
+    pub fn resolve(&self) -> Result<(AccountMeta, u8)> {
+        let seeds: Vec<&[u8]> = self.seeds.iter().map(Vec::as_slice).collect();
+        let (pubkey, bump) = Pubkey::try_find_program_address(&seeds, &self.program_id)
+            .ok_or_else(|| {
+                BonsolClientError::InvalidPdaSpec(format!(
+                    "no address for {} seeds under program {}",
+                    seeds.len(),
+                    self.program_id
+                ))
+            })?;
+        let meta = if self.is_writable {
+            AccountMeta::new(pubkey, false)
+        } else {
+            AccountMeta::new_readonly(pubkey, false)
+        };
+        Ok((meta, bump))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_resolve_matches_find_program_address() {
+        let program_id = Pubkey::new_unique();
+        let owner = Pubkey::new_unique();
+        let seeds = vec![b"vault".to_vec(), owner.to_bytes().to_vec()];
+        let spec = PdaSpec::new(program_id, seeds, true);
+        let (meta, bump) = spec.resolve().unwrap();
+        let expected = Pubkey::find_program_address(&[b"vault", owner.as_ref()], &program_id);
+        assert_eq!((meta.pubkey, bump), expected);
+        assert!(meta.is_writable);
+        assert!(!meta.is_signer);
+
+        let readonly = PdaSpec::new(program_id, vec![b"vault".to_vec()], false);
+        assert!(!readonly.resolve().unwrap().0.is_writable);
+    }
+
+    #[test]
+    fn test_resolve_rejects_invalid_seeds() {
+        let spec = PdaSpec::new(Pubkey::new_unique(), vec![vec![0; 33]], false);
+        assert!(matches!(
+            spec.resolve(),
+            Err(BonsolClientError::InvalidPdaSpec(_))
+        ));
+        let spec = PdaSpec::new(Pubkey::new_unique(), vec![vec![0]; 16], false);
+        assert!(spec.resolve().is_err());
+    }
+}
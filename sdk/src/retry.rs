@@ -0,0 +1,149 @@
+use std::time::Duration;
+
+use rand::Rng;
+
+/// Source of the random component added to each retry delay.
+/// Implement this to make retry delays deterministic in tests.
+pub trait JitterSource {
+    /// Returns a duration in the range `[0, max]`
+    fn jitter(&self, max: Duration) -> Duration;
+}
+
+/// Uniformly distributed jitter backed by the thread local rng.
+pub struct RandomJitter;
+
+impl JitterSource for RandomJitter {
+    fn jitter(&self, max: Duration) -> Duration {
+        let max_nanos = max.as_nanos().min(u64::MAX as u128) as u64;
+        if max_nanos == 0 {
+            return Duration::ZERO;
+        }
+        Duration::from_nanos(rand::thread_rng().gen_range(0..=max_nanos))
+    }
+}
+
+/// Controls how long `send_txn` waits between status checks and before resending an expired
+/// transaction. The delay for attempt `n` is `base_delay * 2^n` capped at `max_delay`, plus up to
+/// `jitter` so clients that started together do not keep retrying in lockstep.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RetryPolicy {
+    pub base_delay: Duration,
+    pub max_delay: Duration,
+    pub jitter: Duration,
+}
+
+impl Default for RetryPolicy {
+    /// Flat 500ms between attempts, this matches the behaviour before retry policies existed
+    fn default() -> Self {
+        RetryPolicy {
+            base_delay: Duration::from_millis(500),
+            max_delay: Duration::from_millis(500),
+            jitter: Duration::ZERO,
+        }
+    }
+}
+
+impl RetryPolicy {
+    pub const fn exponential(base_delay: Duration, max_delay: Duration, jitter: Duration) -> Self {
+        RetryPolicy {
+            base_delay,
+            max_delay,
+            jitter,
+        }
+    }
+
+    pub fn delay<J: JitterSource + ?Sized>(&self, attempt: u32, jitter: &J) -> Duration {
+        let backoff = self
+            .base_delay
+            .saturating_mul(2u32.saturating_pow(attempt))
+            .min(self.max_delay);
+        backoff.saturating_add(jitter.jitter(self.jitter))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct FixedJitter(Duration);
+
+    impl JitterSource for FixedJitter {
+        fn jitter(&self, max: Duration) -> Duration {
+            self.0.min(max)
+        }
+    }
+
+    #[test]
+    fn test_default_policy_is_flat() {
+        let policy = RetryPolicy::default();
+        for attempt in 0..10 {
+            assert_eq!(
+                policy.delay(attempt, &RandomJitter),
+                Duration::from_millis(500)
+            );
+        }
+    }
+
+    #[test]
+    fn test_delays_grow_and_stay_under_max() {
+        let policy = RetryPolicy::exponential(
+            Duration::from_millis(100),
+            Duration::from_secs(5),
+            Duration::from_millis(50),
+        );
+        let jitter = FixedJitter(Duration::from_millis(50));
+        let delays: Vec<Duration> = (0..10).map(|a| policy.delay(a, &jitter)).collect();
+        assert_eq!(delays[0], Duration::from_millis(150));
+        assert_eq!(delays[1], Duration::from_millis(250));
+        assert_eq!(delays[2], Duration::from_millis(450));
+        for pair in delays.windows(2) {
+            assert!(pair[1] >= pair[0]);
+        }
+        let max = policy.max_delay + policy.jitter;
+        assert!(delays.iter().all(|d| *d <= max));
+        assert_eq!(delays[9], max);
+    }
+
+    #[test]
+    fn test_capped_delays_keep_their_jitter() {
+        let policy = RetryPolicy::exponential(
+            Duration::from_millis(100),
+            Duration::from_millis(400),
+            Duration::from_millis(50),
+        );
+        let delays: Vec<Duration> = [0, 10, 20]
+            .iter()
+            .map(|j| policy.delay(8, &FixedJitter(Duration::from_millis(*j))))
+            .collect();
+        assert_eq!(
+            delays,
+            [
+                Duration::from_millis(400),
+                Duration::from_millis(410),
+                Duration::from_millis(420)
+            ]
+        );
+    }
+
+    #[test]
+    fn test_large_attempt_saturates() {
+        let policy = RetryPolicy::exponential(
+            Duration::from_secs(1),
+            Duration::from_secs(30),
+            Duration::ZERO,
+        );
+        assert_eq!(
+            policy.delay(u32::MAX, &RandomJitter),
+            Duration::from_secs(30)
+        );
+    }
+
+    #[test]
+    fn test_random_jitter_bounded() {
+        let max = Duration::from_millis(10);
+        for _ in 0..100 {
+            assert!(RandomJitter.jitter(max) <= max);
+        }
+        assert_eq!(RandomJitter.jitter(Duration::ZERO), Duration::ZERO);
+    }
+}
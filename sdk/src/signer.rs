@@ -0,0 +1,78 @@
+use std::env;
+
+use solana_sdk::bs58;
+use solana_sdk::signature::Keypair;
+use zeroize::Zeroizing;
+
+use crate::{BonsolClientError, Result};
+
+/// Parses a base58 encoded 64 byte keypair, the format `solana-keygen` and most wallets export.
+/// Errors never include the secret.
+pub fn keypair_from_base58(secret: &str) -> Result<Keypair> {
+    let bytes = Zeroizing::new(
+        bs58::decode(secret.trim())
+            .into_vec()
+            .map_err(|_| BonsolClientError::InvalidKeypair("secret is not base58".to_string()))?,
+    );
+    if bytes.len() != 64 {
+        return Err(BonsolClientError::InvalidKeypair(format!(
+            "expected 64 bytes, got {}",
+            bytes.len()
+        )));
+    }
+    Keypair::from_bytes(&bytes)
+        .map_err(|_| BonsolClientError::InvalidKeypair("not an ed25519 keypair".to_string()))
+}
+
+/// Loads a keypair from the base58 secret held in the environment variable `var`, for
+/// deployments that inject keys instead of mounting keypair files. The copy read from the
+/// environment is zeroed once the keypair is built.
+pub fn keypair_from_env(var: &str) -> Result<Keypair> {
+    let secret = Zeroizing::new(env::var(var).map_err(|_| {
+        BonsolClientError::InvalidKeypair(format!("environment variable {} is not set", var))
+    })?);
+    keypair_from_base58(&secret)
+}
+
+#[cfg(test)]
+mod tests {
+    use solana_sdk::signer::Signer;
+
+    use super::*;
+
+    #[test]
+    fn test_keypair_from_base58() {
+        let keypair = Keypair::new();
+        let parsed = keypair_from_base58(&keypair.to_base58_string()).unwrap();
+        assert_eq!(parsed.pubkey(), keypair.pubkey());
+        assert_eq!(parsed.to_bytes(), keypair.to_bytes());
+    }
+
+    #[test]
+    fn test_keypair_from_malformed_base58() {
+        // 0, O, I and l are not in the base58 alphabet
+        assert!(matches!(
+            keypair_from_base58("0OIl"),
+            Err(BonsolClientError::InvalidKeypair(_))
+        ));
+        let short = bs58::encode([1u8; 32]).into_string();
+        let err = keypair_from_base58(&short).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Invalid keypair: expected 64 bytes, got 32"
+        );
+        assert!(!err.to_string().contains(&short));
+    }
+
+    #[test]
+    fn test_keypair_from_env() {
+        let keypair = Keypair::new();
+        env::set_var("BONSOL_TEST_KEYPAIR", keypair.to_base58_string());
+        let parsed = keypair_from_env("BONSOL_TEST_KEYPAIR").unwrap();
+        assert_eq!(parsed.pubkey(), keypair.pubkey());
+        assert!(matches!(
+            keypair_from_env("BONSOL_TEST_KEYPAIR_UNSET"),
+            Err(BonsolClientError::InvalidKeypair(_))
+        ));
+    }
+}
@@ -69,9 +69,11 @@ async fn example_sdk_no_callback_test(
                 verify_input_hash: true,
                 input_hash: Some(input_hash.as_ref()),
                 forward_output: true,
+                refund_destination: None,
             },
             None,
             None,
+            None,
         )
         .await?;
     let bh = client.get_latest_blockhash().await?;
@@ -140,6 +142,7 @@ async fn example_sdk_test(
                 verify_input_hash: true,
                 input_hash: Some(input_hash.as_ref()),
                 forward_output: true,
+                refund_destination: None,
             },
             Some(CallbackConfig {
                 program_id: example_program,
@@ -151,6 +154,7 @@ async fn example_sdk_test(
                 ],
             }),
             None,
+            None,
         )
         .await?;
     let bh = client.get_latest_blockhash().await?;